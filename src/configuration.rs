@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2020, 2021, 2023, 2024  Dirk Stolle
+    Copyright (C) 2020, 2021, 2023, 2024, 2026  Dirk Stolle
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
     the Free Software Foundation, either version 3 of the License, or
@@ -15,22 +15,132 @@
  -------------------------------------------------------------------------------
 */
 
-use std::path::PathBuf;
+use crate::data::{Country, DateRange, Metric};
+use crate::error::Error;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+mod file;
+use file::ConfigFile;
 
 //#[derive(Copy, Clone)]
 pub enum Operation
 {
-  Html(HtmlConfiguration),       // generate HTML files
-  Csv(CsvConfiguration),         // write data to CSV
-  Db(DbConfiguration),           // extract CSV data and write to DB
-  Version                        // show version
+  Html(HtmlConfiguration),         // generate HTML files
+  Csv(CsvConfiguration),           // write data to CSV
+  Db(DbConfiguration),             // extract CSV data and write to DB
+  Update(DbConfiguration),         // extract CSV data and update existing DB
+  Info(InfoConfiguration),         // show summary of a DB
+  Validate(ValidateConfiguration), // check integrity of a DB
+  Merge(MergeConfiguration),       // combine several DBs or CSV files into one DB
+  Migrate(MigrateConfiguration),   // upgrade the schema of a DB
+  Version,                         // show version
+  Help(String, String)             // show help: program name, subcommand ("" = general)
 }
 
 pub struct HtmlConfiguration
 {
   pub db_path: String,
   pub output_directory: String,
-  pub template_path: Option<PathBuf>
+  pub template_path: Option<PathBuf>,
+  pub graphs: Vec<Graph>,
+  pub selection: CountrySelection,
+  pub date_range: DateRange,
+  pub quiet: bool
+}
+
+/// kinds of graphs on the HTML page of a country
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Graph
+{
+  Incidence,        // 14-day and 7-day incidence
+  IncidenceByYear,  // 7-day incidence, one line per year
+  Cases,            // daily infections and deaths
+  Accumulated,      // accumulated infections and deaths
+  Vaccination       // share of vaccinated people, if the data contains it
+}
+
+impl Graph
+{
+  /// all graphs, in the order they appear on a country page
+  pub const ALL: [Graph; 5] = [Graph::Incidence, Graph::IncidenceByYear,
+                               Graph::Cases, Graph::Accumulated, Graph::Vaccination];
+
+  /**
+   * Parses the name of a graph as given in a configuration file.
+   *
+   * @param name   name of the graph, e. g. "incidence" or "cases"
+   * @return Returns the matching graph, if the name is known.
+   *         Returns None otherwise.
+   */
+  pub fn from_name(name: &str) -> Option<Graph>
+  {
+    match name.to_lowercase().as_str()
+    {
+      "incidence" => Some(Graph::Incidence),
+      "incidence-by-year" => Some(Graph::IncidenceByYear),
+      "cases" => Some(Graph::Cases),
+      "accumulated" => Some(Graph::Accumulated),
+      "vaccination" => Some(Graph::Vaccination),
+      _ => None
+    }
+  }
+}
+
+/// selection of the countries that appear in the generated output
+///
+/// Countries are given by their geo id (e. g. "DE") or by their ISO 3166-1
+/// alpha-3 code (e. g. "DEU"), continents by their name. All comparisons are
+/// case-insensitive.
+#[derive(Clone, Debug, Default)]
+pub struct CountrySelection
+{
+  pub countries: Vec<String>,          // countries to include
+  pub continents: Vec<String>,         // continents to include
+  pub exclude_countries: Vec<String>,  // countries to exclude
+  pub exclude_continents: Vec<String>  // continents to exclude
+}
+
+impl CountrySelection
+{
+  /**
+   * Checks whether all countries are selected.
+   *
+   * @return Returns true, if no restriction is set.
+   */
+  pub fn is_empty(&self) -> bool
+  {
+    self.countries.is_empty() && self.continents.is_empty()
+      && self.exclude_countries.is_empty() && self.exclude_continents.is_empty()
+  }
+
+  /**
+   * Checks whether a country is part of the selection.
+   *
+   * A country is selected, if it matches one of the included countries or
+   * continents (or if nothing is included explicitly), and if it matches none
+   * of the excluded countries and continents.
+   *
+   * @param country   the country to check
+   * @return Returns true, if the country is selected.
+   */
+  pub fn includes(&self, country: &Country) -> bool
+  {
+    let matches_country = |list: &[String]| {
+      list.iter().any(|c| c.eq_ignore_ascii_case(&country.geo_id)
+                          || (!country.country_code.is_empty() && c.eq_ignore_ascii_case(&country.country_code)))
+    };
+    let matches_continent = |list: &[String]| {
+      list.iter().any(|c| c.eq_ignore_ascii_case(&country.continent))
+    };
+
+    let included = (self.countries.is_empty() && self.continents.is_empty())
+      || matches_country(&self.countries)
+      || matches_continent(&self.continents);
+    included
+      && !matches_country(&self.exclude_countries)
+      && !matches_continent(&self.exclude_continents)
+  }
 }
 
 #[derive(Copy, Clone)]
@@ -40,101 +150,1589 @@ pub enum DateFormat
   LegacyEcdc  // DD/MM/YYYY
 }
 
+impl DateFormat
+{
+  /**
+   * Parses the name of a date format as given on the command line.
+   *
+   * @param name   name of the date format, e. g. "iso8601" or "ecdc"
+   * @return Returns the matching date format, if the name is known.
+   *         Returns None otherwise.
+   */
+  pub fn from_name(name: &str) -> Option<DateFormat>
+  {
+    match name.to_lowercase().as_str()
+    {
+      "iso8601" | "iso" => Some(DateFormat::Iso8601),
+      "ecdc" | "legacy-ecdc" => Some(DateFormat::LegacyEcdc),
+      _ => None
+    }
+  }
+}
+
+/// rule that decides which input wins, if several inputs of a merge contain
+/// numbers for the same country and day
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Precedence
+{
+  First, // numbers of the input given first are kept
+  Last   // numbers of the input given last are kept
+}
+
+impl Precedence
+{
+  /**
+   * Parses the name of a precedence rule as given on the command line.
+   *
+   * @param name   name of the rule, e. g. "first" or "last"
+   * @return Returns the matching rule, if the name is known.
+   *         Returns None otherwise.
+   */
+  pub fn from_name(name: &str) -> Option<Precedence>
+  {
+    match name.to_lowercase().as_str()
+    {
+      "first" => Some(Precedence::First),
+      "last" => Some(Precedence::Last),
+      _ => None
+    }
+  }
+}
+
+/// handling of missing days in the daily numbers of a country
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GapHandling
+{
+  #[default]
+  Truncate,   // days after a gap at the end of the data are removed
+  Zero,       // missing days are added with zero cases and deaths
+  Interpolate // missing days are added, and the numbers of the day after the
+              // gap are spread evenly over the gap (linear interpolation of the
+              // accumulated numbers)
+}
+
+impl GapHandling
+{
+  /**
+   * Parses the name of a gap handling as given on the command line.
+   *
+   * @param name   name of the gap handling, e. g. "truncate" or "zero"
+   * @return Returns the matching gap handling, if the name is known.
+   *         Returns None otherwise.
+   */
+  pub fn from_name(name: &str) -> Option<GapHandling>
+  {
+    match name.to_lowercase().as_str()
+    {
+      "truncate" => Some(GapHandling::Truncate),
+      "zero" | "zeros" => Some(GapHandling::Zero),
+      "interpolate" => Some(GapHandling::Interpolate),
+      _ => None
+    }
+  }
+}
+
 pub struct CsvConfiguration
 {
   pub db_path: String,
   pub csv_output_file: String,
-  pub date_format: DateFormat
+  pub date_format: DateFormat,
+  pub selection: CountrySelection,
+  pub date_range: DateRange,
+  pub metrics: Vec<Metric>, // further metrics to write as additional columns
+  pub quiet: bool
 }
 
 pub struct DbConfiguration
 {
   pub csv_input_file: String,
-  pub db_path: String
+  pub db_path: String,
+  pub quiet: bool,
+  pub mapping: Option<String>, // path of a mapping file for CSV files of unknown format
+  pub report: Option<String>,  // path of a JSON file for the import report
+  pub gaps: GapHandling        // handling of missing days
 }
 
-pub fn parse_args(args: &[String]) -> Result<Operation, String>
+pub struct InfoConfiguration
+{
+  pub db_path: String,
+  pub json: bool
+}
+
+pub struct ValidateConfiguration
+{
+  pub db_path: String,
+  pub strict: bool,
+  pub quiet: bool
+}
+
+pub struct MigrateConfiguration
+{
+  pub db_path: String,
+  pub quiet: bool
+}
+
+pub struct MergeConfiguration
+{
+  pub inputs: Vec<String>,
+  pub db_path: String,
+  pub precedence: Precedence,
+  pub quiet: bool,
+  pub gaps: GapHandling // handling of missing days in CSV inputs
+}
+
+/// command line arguments of a subcommand, split into positional arguments,
+/// options with values and flags without values
+struct SplitArguments
+{
+  positional: Vec<String>,
+  values: HashMap<String, String>,
+  flags: Vec<String>
+}
+
+impl SplitArguments
+{
+  /**
+   * Gets a value that can either be given as option or as positional argument.
+   *
+   * @param option   name of the option, e. g. "--db"
+   * @param position index of the positional argument
+   * @return Returns the value, if it was given exactly once.
+   *         Returns None, if it was not given at all.
+   *         Returns an error message, if it was given both ways.
+   */
+  fn option_or_positional(&self, option: &str, position: usize) -> Result<Option<String>, String>
+  {
+    match (self.values.get(option), self.positional.get(position))
+    {
+      (Some(_), Some(pos)) => Err(format!(
+        "The value for {option} was given twice, once as option and once as \
+         positional argument ('{pos}')!"
+      )),
+      (Some(value), None) => Ok(Some(value.clone())),
+      (None, Some(pos)) => Ok(Some(pos.clone())),
+      (None, None) => Ok(None)
+    }
+  }
+
+  /**
+   * Checks whether a flag was given.
+   *
+   * @param flag   name of the flag, e. g. "--quiet"
+   * @return Returns true, if the flag was given.
+   */
+  fn has_flag(&self, flag: &str) -> bool
+  {
+    self.flags.iter().any(|f| f == flag)
+  }
+}
+
+/**
+ * Splits the arguments of a subcommand into positional arguments, options with
+ * values and flags.
+ *
+ * @param subcommand   name of the subcommand, used in error messages
+ * @param args         the arguments after the subcommand name
+ * @param with_value   names of options that take a value, e. g. "--db"
+ * @param flags        names of options without value, e. g. "--quiet"
+ * @param aliases      short aliases and their long counterparts, e. g. ("-q", "--quiet")
+ * @return Returns the split arguments in case of success.
+ *         Returns an error message otherwise.
+ */
+fn split_arguments(subcommand: &str, args: &[String], with_value: &[&str],
+                   flags: &[&str], aliases: &[(&str, &str)]) -> Result<SplitArguments, String>
+{
+  let mut result = SplitArguments {
+    positional: Vec::new(),
+    values: HashMap::new(),
+    flags: Vec::new()
+  };
+  let mut idx = 0;
+  let mut options_ended = false;
+  while idx < args.len()
+  {
+    let arg = &args[idx];
+    idx += 1;
+    // A single dash is no option, and "--" ends the list of options.
+    if options_ended || !arg.starts_with('-') || arg == "-"
+    {
+      result.positional.push(arg.clone());
+      continue;
+    }
+    if arg == "--"
+    {
+      options_ended = true;
+      continue;
+    }
+    // Options may be given as "--name=value" or as "--name value".
+    let (name, inline_value) = match arg.split_once('=')
+    {
+      Some((name, value)) => (name, Some(value.to_string())),
+      None => (arg.as_str(), None)
+    };
+    let name = aliases
+      .iter()
+      .find(|(alias, _)| *alias == name)
+      .map_or(name, |(_, long)| *long);
+    if flags.contains(&name)
+    {
+      if inline_value.is_some()
+      {
+        return Err(format!("The option {name} of the {subcommand} subcommand does not take a value!"));
+      }
+      if !result.has_flag(name)
+      {
+        result.flags.push(name.to_string());
+      }
+      continue;
+    }
+    if !with_value.contains(&name)
+    {
+      return Err(format!("Unknown option '{arg}' for the {subcommand} subcommand!"));
+    }
+    let value = match inline_value
+    {
+      Some(value) => value,
+      None =>
+      {
+        if idx >= args.len()
+        {
+          return Err(format!("The option {name} requires a value!"));
+        }
+        idx += 1;
+        args[idx - 1].clone()
+      }
+    };
+    if value.is_empty()
+    {
+      return Err(format!("The value of option {name} must not be empty!"));
+    }
+    if result.values.insert(name.to_string(), value).is_some()
+    {
+      return Err(format!("The option {name} was given more than once!"));
+    }
+  }
+
+  Ok(result)
+}
+
+/**
+ * Checks whether the help for a subcommand was requested.
+ *
+ * @param args   the arguments after the subcommand name
+ * @return Returns true, if "--help" or "-h" occurs before any "--".
+ */
+fn help_requested(args: &[String]) -> bool
+{
+  args
+    .iter()
+    .take_while(|a| *a != "--")
+    .any(|a| a == "--help" || a == "-h")
+}
+
+/**
+ * Checks that there are no excess positional arguments.
+ *
+ * @param subcommand  name of the subcommand, used in error messages
+ * @param split       the split arguments
+ * @param max         maximum number of allowed positional arguments
+ * @return Returns an error message, if there are too many positional arguments.
+ */
+fn check_positional_count(subcommand: &str, split: &SplitArguments, max: usize) -> Result<(), String>
+{
+  if split.positional.len() > max
+  {
+    return Err(format!(
+      "Too many arguments for the {subcommand} subcommand, unexpected '{}'!",
+      split.positional[max]
+    ));
+  }
+  Ok(())
+}
+
+/**
+ * Loads the configuration file given by the --config option, if any.
+ *
+ * @param split   the split arguments
+ * @return Returns the content of the configuration file, or an empty
+ *         configuration if no file was given.
+ *         Returns an error message, if the file could not be loaded.
+ */
+fn load_config_file(split: &SplitArguments) -> Result<ConfigFile, String>
+{
+  match split.values.get("--config")
+  {
+    Some(path) => ConfigFile::load(path),
+    None => Ok(ConfigFile::default())
+  }
+}
+
+/**
+ * Splits a comma-separated list given on the command line.
+ *
+ * @param list   the list, e. g. "DE,FR,IT"
+ * @return Returns the non-empty, trimmed elements of the list.
+ */
+fn split_list(list: &str) -> Vec<String>
+{
+  list
+    .split(',')
+    .map(|s| s.trim())
+    .filter(|s| !s.is_empty())
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/**
+ * Gets the further metrics for CSV output from the command line and the
+ * configuration file. The name "all" selects all metrics.
+ *
+ * @param split   the split arguments
+ * @param file    the content of the configuration file
+ * @return Returns the selected metrics in the order of Metric::ALL.
+ *         Returns an error message, if a name is unknown.
+ */
+fn get_metrics(split: &SplitArguments, file: &ConfigFile) -> Result<Vec<Metric>, String>
+{
+  let names = match split.values.get("--metrics")
+  {
+    Some(value) => split_list(value),
+    None => file.csv.metrics.clone()
+  };
+  let mut selected = Vec::new();
+  for name in names.iter()
+  {
+    if name == "all"
+    {
+      return Ok(Metric::ALL.to_vec());
+    }
+    match Metric::from_name(name)
+    {
+      Some(metric) => selected.push(metric),
+      None =>
+      {
+        let allowed: Vec<&str> = Metric::ALL.iter().map(|m| m.name()).collect();
+        return Err(format!("Unknown metric '{name}'! Allowed values are all, {}.", allowed.join(", ")));
+      }
+    }
+  }
+  Ok(Metric::ALL.into_iter().filter(|m| selected.contains(m)).collect())
+}
+
+/**
+ * Gets the country selection from the command line and the configuration file.
+ * Lists given on the command line replace the corresponding list of the file.
+ *
+ * @param split   the split arguments
+ * @param file    the content of the configuration file
+ * @return Returns the country selection.
+ */
+fn get_selection(split: &SplitArguments, file: &ConfigFile) -> CountrySelection
+{
+  let list = |option: &str, from_file: &Vec<String>| {
+    match split.values.get(option)
+    {
+      Some(value) => split_list(value),
+      None => from_file.clone()
+    }
+  };
+  CountrySelection {
+    countries: list("--countries", &file.selection.countries),
+    continents: list("--continents", &file.selection.continents),
+    exclude_countries: list("--exclude-countries", &file.selection.exclude_countries),
+    exclude_continents: list("--exclude-continents", &file.selection.exclude_continents)
+  }
+}
+
+/**
+ * Gets the date range from the command line and the configuration file.
+ *
+ * @param split   the split arguments
+ * @param file    the content of the configuration file
+ * @return Returns the date range in case of success.
+ *         Returns an error message, if a date is invalid.
+ */
+fn get_date_range(split: &SplitArguments, file: &ConfigFile) -> Result<DateRange, String>
+{
+  let from = split.values.get("--from").or(file.period.from.as_ref()).cloned();
+  let to = split.values.get("--to").or(file.period.to.as_ref()).cloned();
+  DateRange::new(from, to).map_err(|e| e.to_string())
+}
+
+/**
+ * Determines whether informational messages shall be suppressed. The flags
+ * --quiet and --no-quiet on the command line take precedence over the setting
+ * of the configuration file.
+ *
+ * @param split   the split arguments
+ * @param file    the content of the configuration file
+ * @return Returns true, if messages shall be suppressed.
+ *         Returns an error message, if both flags are given.
+ */
+fn get_quiet(split: &SplitArguments, file: &ConfigFile) -> Result<bool, String>
+{
+  match (split.has_flag("--quiet"), split.has_flag("--no-quiet"))
+  {
+    (true, true) => Err(String::from("The options --quiet and --no-quiet cannot be combined!")),
+    (true, false) => Ok(true),
+    (false, true) => Ok(false),
+    (false, false) => Ok(file.quiet.unwrap_or(false))
+  }
+}
+
+/**
+ * Determines how missing days in the input data shall be handled. The option
+ * --gaps on the command line takes precedence over the setting of the
+ * configuration file.
+ *
+ * @param split   the split arguments
+ * @param file    the content of the configuration file
+ * @return Returns the gap handling, truncation by default.
+ *         Returns an error message, if the value is unknown.
+ */
+fn get_gaps(split: &SplitArguments, file: &ConfigFile) -> Result<GapHandling, String>
+{
+  match split.values.get("--gaps").or(file.db.gaps.as_ref())
+  {
+    None => Ok(GapHandling::default()),
+    Some(name) => GapHandling::from_name(name).ok_or_else(|| format!(
+      "Unknown gap handling '{name}'! Allowed values are truncate, zero and interpolate."
+    ))
+  }
+}
+
+/// options to select countries and dates, used by the html and csv subcommands
+const SELECTION_OPTIONS: [&str; 6] = ["--countries", "--continents",
+                                      "--exclude-countries", "--exclude-continents",
+                                      "--from", "--to"];
+
+/**
+ * Parses the arguments of the db and update subcommands, which share their
+ * options.
+ *
+ * @param subcommand   name of the subcommand, "db" or "update"
+ * @param sub_args     the arguments after the subcommand name
+ * @return Returns the configuration in case of success.
+ *         Returns an error message otherwise.
+ */
+fn parse_db_configuration(subcommand: &str, sub_args: &[String]) -> Result<DbConfiguration, String>
+{
+  let split = split_arguments(subcommand, sub_args, &["--input", "--db", "--mapping", "--report", "--gaps", "--config"],
+                              &["--quiet", "--no-quiet"], &[("-q", "--quiet"), ("-i", "--input")])?;
+  check_positional_count(subcommand, &split, 2)?;
+  let file = load_config_file(&split)?;
+  let csv_input_file = match split.option_or_positional("--input", 0)?.or(file.db.input_file.clone())
+  {
+    Some(path) => path,
+    None => return Err(String::from("Not enough command line parameters for DB operation! \
+                                     The path of the CSV file is missing."))
+  };
+  let db_path = match split.option_or_positional("--db", 1)?.or(file.database.clone())
+  {
+    Some(path) => path,
+    None => return Err(String::from("Not enough command line parameters for DB operation! \
+                                     The path of the database is missing."))
+  };
+  let gaps = get_gaps(&split, &file)?;
+  Ok(DbConfiguration {
+    csv_input_file,
+    db_path,
+    quiet: get_quiet(&split, &file)?,
+    mapping: split.values.get("--mapping").cloned().or(file.db.mapping.clone()),
+    report: split.values.get("--report").cloned().or(file.db.report.clone()),
+    gaps
+  })
+}
+
+/**
+ * Parses the command line arguments.
+ *
+ * @param args   command line arguments, including the program name
+ * @return Returns the requested operation in case of success.
+ *         Returns an Error::Configuration with a description of the problem,
+ *         if the arguments are invalid.
+ */
+pub fn parse_args(args: &[String]) -> Result<Operation, Error>
+{
+  parse_operation(args).map_err(Error::Configuration)
+}
+
+fn parse_operation(args: &[String]) -> Result<Operation, String>
 {
   if args.len() < 2
   {
     return Err(String::from("Not enough command line parameters!"));
   }
 
+  let sub_args = &args[2..];
+  if args[1] == "--help" || args[1] == "-h" || args[1] == "help"
+  {
+    // "help html" shows the help for the html subcommand.
+    let topic = sub_args.first().cloned().unwrap_or_default();
+    return Ok(Operation::Help(program_name(&args[0]), topic));
+  }
+
+  let is_subcommand = ["csv", "html", "db", "update", "info", "validate", "merge", "migrate", "version"].contains(&args[1].as_str());
+  if is_subcommand && help_requested(sub_args)
+  {
+    return Ok(Operation::Help(program_name(&args[0]), args[1].clone()));
+  }
+
   if args[1] == "csv"
   {
-    // requires three parameters:
+    // positional form:
     // 1:   csv
     // 2:   /path/to/corona.db
     // 3:   /path/to/output.csv
-    if args.len() < 4
+    let options = [&["--db", "--output", "--date-format", "--metrics", "--config"][..], &SELECTION_OPTIONS].concat();
+    let split = split_arguments("csv", sub_args, &options, &["--quiet", "--no-quiet"],
+                                &[("-q", "--quiet"), ("-o", "--output"), ("--continent", "--continents")])?;
+    check_positional_count("csv", &split, 2)?;
+    let file = load_config_file(&split)?;
+    let db_path = match split.option_or_positional("--db", 0)?.or(file.database.clone())
     {
-      return Err(String::from(
-        "Not enough command line parameters for CSV mode!"
-      ));
-    }
-
-    let db_path = args[2].clone();
-    let csv_output_file = args[3].clone();
-    let date_format = DateFormat::Iso8601; // TODO: make this an adjustable parameter
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for CSV mode! \
+                                       The path of the database is missing."))
+    };
+    let csv_output_file = match split.option_or_positional("--output", 1)?.or(file.csv.output_file.clone())
+    {
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for CSV mode! \
+                                       The path of the CSV file is missing."))
+    };
+    let date_format = match split.values.get("--date-format").or(file.csv.date_format.as_ref())
+    {
+      None => DateFormat::Iso8601,
+      Some(name) => match DateFormat::from_name(name)
+      {
+        Some(format) => format,
+        None => return Err(format!(
+          "Unknown date format '{name}'! Allowed values are iso8601 and ecdc."
+        ))
+      }
+    };
     return Ok(Operation::Csv(CsvConfiguration {
       db_path,
       csv_output_file,
-      date_format
+      date_format,
+      selection: get_selection(&split, &file),
+      date_range: get_date_range(&split, &file)?,
+      metrics: get_metrics(&split, &file)?,
+      quiet: get_quiet(&split, &file)?
     }));
   }
 
   if args[1] == "html"
   {
-    // requires three parameters, with optional fourth:
+    // positional form:
     // 1:   html
     // 2:   /path/to/corona.db
-    // 3:   /path/to/output.csv
+    // 3:   /path/to/output/directory
     // 4:   /path/to/main.tpl (optional)
-    if args.len() < 4
+    let options = [&["--db", "--output", "--template", "--config"][..], &SELECTION_OPTIONS].concat();
+    let split = split_arguments("html", sub_args, &options, &["--quiet", "--no-quiet"],
+                                &[("-q", "--quiet"), ("-o", "--output"), ("--continent", "--continents")])?;
+    check_positional_count("html", &split, 3)?;
+    let file = load_config_file(&split)?;
+    let db_path = match split.option_or_positional("--db", 0)?.or(file.database.clone())
     {
-      return Err(String::from(
-        "Not enough command line parameters for HTML generation!"
-      ));
-    }
-
-    let db_path = args[2].clone();
-    let output_directory = args[3].clone();
-    let template_path = if args.len() >= 5
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for HTML generation! \
+                                       The path of the database is missing."))
+    };
+    let output_directory = match split.option_or_positional("--output", 1)?.or(file.html.output_directory.clone())
     {
-      Some(PathBuf::from(&args[4]))
-    }
-    else
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for HTML generation! \
+                                       The output directory is missing."))
+    };
+    let template_path = split
+      .option_or_positional("--template", 2)?
+      .or(file.html.template.clone())
+      .map(PathBuf::from);
+    let graphs = match &file.html.graphs
     {
-      None
+      None => Graph::ALL.to_vec(),
+      Some(names) =>
+      {
+        let mut graphs = Vec::new();
+        for name in names.iter()
+        {
+          match Graph::from_name(name)
+          {
+            Some(graph) => graphs.push(graph),
+            None => return Err(format!(
+              "Unknown graph '{name}'! Allowed values are incidence, \
+               incidence-by-year, cases, accumulated and vaccination."
+            ))
+          }
+        }
+        graphs
+      }
     };
-    return Ok(Operation::Html(HtmlConfiguration{ db_path, output_directory, template_path }));
+    return Ok(Operation::Html(HtmlConfiguration {
+      db_path,
+      output_directory,
+      template_path,
+      graphs,
+      selection: get_selection(&split, &file),
+      date_range: get_date_range(&split, &file)?,
+      quiet: get_quiet(&split, &file)?
+    }));
   }
 
-  if args[1] == "db"
+  if args[1] == "db" || args[1] == "update"
   {
-    // requires three parameters:
-    // 1:   db
+    // positional form:
+    // 1:   db | update
     // 2:   /path/to/input.csv
     // 3:   /path/to/corona.db
-    if args.len() < 4
+    let config = parse_db_configuration(&args[1], sub_args)?;
+    return match args[1].as_str()
     {
-      return Err(String::from(
-        "Not enough command line parameters for DB operation!"
-      ));
-    }
+      "db" => Ok(Operation::Db(config)),
+      _ => Ok(Operation::Update(config))
+    };
+  }
 
-    let csv_input_file = args[2].clone();
-    let db_path = args[3].clone();
-    return Ok(Operation::Db(DbConfiguration { csv_input_file, db_path }));
+  if args[1] == "info"
+  {
+    // positional form:
+    // 1:   info
+    // 2:   /path/to/corona.db
+    let split = split_arguments("info", sub_args, &["--db", "--config"], &["--json"], &[])?;
+    check_positional_count("info", &split, 1)?;
+    let file = load_config_file(&split)?;
+    let db_path = match split.option_or_positional("--db", 0)?.or(file.database.clone())
+    {
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for info! \
+                                       The path of the database is missing."))
+    };
+    return Ok(Operation::Info(InfoConfiguration {
+      db_path,
+      json: split.has_flag("--json")
+    }));
+  }
+
+  if args[1] == "validate"
+  {
+    // positional form:
+    // 1:   validate
+    // 2:   /path/to/corona.db
+    let split = split_arguments("validate", sub_args, &["--db", "--config"],
+                                &["--strict", "--quiet", "--no-quiet"], &[("-q", "--quiet")])?;
+    check_positional_count("validate", &split, 1)?;
+    let file = load_config_file(&split)?;
+    let db_path = match split.option_or_positional("--db", 0)?.or(file.database.clone())
+    {
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for validation! \
+                                       The path of the database is missing."))
+    };
+    return Ok(Operation::Validate(ValidateConfiguration {
+      db_path,
+      strict: split.has_flag("--strict"),
+      quiet: get_quiet(&split, &file)?
+    }));
+  }
+
+  if args[1] == "migrate"
+  {
+    // positional form:
+    // 1:   migrate
+    // 2:   /path/to/corona.db
+    let split = split_arguments("migrate", sub_args, &["--db", "--config"],
+                                &["--quiet", "--no-quiet"], &[("-q", "--quiet")])?;
+    check_positional_count("migrate", &split, 1)?;
+    let file = load_config_file(&split)?;
+    let db_path = match split.option_or_positional("--db", 0)?.or(file.database.clone())
+    {
+      Some(path) => path,
+      None => return Err(String::from("Not enough command line parameters for migration! \
+                                       The path of the database is missing."))
+    };
+    return Ok(Operation::Migrate(MigrateConfiguration {
+      db_path,
+      quiet: get_quiet(&split, &file)?
+    }));
+  }
+
+  if args[1] == "merge"
+  {
+    // positional form:
+    // 1:   merge
+    // 2+:  /path/to/input.db or /path/to/input.csv (at least two)
+    let split = split_arguments("merge", sub_args, &["--output", "--prefer", "--gaps", "--config"],
+                                &["--quiet", "--no-quiet"], &[("-q", "--quiet"), ("-o", "--output")])?;
+    let file = load_config_file(&split)?;
+    if split.positional.len() < 2
+    {
+      return Err(String::from("Not enough command line parameters for merge! \
+                               At least two input files are required."));
+    }
+    let db_path = match split.values.get("--output").or(file.database.as_ref())
+    {
+      Some(path) => path.clone(),
+      None => return Err(String::from("Not enough command line parameters for merge! \
+                                       The path of the database to create is missing."))
+    };
+    let precedence = match split.values.get("--prefer")
+    {
+      None => Precedence::First,
+      Some(name) => match Precedence::from_name(name)
+      {
+        Some(rule) => rule,
+        None => return Err(format!(
+          "Unknown precedence rule '{name}'! Allowed values are first and last."
+        ))
+      }
+    };
+    return Ok(Operation::Merge(MergeConfiguration {
+      inputs: split.positional.clone(),
+      db_path,
+      precedence,
+      quiet: get_quiet(&split, &file)?,
+      gaps: get_gaps(&split, &file)?
+    }));
   }
 
   if args[1] == "version" || args[1] == "--version"
   {
+    let split = split_arguments("version", sub_args, &[], &[], &[])?;
+    check_positional_count("version", &split, 0)?;
     return Ok(Operation::Version);
   }
 
   // invalid command line parameters
-  Err(String::from(
-    "Invalid command line parameters have been specified!"
-  ))
+  Err(format!("Unknown subcommand '{}'!", args[1]))
+}
+
+/**
+ * Gets the name of the executable as it is shown in the help text.
+ *
+ * @param arg0   first command line argument, i. e. the path of the executable
+ * @return Returns the file name of the executable without its directory.
+ */
+pub fn program_name(arg0: &str) -> String
+{
+  match Path::new(arg0).file_name()
+  {
+    Some(name) => name.to_string_lossy().into_owned(),
+    None => arg0.to_string()
+  }
+}
+
+/**
+ * Gets the help text for the application or one of its subcommands.
+ *
+ * @param program     name of the executable, e. g. "corona"
+ * @param subcommand  name of the subcommand; an empty string or an unknown
+ *                    name shows the general usage information
+ * @return Returns the help text.
+ */
+pub fn help_text(program: &str, subcommand: &str) -> String
+{
+  match subcommand
+  {
+    "html" => format!(
+"Creates HTML files with graphs of the case numbers in a database.
+
+Usage: {program} html [OPTIONS] [DATABASE] [OUTPUT_DIRECTORY] [TEMPLATE]
+
+Options:
+  --db FILE               path of the SQLite database to read
+  -o, --output DIRECTORY  directory where the HTML files shall be created
+  --template FILE         path of a custom template file (optional)
+  --countries LIST        only include the given countries, e. g. DE,FR,ITA;
+                          countries are given by geo id or ISO-3 code
+  --continents LIST       only include countries of the given continents,
+                          e. g. Europe (alias: --continent)
+  --exclude-countries LIST
+                          exclude the given countries
+  --exclude-continents LIST
+                          exclude countries of the given continents
+  --from DATE             only use data from this day on (YYYY-MM-DD)
+  --to DATE               only use data up to this day (YYYY-MM-DD)
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             do not show informational messages and warnings
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Instead of the options --db, --output and --template the values can also be
+given as positional arguments in that order. Values given on the command line
+take precedence over values from the configuration file."),
+    "csv" => format!(
+"Writes the case numbers in a database to a CSV file.
+
+Usage: {program} csv [OPTIONS] [DATABASE] [CSV_FILE]
+
+Options:
+  --db FILE               path of the SQLite database to read
+  -o, --output FILE       path of the CSV file to create; must not exist yet
+  --date-format FORMAT    format of the dates in the first column, either
+                          iso8601 (YYYY-MM-DD, default) or ecdc (DD/MM/YYYY)
+  --metrics LIST          write further metrics as additional columns, e. g.
+                          icu_patients,positive_rate; all writes all of them
+  --countries LIST        only include the given countries, e. g. DE,FR,ITA;
+                          countries are given by geo id or ISO-3 code
+  --continents LIST       only include countries of the given continents,
+                          e. g. Europe (alias: --continent)
+  --exclude-countries LIST
+                          exclude the given countries
+  --exclude-continents LIST
+                          exclude countries of the given continents
+  --from DATE             only use data from this day on (YYYY-MM-DD)
+  --to DATE               only use data up to this day (YYYY-MM-DD)
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             do not show informational messages and warnings
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Instead of the options --db and --output the values can also be given as
+positional arguments in that order. Values given on the command line take
+precedence over values from the configuration file."),
+    "db" => format!(
+"Creates a SQLite database from a CSV file.
+
+Usage: {program} db [OPTIONS] [CSV_FILE] [DATABASE]
+
+Options:
+  -i, --input FILE        path of the CSV file to read; may be compressed
+                          with gzip or zip, - reads from standard input
+  --db FILE               path of the SQLite database to create; must not
+                          exist yet
+  --mapping FILE          read a CSV file of an unknown format as described
+                          by the given mapping file
+  --report FILE           write the import report (skipped rows, removed
+                          dates, unknown countries) as JSON to the given file
+  --gaps MODE             handling of missing days in OWID's compact data:
+                          truncate (remove the days after a gap at the end,
+                          default), zero (add the missing days with zeros) or
+                          interpolate (spread the numbers over the gap)
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             do not show informational messages and warnings
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Instead of the options --input and --db the values can also be given as
+positional arguments in that order. Values given on the command line take
+precedence over values from the configuration file."),
+    "update" => format!(
+"Updates an existing SQLite database with the data from a newer CSV file.
+Only new days and revised numbers are written, and incidence values and
+accumulated numbers are only recalculated for countries that have changed.
+
+Usage: {program} update [OPTIONS] [CSV_FILE] [DATABASE]
+
+Options:
+  -i, --input FILE        path of the CSV file to read; may be compressed
+                          with gzip or zip, - reads from standard input
+  --db FILE               path of the existing SQLite database to update
+  --mapping FILE          read a CSV file of an unknown format as described
+                          by the given mapping file
+  --report FILE           write the import report (skipped rows, removed
+                          dates, unknown countries) as JSON to the given file
+  --gaps MODE             handling of missing days in OWID's compact data:
+                          truncate (remove the days after a gap at the end,
+                          default), zero (add the missing days with zeros) or
+                          interpolate (spread the numbers over the gap)
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             do not show informational messages and warnings
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Instead of the options --input and --db the values can also be given as
+positional arguments in that order. Values given on the command line take
+precedence over values from the configuration file."),
+    "info" => format!(
+"Shows a summary of the content of a database.
+
+Usage: {program} info [OPTIONS] [DATABASE]
+
+Options:
+  --db FILE               path of the SQLite database to read
+  --json                  print the summary in JSON format
+  --config FILE           read settings from a TOML configuration file
+  -h, --help              show this help
+
+Instead of the option --db the path can also be given as positional argument."),
+    "validate" => format!(
+"Checks a database for inconsistent data and lists all problems.
+
+Usage: {program} validate [OPTIONS] [DATABASE]
+
+Options:
+  --db FILE               path of the SQLite database to check
+  --strict                treat warnings (gaps in dates, negative daily
+                          numbers) as errors
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             only show errors, but no warnings
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Instead of the option --db the path can also be given as positional argument.
+The exit code is non-zero, if errors were found."),
+    "merge" => format!(
+"Combines several databases or CSV files into one new database. Countries are
+matched by their ISO codes, and incidence values and accumulated numbers are
+recalculated from the merged daily numbers.
+
+Usage: {program} merge [OPTIONS] INPUT INPUT...
+
+Options:
+  -o, --output FILE       path of the SQLite database to create; must not
+                          exist yet
+  --prefer RULE           which input wins for days that are contained in
+                          several inputs: first (default) keeps the numbers of
+                          the input given first, last keeps the numbers of the
+                          input given last
+  --gaps MODE             handling of missing days in OWID's compact data of
+                          CSV inputs: truncate (default), zero or interpolate,
+                          as for the db subcommand
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             do not show informational messages and warnings
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Each INPUT can either be a SQLite database or a CSV file in one of the formats
+supported by the db subcommand. - reads a CSV file from standard input."),
+    "migrate" => format!(
+"Upgrades the schema of a database created by an older version of the
+application to the current version. Databases with the current schema are
+left unchanged.
+
+Usage: {program} migrate [OPTIONS] [DATABASE]
+
+Options:
+  --db FILE               path of the SQLite database to upgrade
+  --config FILE           read settings from a TOML configuration file
+  -q, --quiet             do not show informational messages
+  --no-quiet              show all messages, even if the configuration file
+                          enables quiet mode
+  -h, --help              show this help
+
+Instead of the option --db the path can also be given as positional argument."),
+    "version" => format!(
+"Shows the version of the application.
+
+Usage: {program} version"),
+    _ => format!(
+"Usage: {program} SUBCOMMAND [OPTIONS]
+
+Subcommands:
+  html      create HTML files with graphs from a database
+  csv       write the data of a database to a CSV file
+  db        create a database from a CSV file
+  update    update an existing database from a newer CSV file
+  info      show a summary of the content of a database
+  validate  check a database for inconsistent data
+  merge     combine several databases or CSV files into one database
+  migrate   upgrade a database created by an older version
+  version   show version information
+  help      show this help
+
+Use '{program} SUBCOMMAND --help' to get help for a subcommand, e. g.
+'{program} html --help'.")
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  /**
+   * Converts string slices to a vector of command line arguments, including
+   * the program name.
+   */
+  fn to_args(args: &[&str]) -> Vec<String>
+  {
+    let mut result = vec![String::from("corona")];
+    result.extend(args.iter().map(|s| s.to_string()));
+    result
+  }
+
+  #[test]
+  fn html_positional()
+  {
+    let op = parse_args(&to_args(&["html", "/tmp/corona.db", "/tmp/out"]));
+    match op
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert_eq!(config.output_directory, "/tmp/out");
+        assert!(config.template_path.is_none());
+        assert!(!config.quiet);
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+
+    let op = parse_args(&to_args(&["html", "/tmp/corona.db", "/tmp/out", "/tmp/my.tpl"]));
+    match op
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.template_path, Some(PathBuf::from("/tmp/my.tpl")));
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+  }
+
+  #[test]
+  fn html_options()
+  {
+    let op = parse_args(&to_args(&["html", "--quiet", "--template=/tmp/my.tpl",
+                                   "--output", "/tmp/out", "--db", "/tmp/corona.db"]));
+    match op
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert_eq!(config.output_directory, "/tmp/out");
+        assert_eq!(config.template_path, Some(PathBuf::from("/tmp/my.tpl")));
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+  }
+
+  #[test]
+  fn html_mixed()
+  {
+    let op = parse_args(&to_args(&["html", "-q", "/tmp/corona.db", "-o", "/tmp/out"]));
+    match op
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert_eq!(config.output_directory, "/tmp/out");
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+  }
+
+  #[test]
+  fn html_missing_output()
+  {
+    assert!(parse_args(&to_args(&["html", "/tmp/corona.db"])).is_err());
+    assert!(parse_args(&to_args(&["html", "--db", "/tmp/corona.db"])).is_err());
+  }
+
+  #[test]
+  fn csv_date_format()
+  {
+    let op = parse_args(&to_args(&["csv", "/tmp/corona.db", "/tmp/out.csv"]));
+    match op
+    {
+      Ok(Operation::Csv(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert_eq!(config.csv_output_file, "/tmp/out.csv");
+        assert!(matches!(config.date_format, DateFormat::Iso8601));
+      },
+      _ => panic!("Expected CSV operation!")
+    }
+
+    let op = parse_args(&to_args(&["csv", "--date-format", "ecdc", "/tmp/corona.db", "/tmp/out.csv"]));
+    match op
+    {
+      Ok(Operation::Csv(config)) =>
+      {
+        assert!(matches!(config.date_format, DateFormat::LegacyEcdc));
+      },
+      _ => panic!("Expected CSV operation!")
+    }
+
+    let op = parse_args(&to_args(&["csv", "--date-format=foo", "/tmp/corona.db", "/tmp/out.csv"]));
+    assert!(op.is_err());
+  }
+
+  #[test]
+  fn csv_metrics()
+  {
+    match parse_args(&to_args(&["csv", "/tmp/corona.db", "/tmp/out.csv"]))
+    {
+      Ok(Operation::Csv(config)) => assert!(config.metrics.is_empty()),
+      _ => panic!("Expected CSV operation!")
+    }
+    // Metrics are sorted like Metric::ALL.
+    match parse_args(&to_args(&["csv", "--metrics", "positive_rate,icu_patients", "/tmp/corona.db", "/tmp/out.csv"]))
+    {
+      Ok(Operation::Csv(config)) => assert_eq!(config.metrics, vec![Metric::IcuPatients, Metric::PositiveRate]),
+      _ => panic!("Expected CSV operation!")
+    }
+    match parse_args(&to_args(&["csv", "--metrics=all", "/tmp/corona.db", "/tmp/out.csv"]))
+    {
+      Ok(Operation::Csv(config)) => assert_eq!(config.metrics, Metric::ALL.to_vec()),
+      _ => panic!("Expected CSV operation!")
+    }
+    assert!(parse_args(&to_args(&["csv", "--metrics", "icu", "/tmp/corona.db", "/tmp/out.csv"])).is_err());
+  }
+
+  #[test]
+  fn db_options()
+  {
+    let op = parse_args(&to_args(&["db", "--db", "/tmp/corona.db", "-i", "/tmp/in.csv"]));
+    match op
+    {
+      Ok(Operation::Db(config)) =>
+      {
+        assert_eq!(config.csv_input_file, "/tmp/in.csv");
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(!config.quiet);
+        assert!(config.mapping.is_none());
+        assert!(config.report.is_none());
+        assert_eq!(config.gaps, GapHandling::Truncate);
+      },
+      _ => panic!("Expected DB operation!")
+    }
+    let op = parse_args(&to_args(&["db", "--mapping", "/tmp/rki.toml", "/tmp/in.csv", "/tmp/corona.db"]));
+    match op
+    {
+      Ok(Operation::Db(config)) => assert_eq!(config.mapping, Some(String::from("/tmp/rki.toml"))),
+      _ => panic!("Expected DB operation!")
+    }
+    let op = parse_args(&to_args(&["db", "--report", "/tmp/report.json", "/tmp/in.csv", "/tmp/corona.db"]));
+    match op
+    {
+      Ok(Operation::Db(config)) => assert_eq!(config.report, Some(String::from("/tmp/report.json"))),
+      _ => panic!("Expected DB operation!")
+    }
+    let op = parse_args(&to_args(&["db", "--gaps", "interpolate", "/tmp/in.csv", "/tmp/corona.db"]));
+    match op
+    {
+      Ok(Operation::Db(config)) => assert_eq!(config.gaps, GapHandling::Interpolate),
+      _ => panic!("Expected DB operation!")
+    }
+    assert!(parse_args(&to_args(&["db", "--gaps", "guess", "/tmp/in.csv", "/tmp/corona.db"])).is_err());
+  }
+
+  #[test]
+  fn update_options()
+  {
+    let op = parse_args(&to_args(&["update", "-q", "/tmp/in.csv", "--db", "/tmp/corona.db"]));
+    match op
+    {
+      Ok(Operation::Update(config)) =>
+      {
+        assert_eq!(config.csv_input_file, "/tmp/in.csv");
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected update operation!")
+    }
+    assert!(parse_args(&to_args(&["update", "/tmp/in.csv"])).is_err());
+  }
+
+  #[test]
+  fn db_stdin_dash_is_positional()
+  {
+    let op = parse_args(&to_args(&["db", "-", "/tmp/corona.db"]));
+    match op
+    {
+      Ok(Operation::Db(config)) => assert_eq!(config.csv_input_file, "-"),
+      _ => panic!("Expected DB operation!")
+    }
+  }
+
+  #[test]
+  fn info_options()
+  {
+    match parse_args(&to_args(&["info", "/tmp/corona.db"]))
+    {
+      Ok(Operation::Info(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(!config.json);
+      },
+      _ => panic!("Expected info operation!")
+    }
+    match parse_args(&to_args(&["info", "--json", "--db", "/tmp/corona.db"]))
+    {
+      Ok(Operation::Info(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(config.json);
+      },
+      _ => panic!("Expected info operation!")
+    }
+    assert!(parse_args(&to_args(&["info"])).is_err());
+    assert!(parse_args(&to_args(&["info", "/tmp/a.db", "/tmp/b.db"])).is_err());
+  }
+
+  #[test]
+  fn validate_options()
+  {
+    match parse_args(&to_args(&["validate", "/tmp/corona.db"]))
+    {
+      Ok(Operation::Validate(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(!config.strict);
+        assert!(!config.quiet);
+      },
+      _ => panic!("Expected validate operation!")
+    }
+    match parse_args(&to_args(&["validate", "--strict", "-q", "--db=/tmp/corona.db"]))
+    {
+      Ok(Operation::Validate(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(config.strict);
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected validate operation!")
+    }
+    assert!(parse_args(&to_args(&["validate"])).is_err());
+  }
+
+  #[test]
+  fn migrate_options()
+  {
+    match parse_args(&to_args(&["migrate", "-q", "/tmp/corona.db"]))
+    {
+      Ok(Operation::Migrate(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected migrate operation!")
+    }
+    assert!(parse_args(&to_args(&["migrate"])).is_err());
+    assert!(help_text("corona", "migrate").contains("--db FILE"));
+  }
+
+  #[test]
+  fn merge_options()
+  {
+    match parse_args(&to_args(&["merge", "-o", "/tmp/merged.db", "a.db", "b.csv", "c.db"]))
+    {
+      Ok(Operation::Merge(config)) =>
+      {
+        assert_eq!(config.inputs, vec!["a.db", "b.csv", "c.db"]);
+        assert_eq!(config.db_path, "/tmp/merged.db");
+        assert_eq!(config.precedence, Precedence::First);
+        assert!(!config.quiet);
+        assert_eq!(config.gaps, GapHandling::Truncate);
+      },
+      _ => panic!("Expected merge operation!")
+    }
+    match parse_args(&to_args(&["merge", "--prefer=last", "-q", "a.db", "b.db", "--output", "m.db"]))
+    {
+      Ok(Operation::Merge(config)) =>
+      {
+        assert_eq!(config.inputs, vec!["a.db", "b.db"]);
+        assert_eq!(config.db_path, "m.db");
+        assert_eq!(config.precedence, Precedence::Last);
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected merge operation!")
+    }
+    match parse_args(&to_args(&["merge", "--gaps", "zero", "-o", "m.db", "a.db", "-"]))
+    {
+      Ok(Operation::Merge(config)) =>
+      {
+        assert_eq!(config.inputs, vec!["a.db", "-"]);
+        assert_eq!(config.gaps, GapHandling::Zero);
+      },
+      _ => panic!("Expected merge operation!")
+    }
+    assert!(parse_args(&to_args(&["merge", "--gaps", "guess", "-o", "m.db", "a.db", "b.db"])).is_err());
+    // only one input
+    assert!(parse_args(&to_args(&["merge", "-o", "m.db", "a.db"])).is_err());
+    // no output
+    assert!(parse_args(&to_args(&["merge", "a.db", "b.db"])).is_err());
+    // unknown rule
+    let result = parse_args(&to_args(&["merge", "--prefer", "newest", "-o", "m.db", "a.db", "b.db"]));
+    assert!(matches!(result, Err(e) if e.to_string().contains("newest")));
+  }
+
+  #[test]
+  fn unknown_option()
+  {
+    let op = parse_args(&to_args(&["html", "--foo", "/tmp/corona.db", "/tmp/out"]));
+    match op
+    {
+      Err(e) => assert!(e.to_string().contains("--foo")),
+      _ => panic!("Expected error!")
+    }
+    // --date-format only exists for the csv subcommand.
+    let op = parse_args(&to_args(&["db", "--date-format", "ecdc", "/tmp/in.csv", "/tmp/corona.db"]));
+    assert!(op.is_err());
+  }
+
+  #[test]
+  fn value_given_twice()
+  {
+    let op = parse_args(&to_args(&["db", "--input", "/tmp/a.csv", "/tmp/b.csv", "/tmp/corona.db"]));
+    assert!(op.is_err());
+    let op = parse_args(&to_args(&["db", "--db=/tmp/a.db", "--db=/tmp/b.db", "/tmp/in.csv"]));
+    assert!(op.is_err());
+  }
+
+  #[test]
+  fn too_many_arguments()
+  {
+    let op = parse_args(&to_args(&["csv", "/tmp/corona.db", "/tmp/out.csv", "/tmp/extra"]));
+    assert!(op.is_err());
+  }
+
+  #[test]
+  fn help()
+  {
+    match parse_args(&to_args(&["html", "--help"]))
+    {
+      Ok(Operation::Help(program, topic)) =>
+      {
+        assert_eq!(program, "corona");
+        assert_eq!(topic, "html");
+      },
+      _ => panic!("Expected help!")
+    }
+    match parse_args(&to_args(&["csv", "/tmp/corona.db", "-h"]))
+    {
+      Ok(Operation::Help(_, topic)) => assert_eq!(topic, "csv"),
+      _ => panic!("Expected help!")
+    }
+    match parse_args(&to_args(&["--help"]))
+    {
+      Ok(Operation::Help(_, topic)) => assert!(topic.is_empty()),
+      _ => panic!("Expected help!")
+    }
+    match parse_args(&to_args(&["help", "db"]))
+    {
+      Ok(Operation::Help(_, topic)) => assert_eq!(topic, "db"),
+      _ => panic!("Expected help!")
+    }
+    // The program name is the file name of the executable.
+    let args = vec![String::from("/usr/local/bin/corona-stats"), String::from("--help")];
+    match parse_args(&args)
+    {
+      Ok(Operation::Help(program, _)) => assert_eq!(program, "corona-stats"),
+      _ => panic!("Expected help!")
+    }
+  }
+
+  #[test]
+  fn help_text_mentions_options()
+  {
+    assert!(help_text("corona", "csv").contains("--date-format"));
+    assert!(help_text("corona", "html").contains("--template"));
+    assert!(help_text("corona", "db").contains("--input"));
+    assert!(help_text("corona", "").contains("html"));
+  }
+
+  #[test]
+  fn config_file_with_overrides()
+  {
+    let dir = std::env::temp_dir().join("test_parse_args_config_file");
+    assert!(std::fs::create_dir_all(&dir).is_ok());
+    let path = dir.join("corona.toml");
+    assert!(std::fs::write(&path, "database = \"/data/corona.db\"\n\
+                                   quiet = true\n\
+                                   [html]\noutput_directory = \"/www\"\n\
+                                   graphs = [\"cases\", \"incidence\"]\n\
+                                   [csv]\noutput_file = \"/out/corona.csv\"\n\
+                                   date_format = \"ecdc\"\n\
+                                   [selection]\ncountries = [\"DE\"]\n").is_ok());
+    let path = path.to_str().unwrap();
+
+    match parse_args(&to_args(&["html", "--config", path]))
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.db_path, "/data/corona.db");
+        assert_eq!(config.output_directory, "/www");
+        assert_eq!(config.graphs, vec![Graph::Cases, Graph::Incidence]);
+        assert_eq!(config.selection.countries, vec!["DE"]);
+        assert!(config.quiet);
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+    // Command line values take precedence over the file.
+    match parse_args(&to_args(&["html", "--config", path, "-o", "/tmp/out", "/tmp/corona.db"]))
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.db_path, "/tmp/corona.db");
+        assert_eq!(config.output_directory, "/tmp/out");
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+    // Quiet mode of the file can be turned off on the command line.
+    match parse_args(&to_args(&["html", "--config", path, "--no-quiet"]))
+    {
+      Ok(Operation::Html(config)) => assert!(!config.quiet),
+      _ => panic!("Expected HTML operation!")
+    }
+    match parse_args(&to_args(&["validate", "--config", path, "--no-quiet"]))
+    {
+      Ok(Operation::Validate(config)) => assert!(!config.quiet),
+      _ => panic!("Expected validate operation!")
+    }
+    assert!(parse_args(&to_args(&["csv", "--config", path, "--quiet", "--no-quiet"])).is_err());
+    match parse_args(&to_args(&["csv", "--config", path, "--date-format", "iso8601"]))
+    {
+      Ok(Operation::Csv(config)) =>
+      {
+        assert_eq!(config.db_path, "/data/corona.db");
+        assert_eq!(config.csv_output_file, "/out/corona.csv");
+        assert!(matches!(config.date_format, DateFormat::Iso8601));
+      },
+      _ => panic!("Expected CSV operation!")
+    }
+    // The file does not contain an input file for the db subcommand.
+    assert!(parse_args(&to_args(&["db", "--config", path])).is_err());
+    // clean up
+    assert!(std::fs::remove_dir_all(dir).is_ok());
+  }
+
+  #[test]
+  fn config_file_invalid()
+  {
+    let dir = std::env::temp_dir().join("test_parse_args_config_file_invalid");
+    assert!(std::fs::create_dir_all(&dir).is_ok());
+    let path = dir.join("corona.toml");
+    assert!(std::fs::write(&path, "[html]\ngraphs = [\"pie-chart\"]\n").is_ok());
+    let path = path.to_str().unwrap();
+    let op = parse_args(&to_args(&["html", "--config", path, "/tmp/corona.db", "/tmp/out"]));
+    match op
+    {
+      Err(e) => assert!(e.to_string().contains("pie-chart")),
+      _ => panic!("Expected error!")
+    }
+    assert!(parse_args(&to_args(&["html", "--config", "/does/not/exist.toml"])).is_err());
+    // clean up
+    assert!(std::fs::remove_dir_all(dir).is_ok());
+  }
+
+  #[test]
+  fn country_selection()
+  {
+    match parse_args(&to_args(&["html", "--countries", "DE, fr,ITA", "--continent=Oceania",
+                                "--exclude-countries", "AU", "/tmp/corona.db", "/tmp/out"]))
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.selection.countries, vec!["DE", "fr", "ITA"]);
+        assert_eq!(config.selection.continents, vec!["Oceania"]);
+        assert_eq!(config.selection.exclude_countries, vec!["AU"]);
+        assert!(config.selection.exclude_continents.is_empty());
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+    match parse_args(&to_args(&["csv", "--exclude-continents", "Asia,Africa", "/tmp/corona.db", "/tmp/out.csv"]))
+    {
+      Ok(Operation::Csv(config)) =>
+      {
+        assert!(config.selection.countries.is_empty());
+        assert_eq!(config.selection.exclude_continents, vec!["Asia", "Africa"]);
+      },
+      _ => panic!("Expected CSV operation!")
+    }
+    // The db subcommand does not select countries.
+    assert!(parse_args(&to_args(&["db", "--countries", "DE", "/tmp/in.csv", "/tmp/corona.db"])).is_err());
+  }
+
+  #[test]
+  fn country_selection_includes()
+  {
+    let country = |geo_id: &str, code: &str, continent: &str| Country {
+      country_id: 1,
+      name: String::from("Test"),
+      population: 1000,
+      geo_id: geo_id.to_string(),
+      country_code: code.to_string(),
+      continent: continent.to_string()
+    };
+    let germany = country("DE", "DEU", "Europe");
+    let japan = country("JP", "JPN", "Asia");
+
+    let all = CountrySelection::default();
+    assert!(all.includes(&germany));
+    assert!(all.includes(&japan));
+
+    let selection = CountrySelection {
+      countries: vec![String::from("deu")],
+      ..Default::default()
+    };
+    assert!(selection.includes(&germany));
+    assert!(!selection.includes(&japan));
+
+    let selection = CountrySelection {
+      continents: vec![String::from("asia")],
+      ..Default::default()
+    };
+    assert!(!selection.includes(&germany));
+    assert!(selection.includes(&japan));
+
+    let selection = CountrySelection {
+      exclude_countries: vec![String::from("JP")],
+      ..Default::default()
+    };
+    assert!(selection.includes(&germany));
+    assert!(!selection.includes(&japan));
+
+    let selection = CountrySelection {
+      continents: vec![String::from("Europe")],
+      exclude_continents: vec![String::from("Europe")],
+      ..Default::default()
+    };
+    assert!(!selection.includes(&germany));
+  }
+
+  #[test]
+  fn date_range()
+  {
+    match parse_args(&to_args(&["html", "--from", "2021-10-01", "--to=2022-03-31",
+                                "/tmp/corona.db", "/tmp/out"]))
+    {
+      Ok(Operation::Html(config)) =>
+      {
+        assert_eq!(config.date_range.from, Some(String::from("2021-10-01")));
+        assert_eq!(config.date_range.to, Some(String::from("2022-03-31")));
+      },
+      _ => panic!("Expected HTML operation!")
+    }
+    match parse_args(&to_args(&["csv", "--to", "2020-12-31", "/tmp/corona.db", "/tmp/out.csv"]))
+    {
+      Ok(Operation::Csv(config)) =>
+      {
+        assert!(config.date_range.from.is_none());
+        assert_eq!(config.date_range.to, Some(String::from("2020-12-31")));
+      },
+      _ => panic!("Expected CSV operation!")
+    }
+    // invalid dates
+    assert!(parse_args(&to_args(&["csv", "--from", "31.12.2020", "/tmp/corona.db", "/tmp/out.csv"])).is_err());
+    assert!(parse_args(&to_args(&["csv", "--from", "2021-02-30", "/tmp/corona.db", "/tmp/out.csv"])).is_err());
+    // start after end
+    assert!(parse_args(&to_args(&["html", "--from", "2022-01-01", "--to", "2021-01-01",
+                                  "/tmp/corona.db", "/tmp/out"])).is_err());
+  }
+
+  #[test]
+  fn version()
+  {
+    assert!(matches!(parse_args(&to_args(&["version"])), Ok(Operation::Version)));
+    assert!(matches!(parse_args(&to_args(&["--version"])), Ok(Operation::Version)));
+    assert!(parse_args(&to_args(&["version", "--foo"])).is_err());
+  }
+
+  #[test]
+  fn unknown_subcommand()
+  {
+    assert!(parse_args(&to_args(&["foo"])).is_err());
+    assert!(parse_args(&to_args(&[])).is_err());
+  }
 }
@@ -0,0 +1,307 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use serde::Deserialize;
+use std::path::Path;
+
+/// content of a project configuration file
+///
+/// All entries are optional. Values given on the command line take precedence
+/// over the values in the file.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile
+{
+  /// path of the SQLite database
+  pub database: Option<String>,
+
+  /// whether informational messages and warnings shall be suppressed
+  pub quiet: Option<bool>,
+
+  /// settings for the html subcommand
+  #[serde(default)]
+  pub html: HtmlSection,
+
+  /// settings for the csv subcommand
+  #[serde(default)]
+  pub csv: CsvSection,
+
+  /// settings for the db subcommand
+  #[serde(default)]
+  pub db: DbSection,
+
+  /// selection of countries for html and csv output
+  #[serde(default)]
+  pub selection: SelectionSection,
+
+  /// period of time for html and csv output
+  #[serde(default)]
+  pub period: PeriodSection
+}
+
+/// settings of the [html] section
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HtmlSection
+{
+  /// directory where the HTML files are created
+  pub output_directory: Option<String>,
+
+  /// path of a custom template file
+  pub template: Option<String>,
+
+  /// names of the graphs to draw on country pages
+  pub graphs: Option<Vec<String>>
+}
+
+/// settings of the [csv] section
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CsvSection
+{
+  /// path of the CSV file to create
+  pub output_file: Option<String>,
+
+  /// date format of the CSV file, "iso8601" or "ecdc"
+  pub date_format: Option<String>,
+
+  /// further metrics to write as additional columns, e. g. "icu_patients",
+  /// or "all"
+  #[serde(default)]
+  pub metrics: Vec<String>
+}
+
+/// settings of the [db] section
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DbSection
+{
+  /// path of the CSV file to read
+  pub input_file: Option<String>,
+
+  /// path of a mapping file for CSV files of unknown format
+  pub mapping: Option<String>,
+
+  /// path of a JSON file for the import report
+  pub report: Option<String>,
+
+  /// handling of missing days: "truncate", "zero" or "interpolate"
+  pub gaps: Option<String>
+}
+
+/// settings of the [selection] section
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SelectionSection
+{
+  /// countries to include, given by their geo id or ISO-3 code
+  #[serde(default)]
+  pub countries: Vec<String>,
+
+  /// continents to include
+  #[serde(default)]
+  pub continents: Vec<String>,
+
+  /// countries to exclude, given by their geo id or ISO-3 code
+  #[serde(default)]
+  pub exclude_countries: Vec<String>,
+
+  /// continents to exclude
+  #[serde(default)]
+  pub exclude_continents: Vec<String>
+}
+
+/// settings of the [period] section
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PeriodSection
+{
+  /// first day to include, in YYYY-MM-DD format
+  pub from: Option<String>,
+
+  /// last day to include, in YYYY-MM-DD format
+  pub to: Option<String>
+}
+
+impl ConfigFile
+{
+  /**
+   * Loads a configuration file.
+   *
+   * Relative paths in the file are interpreted relative to the directory that
+   * contains the configuration file.
+   *
+   * @param path   path of the TOML file to load
+   * @return Returns the parsed configuration in case of success.
+   *         Returns an error message otherwise.
+   */
+  pub fn load(path: &str) -> Result<ConfigFile, String>
+  {
+    let content = match std::fs::read_to_string(path)
+    {
+      Ok(s) => s,
+      Err(e) => return Err(format!("Could not read configuration file {path}: {e}"))
+    };
+    let mut config = ConfigFile::parse(&content)
+      .map_err(|e| format!("Configuration file {path} is invalid: {e}"))?;
+    let base = Path::new(path).parent().unwrap_or(Path::new(""));
+    config.resolve_paths(base);
+    Ok(config)
+  }
+
+  /**
+   * Parses the content of a configuration file.
+   *
+   * @param content   TOML content of the file
+   * @return Returns the parsed configuration in case of success.
+   *         Returns an error message otherwise.
+   */
+  pub fn parse(content: &str) -> Result<ConfigFile, String>
+  {
+    toml::from_str(content).map_err(|e| e.to_string())
+  }
+
+  /**
+   * Makes relative paths relative to a base directory.
+   *
+   * @param base   directory containing the configuration file
+   */
+  fn resolve_paths(&mut self, base: &Path)
+  {
+    let resolve = |p: &mut Option<String>| {
+      if let Some(path) = p
+      {
+        // "-" stands for standard input and is kept as is.
+        if Path::new(path).is_relative() && path != "-"
+        {
+          *path = base.join(&path).to_string_lossy().into_owned();
+        }
+      }
+    };
+    resolve(&mut self.database);
+    resolve(&mut self.html.output_directory);
+    resolve(&mut self.html.template);
+    resolve(&mut self.csv.output_file);
+    resolve(&mut self.db.input_file);
+    resolve(&mut self.db.mapping);
+    resolve(&mut self.db.report);
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  #[test]
+  fn parse_full()
+  {
+    let content = r#"
+database = "corona.db"
+quiet = true
+
+[html]
+output_directory = "/var/www/corona"
+template = "main.tpl"
+graphs = ["incidence", "cases"]
+
+[csv]
+output_file = "corona.csv"
+date_format = "ecdc"
+metrics = ["icu_patients", "positive_rate"]
+
+[db]
+input_file = "compact.csv"
+mapping = "mapping.toml"
+report = "import.json"
+gaps = "interpolate"
+
+[selection]
+countries = ["DE", "FR"]
+continents = ["Europe"]
+exclude_countries = ["FRA"]
+exclude_continents = ["Asia"]
+
+[period]
+from = "2021-10-01"
+to = "2022-03-31"
+"#;
+    let config = ConfigFile::parse(content);
+    assert!(config.is_ok());
+    let config = config.unwrap();
+    assert_eq!(config.database, Some(String::from("corona.db")));
+    assert_eq!(config.quiet, Some(true));
+    assert_eq!(config.html.output_directory, Some(String::from("/var/www/corona")));
+    assert_eq!(config.html.graphs, Some(vec![String::from("incidence"), String::from("cases")]));
+    assert_eq!(config.csv.date_format, Some(String::from("ecdc")));
+    assert_eq!(config.csv.metrics, vec!["icu_patients", "positive_rate"]);
+    assert_eq!(config.db.input_file, Some(String::from("compact.csv")));
+    assert_eq!(config.db.mapping, Some(String::from("mapping.toml")));
+    assert_eq!(config.db.report, Some(String::from("import.json")));
+    assert_eq!(config.db.gaps, Some(String::from("interpolate")));
+    assert_eq!(config.selection.countries, vec!["DE", "FR"]);
+    assert_eq!(config.selection.continents, vec!["Europe"]);
+    assert_eq!(config.selection.exclude_countries, vec!["FRA"]);
+    assert_eq!(config.selection.exclude_continents, vec!["Asia"]);
+    assert_eq!(config.period.from, Some(String::from("2021-10-01")));
+    assert_eq!(config.period.to, Some(String::from("2022-03-31")));
+  }
+
+  #[test]
+  fn parse_empty()
+  {
+    let config = ConfigFile::parse("");
+    assert!(config.is_ok());
+    let config = config.unwrap();
+    assert!(config.database.is_none());
+    assert!(config.html.template.is_none());
+    assert!(config.selection.countries.is_empty());
+  }
+
+  #[test]
+  fn parse_unknown_key()
+  {
+    assert!(ConfigFile::parse("databse = \"corona.db\"").is_err());
+    assert!(ConfigFile::parse("[html]\noutput = \"out\"").is_err());
+  }
+
+  #[test]
+  fn load_resolves_relative_paths()
+  {
+    let dir = std::env::temp_dir().join("test_config_file_relative");
+    assert!(std::fs::create_dir_all(&dir).is_ok());
+    let path = dir.join("corona.toml");
+    assert!(std::fs::write(&path, "database = \"corona.db\"\n\
+                                   [html]\noutput_directory = \"/absolute/out\"\n\
+                                   [db]\ninput_file = \"-\"\n").is_ok());
+    let config = ConfigFile::load(path.to_str().unwrap());
+    assert!(config.is_ok());
+    let config = config.unwrap();
+    assert_eq!(config.database, Some(dir.join("corona.db").to_string_lossy().into_owned()));
+    assert_eq!(config.html.output_directory, Some(String::from("/absolute/out")));
+    assert_eq!(config.db.input_file, Some(String::from("-")));
+    // clean up
+    assert!(std::fs::remove_dir_all(dir).is_ok());
+  }
+
+  #[test]
+  fn load_missing_file()
+  {
+    let path = std::env::temp_dir().join("this_config_file_does_not_exist.toml");
+    assert!(ConfigFile::load(path.to_str().unwrap()).is_err());
+  }
+}
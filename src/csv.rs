@@ -17,10 +17,12 @@
 
 use super::configuration::CsvConfiguration;
 use crate::data::Country;
-use crate::data::NumbersAndIncidence;
+use crate::data::{Metric, NumbersAndIncidence};
 use crate::database::Database;
+use crate::error::Error;
 use crate::DateFormat;
 
+use std::collections::HashMap;
 use std::path::Path;
 
 pub struct Csv
@@ -35,18 +37,17 @@ impl Csv
    *
    * @config   application configuration
    * @return   Returns a Result containing the Csv object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
+   *           Returns an Error, if the configuration seems to be invalid.
    */
-  pub fn new(config: &CsvConfiguration) -> Result<Csv, String>
+  pub fn new(config: &CsvConfiguration) -> Result<Csv, Error>
   {
     if config.db_path.is_empty()
     {
-      return Err("Path to SQLite database must not be an empty string!".to_string());
+      return Err(Error::Configuration("Path to SQLite database must not be an empty string!".to_string()));
     }
     if config.csv_output_file.is_empty()
     {
-      return Err("Path of CSV file must be set to a non-empty string!".to_string());
+      return Err(Error::Configuration("Path of CSV file must be set to a non-empty string!".to_string()));
     }
 
     Ok(Csv
@@ -55,7 +56,11 @@ impl Csv
       {
         db_path: config.db_path.clone(),
         csv_output_file: config.csv_output_file.clone(),
-        date_format: config.date_format
+        date_format: config.date_format,
+        selection: config.selection.clone(),
+        date_range: config.date_range.clone(),
+        metrics: config.metrics.clone(),
+        quiet: config.quiet
       }
     })
   }
@@ -63,64 +68,43 @@ impl Csv
   /**
    * Creates the CSV file.
    *
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the CSV file could not be created.
    */
-  pub fn create_csv(&self) -> bool
+  pub fn create_csv(&self) -> Result<(), Error>
   {
     match crate::checks::sqlite_check()
     {
-      crate::checks::Status::Error(msg) =>
-      {
-        eprintln!("{msg}");
-        return false;
-      },
-      crate::checks::Status::Warn(msg) => println!("Warning: {msg}"),
+      crate::checks::Status::Error(msg) => return Err(Error::UnsupportedSqlite(msg)),
+      crate::checks::Status::Warn(msg) if !self.config.quiet => println!("Warning: {msg}"),
       _ => ()
     }
 
-    let db = Database::new(&self.config.db_path);
-    let db = match db
-    {
-      Ok(db) => db,
-      Err(_) =>
-      {
-        eprintln!(
-          "Error: Database file {} does not exist or is not readable!",
-          self.config.db_path
-        );
-        return false;
-      }
-    };
-    let countries = db.countries();
+    let db = Database::new(&self.config.db_path)?;
+    let countries = db.countries()?;
     if countries.is_empty()
     {
       // Something is wrong here, there is no data.
-      eprintln!(
-        "Error: Could not find any countries in the database {}!",
+      return Err(Error::NoData(format!(
+        "Could not find any countries in the database {}!",
         self.config.db_path
-      );
-      return false;
+      )));
+    }
+    let countries: Vec<Country> = countries
+      .into_iter()
+      .filter(|c| self.config.selection.includes(c))
+      .collect();
+    if countries.is_empty()
+    {
+      return Err(Error::NoData("None of the countries in the database matches the selection!".to_string()));
     }
     // Do not overwrite existing file.
     let path = Path::new(&self.config.csv_output_file);
     if path.exists()
     {
-      eprintln!(
-        "Error: A file or directory named {} already exists!",
-        self.config.csv_output_file
-      );
-      return false;
+      return Err(Error::AlreadyExists(path.to_path_buf()));
     }
     // Write CSV header.
-    let mut writer = match csv::Writer::from_path(&self.config.csv_output_file)
-    {
-      Ok(w) => w,
-      Err(e) =>
-      {
-        eprintln!("Error: Could not create CSV file! {e}");
-        return false;
-      }
-    };
+    let mut writer = csv::Writer::from_path(path)?;
     const CSV_HEADER: [&str; 13] = [
       "dateRep",
       "day",
@@ -136,50 +120,47 @@ impl Csv
       "Cumulative_number_for_14_days_of_COVID-19_cases_per_100000",
       "Cumulative_number_for_7_days_of_COVID-19_cases_per_100000"
     ];
-    if let Err(e) = writer.write_record(CSV_HEADER)
-    {
-      eprintln!("Error: Could not write CSV header! {e}");
-      return false;
-    }
+    let header = CSV_HEADER
+      .into_iter()
+      .chain(self.config.metrics.iter().map(|m| m.name()));
+    writer.write_record(header)?;
     let date_format = &self.config.date_format;
     // Handle each country.
     for country in countries.iter()
     {
-      let numbers = db.numbers_with_incidence(&country.country_id);
+      let numbers = db.numbers_with_incidence(&country.country_id, &self.config.date_range)?;
       if numbers.is_empty()
       {
-        eprintln!(
-          "Error while retrieving data for {} ({}) from the database!",
+        return Err(Error::NoData(format!(
+          "There is no data for {} ({}) in the database!",
           country.name, country.geo_id
-        );
-        return false;
+        )));
       }
-      for num in numbers.iter()
+      // values of the selected metrics, indexed by date
+      let mut metrics: HashMap<String, HashMap<Metric, f64>> = HashMap::new();
+      if !self.config.metrics.is_empty()
       {
-        let rec = Csv::num_to_vec(num, country, date_format);
-        let success = writer.write_record(&rec);
-        if let Err(error) = success
+        for value in db.metrics(&country.country_id, &self.config.date_range)?
         {
-          eprintln!(
-            "Error while writing data record for {} to {}! {}",
-            country.name,
-            self.config.csv_output_file,
-            error
-          );
-          return false;
+          metrics.entry(value.date).or_default().insert(value.metric, value.value);
         }
       }
-    }
-
-    match writer.flush()
-    {
-      Ok(_) => true,
-      Err(e) =>
+      for num in numbers.iter()
       {
-        eprintln!("Error: Could not flush write buffer! {e}");
-        false
+        let mut rec = Csv::num_to_vec(num, country, date_format);
+        let values = metrics.get(&num.date);
+        rec.extend(self.config.metrics.iter().map(|m| {
+          match values.and_then(|v| v.get(m))
+          {
+            Some(value) => value.to_string(),
+            None => String::new()
+          }
+        }));
+        writer.write_record(&rec)?;
       }
     }
+
+    writer.flush().map_err(|e| Error::io(path, e))
   }
 
   /**
@@ -231,6 +212,8 @@ impl Csv
 mod tests
 {
   use super::*;
+  use crate::configuration::CountrySelection;
+  use crate::data::DateRange;
 
   /**
    * Gets path to the corona.db file in data directory.
@@ -258,10 +241,14 @@ mod tests
     let config = CsvConfiguration {
       db_path: get_sqlite_db_path(),
       csv_output_file: csv_file_name.to_str().unwrap().to_string(),
-      date_format: DateFormat::LegacyEcdc
+      date_format: DateFormat::LegacyEcdc,
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      metrics: Vec::new(),
+      quiet: false
     };
     let csv = Csv::new(&config).unwrap();
-    assert!(csv.create_csv());
+    assert!(csv.create_csv().is_ok());
     // Check that CSV file exists.
     assert!(csv_file_name.exists());
     // Check contents.
@@ -298,10 +285,14 @@ mod tests
     let config = CsvConfiguration {
       db_path: get_sqlite_db_path(),
       csv_output_file: csv_file_name.to_str().unwrap().to_string(),
-      date_format: DateFormat::Iso8601
+      date_format: DateFormat::Iso8601,
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      metrics: Vec::new(),
+      quiet: false
     };
     let csv = Csv::new(&config).unwrap();
-    assert!(csv.create_csv());
+    assert!(csv.create_csv().is_ok());
     // Check that CSV file exists.
     assert!(csv_file_name.exists());
     // Check contents.
@@ -327,4 +318,90 @@ mod tests
     // clean up
     assert!(fs::remove_file(csv_file_name).is_ok());
   }
+
+  #[test]
+  fn selection_of_countries()
+  {
+    use std::env;
+    use std::fs;
+
+    let csv_file_name = env::temp_dir().join("test_csv_corona_selection.csv");
+    let config = CsvConfiguration {
+      db_path: get_sqlite_db_path(),
+      csv_output_file: csv_file_name.to_str().unwrap().to_string(),
+      date_format: DateFormat::Iso8601,
+      selection: CountrySelection {
+        countries: vec![String::from("DE"), String::from("fra")],
+        continents: vec![String::from("Oceania")],
+        exclude_countries: vec![String::from("AU")],
+        exclude_continents: Vec::new()
+      },
+      date_range: DateRange::default(),
+      metrics: Vec::new(),
+      quiet: true
+    };
+    let csv = Csv::new(&config).unwrap();
+    assert!(csv.create_csv().is_ok());
+    let contents = fs::read_to_string(&csv_file_name);
+    assert!(contents.is_ok());
+    let contents = contents.unwrap();
+    // Selected by geo id, ISO-3 code and by continent.
+    assert!(contents.lines().any(|l| l.contains(",DE,DEU,")));
+    assert!(contents.lines().any(|l| l.contains(",FR,FRA,")));
+    assert!(contents.lines().any(|l| l.contains(",NZ,NZL,")));
+    // Excluded or not selected.
+    assert!(!contents.lines().any(|l| l.contains(",AU,AUS,")));
+    assert!(!contents.lines().any(|l| l.contains(",IT,ITA,")));
+    // clean up
+    assert!(fs::remove_file(csv_file_name).is_ok());
+  }
+
+  #[test]
+  fn metrics()
+  {
+    use crate::data::{MetricValue, Numbers};
+    use std::env;
+    use std::fs;
+
+    let db_file_name = env::temp_dir().join("test_csv_metrics.db");
+    let csv_file_name = env::temp_dir().join("test_csv_metrics.csv");
+    let _ = fs::remove_file(&db_file_name);
+    let _ = fs::remove_file(&csv_file_name);
+    {
+      let db = Database::create(db_file_name.to_str().unwrap()).unwrap();
+      let id = db.get_country_id_or_insert("DE", "Germany", &83019213, "DEU", "Europe").unwrap();
+      let mut numbers = vec![
+        Numbers { date: String::from("2021-03-01"), cases: 10, deaths: 1 },
+        Numbers { date: String::from("2021-03-02"), cases: 12, deaths: 0 }
+      ];
+      crate::db::save::numbers_into_db(&db, &id, &83019213, &mut numbers).unwrap();
+      let values = vec![
+        MetricValue { date: String::from("2021-03-01"), metric: Metric::IcuPatients, value: 2912.0 },
+        MetricValue { date: String::from("2021-03-02"), metric: Metric::PositiveRate, value: 0.065 },
+        MetricValue { date: String::from("2021-03-02"), metric: Metric::NewTests, value: 1234.0 }
+      ];
+      db.insert_metrics(&id, &values).unwrap();
+    }
+
+    let config = CsvConfiguration {
+      db_path: db_file_name.to_str().unwrap().to_string(),
+      csv_output_file: csv_file_name.to_str().unwrap().to_string(),
+      date_format: DateFormat::Iso8601,
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      metrics: vec![Metric::IcuPatients, Metric::PositiveRate],
+      quiet: true
+    };
+    let csv = Csv::new(&config).unwrap();
+    assert!(csv.create_csv().is_ok());
+    let contents = fs::read_to_string(&csv_file_name).unwrap();
+    assert!(contents.lines().next().unwrap().ends_with(",icu_patients,positive_rate"));
+    assert!(contents.lines().any(|l| l.starts_with("2021-03-01,") && l.ends_with(",2912,")));
+    // Metrics that were not selected are not written.
+    assert!(contents.lines().any(|l| l.starts_with("2021-03-02,") && l.ends_with(",,0.065")));
+    // clean up
+    assert!(fs::remove_file(csv_file_name).is_ok());
+    assert!(fs::remove_file(db_file_name).is_ok());
+  }
+
 }
@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2020, 2021, 2022, 2025  Dirk Stolle
+    Copyright (C) 2020, 2021, 2022, 2025, 2026  Dirk Stolle
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
     the Free Software Foundation, either version 3 of the License, or
@@ -15,74 +15,383 @@
  -------------------------------------------------------------------------------
 */
 
+use crate::error::Error;
 use chrono::Days;
+use serde::Serialize;
 
 /// struct that contains data of a single country
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Country
 {
+  /// id of the country in the database
   pub country_id: i32,
+  /// name of the country, e. g. "Germany"
   pub name: String,
+  /// number of inhabitants, -1 if unknown
   pub population: i32,
+  /// two-letter geo id of the country, e. g. "DE"
   pub geo_id: String,
+  /// three-letter ISO code of the country, e. g. "DEU", may be empty
   pub country_code: String,
+  /// name of the continent, e. g. "Europe"
   pub continent: String
 }
 
+/// struct that contains the amount of data for a single country
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CountryStatistics
+{
+  /// the country
+  #[serde(flatten)]
+  pub country: Country,
+  /// first date with numbers in YYYY-MM-DD format, None if there is no data
+  pub first_date: Option<String>,
+  /// last date with numbers in YYYY-MM-DD format, None if there is no data
+  pub last_date: Option<String>,
+  /// number of rows in the covid19 table
+  pub rows: i64
+}
+
+/// struct that contains a single row of the covid19 table
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyRecord
+{
+  /// id of the country in the database
+  pub country_id: i32,
+  /// date in YYYY-MM-DD format
+  pub date: String,
+  /// number of new cases on that day
+  pub cases: i32,
+  /// number of new deaths on that day
+  pub deaths: i32,
+  /// sum of all cases up to and including that day, if calculated
+  pub total_cases: Option<i32>,
+  /// sum of all deaths up to and including that day, if calculated
+  pub total_deaths: Option<i32>
+}
+
+/// interval in which a country reports its numbers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Granularity
+{
+  /// new numbers are reported every day
+  #[default]
+  Daily,
+  /// new numbers are reported once per week, the days in between have no
+  /// numbers or zero numbers
+  Weekly
+}
+
+impl Granularity
+{
+  /**
+   * Gets the name of the granularity as stored in the database.
+   *
+   * @return Returns "daily" or "weekly".
+   */
+  pub fn name(&self) -> &'static str
+  {
+    match self
+    {
+      Granularity::Daily => "daily",
+      Granularity::Weekly => "weekly"
+    }
+  }
+
+  /**
+   * Gets the granularity from its name.
+   *
+   * @param name   the name, e. g. "weekly"
+   * @return Returns the matching granularity, if any.
+   */
+  pub fn from_name(name: &str) -> Option<Granularity>
+  {
+    match name
+    {
+      "daily" => Some(Granularity::Daily),
+      "weekly" => Some(Granularity::Weekly),
+      _ => None
+    }
+  }
+}
+
+/// further daily metric of a country besides cases and deaths, e. g. the
+/// number of patients in intensive care units
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Metric
+{
+  /// number of COVID-19 patients in hospital on that day
+  HospPatients,
+  /// number of COVID-19 patients in intensive care units on that day
+  IcuPatients,
+  /// number of new admissions to hospitals in the week up to that day
+  WeeklyHospAdmissions,
+  /// number of new admissions to intensive care units in the week up to that day
+  WeeklyIcuAdmissions,
+  /// number of new tests on that day
+  NewTests,
+  /// share of positive tests, from 0.0 to 1.0
+  PositiveRate,
+  /// total number of vaccination doses administered so far
+  TotalVaccinations,
+  /// total number of people that received at least one dose so far
+  PeopleVaccinated,
+  /// total number of people that received all doses of the initial protocol
+  PeopleFullyVaccinated,
+  /// total number of booster doses administered so far
+  TotalBoosters,
+  /// number of new vaccination doses administered on that day
+  NewVaccinations,
+  /// excess mortality in percent, i. e. the difference between reported and
+  /// projected deaths relative to the projected deaths
+  ExcessMortality
+}
+
+impl Metric
+{
+  /// all metrics, in the order of the columns in CSV output
+  pub const ALL: [Metric; 12] = [
+    Metric::HospPatients,
+    Metric::IcuPatients,
+    Metric::WeeklyHospAdmissions,
+    Metric::WeeklyIcuAdmissions,
+    Metric::NewTests,
+    Metric::PositiveRate,
+    Metric::TotalVaccinations,
+    Metric::PeopleVaccinated,
+    Metric::PeopleFullyVaccinated,
+    Metric::TotalBoosters,
+    Metric::NewVaccinations,
+    Metric::ExcessMortality
+  ];
+
+  /**
+   * Gets the name of the metric as stored in the database. It is the same as
+   * the name of the column in Our World In Data's CSV files.
+   *
+   * @return Returns the name, e. g. "icu_patients".
+   */
+  pub fn name(&self) -> &'static str
+  {
+    match self
+    {
+      Metric::HospPatients => "hosp_patients",
+      Metric::IcuPatients => "icu_patients",
+      Metric::WeeklyHospAdmissions => "weekly_hosp_admissions",
+      Metric::WeeklyIcuAdmissions => "weekly_icu_admissions",
+      Metric::NewTests => "new_tests",
+      Metric::PositiveRate => "positive_rate",
+      Metric::TotalVaccinations => "total_vaccinations",
+      Metric::PeopleVaccinated => "people_vaccinated",
+      Metric::PeopleFullyVaccinated => "people_fully_vaccinated",
+      Metric::TotalBoosters => "total_boosters",
+      Metric::NewVaccinations => "new_vaccinations",
+      Metric::ExcessMortality => "excess_mortality"
+    }
+  }
+
+  /**
+   * Gets the metric from its name.
+   *
+   * @param name   the name, e. g. "icu_patients"
+   * @return Returns the matching metric, if any.
+   */
+  pub fn from_name(name: &str) -> Option<Metric>
+  {
+    Metric::ALL.iter().find(|m| m.name() == name).copied()
+  }
+}
+
+/// value of a metric for a single day in a single country
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricValue
+{
+  /// date in YYYY-MM-DD format
+  pub date: String,
+  /// the metric
+  pub metric: Metric,
+  /// value of the metric on that day
+  pub value: f64
+}
+
+/// record of a single import of data into the database
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Provenance
+{
+  /// name of the source format, e. g. "OWID"
+  pub format: String,
+  /// attribution of the data source, e. g. "Our World in Data"
+  pub attribution: String,
+  /// name of the input file, or "-" for the standard input; names of several
+  /// files, e. g. of the JHU data, are separated by commas
+  pub file: String,
+  /// SHA-256 hash of the data read from the input file as hexadecimal string,
+  /// one per file in the same order as the names, if known
+  pub sha256: Option<String>,
+  /// time of the import in RFC 3339 format, e. g. "2026-10-18T12:34:56Z"
+  pub imported_at: String,
+  /// version of the application that performed the import
+  pub version: String,
+  /// number of data rows that have been read from the input
+  pub rows_read: i64,
+  /// number of data rows that have been imported
+  pub rows_kept: i64,
+  /// number of data rows that have been skipped
+  pub rows_skipped: i64
+}
+
 /// struct to hold the case numbers for a single day in a single country
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Numbers
 {
+  /// date in YYYY-MM-DD format
   pub date: String,
+  /// number of new cases on that day
   pub cases: i32,
+  /// number of new deaths on that day
   pub deaths: i32
 }
 
 /// struct to hold the case numbers and 14-day incidence as well as
 /// 7-day incidence for a single day in a single country
+#[derive(Clone, Debug, PartialEq)]
 pub struct NumbersAndIncidence
 {
+  /// date in YYYY-MM-DD format
   pub date: String,
+  /// number of new cases on that day
   pub cases: i32,
+  /// number of new deaths on that day
   pub deaths: i32,
+  /// 14-day incidence per 100000 inhabitants, if known
   pub incidence_14d: Option<f64>,
+  /// 7-day incidence per 100000 inhabitants, if known
   pub incidence_7d: Option<f64>
 }
 
 /// struct to hold the case numbers and 14-day incidence as well as 7-day
 /// incidence and sum of cases and deaths so far for a single day in a single
 /// country
+#[derive(Clone, Debug, PartialEq)]
 pub struct NumbersAndIncidenceAndTotals
 {
+  /// date in YYYY-MM-DD format
   pub date: String,
+  /// number of new cases on that day
   pub cases: i32,
+  /// number of new deaths on that day
   pub deaths: i32,
+  /// 14-day incidence per 100000 inhabitants, if known
   pub incidence_14d: Option<f64>,
+  /// 7-day incidence per 100000 inhabitants, if known
   pub incidence_7d: Option<f64>,
+  /// sum of all cases up to and including that day
   pub total_cases: i32,
+  /// sum of all deaths up to and including that day
   pub total_deaths: i32
 }
 
 /// struct to hold 7-day incidence value for a single day in a single country
+#[derive(Clone, Debug, PartialEq)]
 pub struct Incidence7
 {
+  /// date in YYYY-MM-DD format
   pub date: String,
+  /// 7-day incidence per 100000 inhabitants
   pub incidence_7d: f64
 }
 
 /// struct to hold 14-day incidence value for a single day in a single country
+#[derive(Clone, Debug, PartialEq)]
 pub struct Incidence14
 {
+  /// date in YYYY-MM-DD format
   pub date: String,
+  /// 14-day incidence per 100000 inhabitants
   pub incidence_14d: f64
 }
 
 /// struct to hold incidence value for a single day in a single country
+#[derive(Clone, Debug, PartialEq)]
 pub struct IncidenceWithDay
 {
-  pub day_of_year: u16, // day of year, range [1;366]
+  /// day of year, between 1 and 366
+  pub day_of_year: u16,
+  /// 7-day incidence per 100000 inhabitants
   pub incidence: f64
 }
 
+/// period of time that database queries are restricted to
+///
+/// ```
+/// use corona::DateRange;
+///
+/// let range = DateRange::new(Some(String::from("2020-10-01")), None).unwrap();
+/// assert_eq!(range.lower(), "2020-10-01");
+/// assert!(DateRange::new(Some(String::from("2020-13-01")), None).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DateRange
+{
+  /// first day (inclusive) in YYYY-MM-DD format, None = no limit
+  pub from: Option<String>,
+  /// last day (inclusive) in YYYY-MM-DD format, None = no limit
+  pub to: Option<String>
+}
+
+impl DateRange
+{
+  /**
+   * Creates a new date range after checking the given dates.
+   *
+   * @param from   first day of the range in YYYY-MM-DD format, if any
+   * @param to     last day of the range in YYYY-MM-DD format, if any
+   * @return Returns the date range in case of success.
+   *         Returns an Error::Configuration, if a date is invalid or if the
+   *         first day is after the last day.
+   */
+  pub fn new(from: Option<String>, to: Option<String>) -> Result<DateRange, Error>
+  {
+    for date in from.iter().chain(to.iter())
+    {
+      if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err()
+      {
+        return Err(Error::Configuration(format!("'{date}' is not a valid date in the format YYYY-MM-DD!")));
+      }
+    }
+    if let (Some(f), Some(t)) = (&from, &to)
+    {
+      if f > t
+      {
+        return Err(Error::Configuration(format!("The start date {f} is after the end date {t}!")));
+      }
+    }
+    Ok(DateRange { from, to })
+  }
+
+  /**
+   * Gets the lower bound for use in SQL queries.
+   *
+   * @return Returns the first day of the range, or a value that is lower than
+   *         any date, if there is no lower limit.
+   */
+  pub fn lower(&self) -> &str
+  {
+    self.from.as_deref().unwrap_or("0000-00-00")
+  }
+
+  /**
+   * Gets the upper bound for use in SQL queries.
+   *
+   * @return Returns the last day of the range, or a value that is greater
+   *         than any date, if there is no upper limit.
+   */
+  pub fn upper(&self) -> &str
+  {
+    self.to.as_deref().unwrap_or("9999-99-99")
+  }
+}
+
 /**
  * Calculates the 14-day incidence and 7-day incidence for a slice of Numbers
  * that are pre-sorted by date in ascending order.
@@ -92,7 +401,7 @@ pub struct IncidenceWithDay
  * @param population  number of inhabitants in the country
  * @return Returns the numbers with 14-day and 7-day incidence calculated.
  */
-pub fn calculate_incidence(numbers: &[Numbers], population: &i32) -> Vec<NumbersAndIncidence>
+pub(crate) fn calculate_incidence(numbers: &[Numbers], population: &i32) -> Vec<NumbersAndIncidence>
 {
   let len = numbers.len();
   let mut result: Vec<NumbersAndIncidence> = Vec::with_capacity(len);
@@ -197,6 +506,177 @@ pub fn calculate_incidence(numbers: &[Numbers], population: &i32) -> Vec<Numbers
   result
 }
 
+/// number of the most recent reports that have to be one or more weeks apart
+/// from each other, before numbers are considered to be reported weekly
+const WEEKLY_REPORTS: usize = 6;
+
+/**
+ * Detects whether the numbers of a country are reported daily or weekly. Only
+ * the most recent reports (i. e. days with new cases) are checked, because
+ * many countries switched from daily to weekly reporting at some point.
+ *
+ * @param numbers   pairs of date in YYYY-MM-DD format and number of new cases
+ * @return Returns Granularity::Weekly, if the last reports are each a multiple
+ *         of seven days apart. Returns Granularity::Daily otherwise.
+ */
+pub(crate) fn detect_granularity<'a, I>(numbers: I) -> Granularity
+where
+  I: IntoIterator<Item = (&'a str, i32)>
+{
+  match weekly_since(numbers)
+  {
+    Some(_) => Granularity::Weekly,
+    None => Granularity::Daily
+  }
+}
+
+/**
+ * Finds the day from which on the numbers of a country are reported weekly.
+ * Reports (i. e. days with new cases) that are a multiple of seven days apart
+ * are counted backwards from the most recent one, so that countries which
+ * switched from daily to weekly reporting keep their daily numbers before.
+ *
+ * @param numbers   pairs of date in YYYY-MM-DD format and number of new cases
+ * @return Returns the day after the last report before the weekly reports, or
+ *         the first day of the numbers, if they have been reported weekly from
+ *         the start. Returns None, if the last reports are not weekly.
+ */
+pub(crate) fn weekly_since<'a, I>(numbers: I) -> Option<chrono::NaiveDate>
+where
+  I: IntoIterator<Item = (&'a str, i32)>
+{
+  let mut first_day: Option<chrono::NaiveDate> = None;
+  let mut reports: Vec<chrono::NaiveDate> = Vec::new();
+  for (date, cases) in numbers.into_iter()
+  {
+    let date = match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+    {
+      Ok(d) => d,
+      Err(_) => continue
+    };
+    first_day = Some(first_day.map_or(date, |first| first.min(date)));
+    if cases != 0
+    {
+      reports.push(date);
+    }
+  }
+  if reports.len() < WEEKLY_REPORTS
+  {
+    return None;
+  }
+  reports.sort_unstable();
+  let is_week_apart = |earlier: chrono::NaiveDate, later: chrono::NaiveDate| {
+    let days = (later - earlier).num_days();
+    days > 0 && days % 7 == 0
+  };
+  let mut start = reports.len() - 1;
+  while start > 0 && is_week_apart(reports[start - 1], reports[start])
+  {
+    start -= 1;
+  }
+  if reports.len() - start < WEEKLY_REPORTS
+  {
+    return None;
+  }
+  match start
+  {
+    0 => first_day,
+    _ => reports[start - 1].succ_opt()
+  }
+}
+
+/**
+ * Calculates the 14-day incidence and 7-day incidence for a slice of Numbers
+ * that are reported weekly. Unlike calculate_incidence() the sums are taken
+ * over periods of 7 and 14 days, not over 7 and 14 elements, so that days
+ * without any element do not distort the result.
+ *
+ * @param number   slice of numbers, has to be sorted by date in ascending order,
+ *                 but may contain gaps
+ * @param population  number of inhabitants in the country
+ * @return Returns the numbers with 14-day and 7-day incidence calculated.
+ */
+pub(crate) fn calculate_incidence_weekly(numbers: &[Numbers], population: &i32) -> Vec<NumbersAndIncidence>
+{
+  let dates: Vec<Option<chrono::NaiveDate>> = numbers
+    .iter()
+    .map(|n| chrono::NaiveDate::parse_from_str(&n.date, "%Y-%m-%d").ok())
+    .collect();
+  let first_date = dates.iter().flatten().next().copied();
+  let mut result: Vec<NumbersAndIncidence> = Vec::with_capacity(numbers.len());
+  for (idx, elem) in numbers.iter().enumerate()
+  {
+    // Sums the cases of the elements within the given number of days up to
+    // and including the current day, if there is data for the whole period.
+    let sum_of_days = |days: u64| -> Option<i32> {
+      let date = dates[idx]?;
+      let start = date.checked_sub_days(Days::new(days - 1))?;
+      if *population <= 0 || first_date? > start
+      {
+        return None;
+      }
+      let sum = (0..=idx)
+        .rev()
+        .take_while(|i| dates[*i].is_some_and(|d| d >= start))
+        .map(|i| numbers[i].cases)
+        .sum();
+      Some(sum)
+    };
+    result.push(NumbersAndIncidence {
+      date: elem.date.clone(),
+      cases: elem.cases,
+      deaths: elem.deaths,
+      incidence_14d: sum_of_days(14).map(|sum| sum as f64 * 100_000.0 / *population as f64),
+      incidence_7d: sum_of_days(7).map(|sum| sum as f64 * 100_000.0 / *population as f64)
+    });
+  }
+
+  result
+}
+
+/**
+ * Adds up the numbers of each week. The weeks end on the weekday of the last
+ * element, which is usually the day on which the numbers are reported.
+ *
+ * @param numbers   slice of numbers, has to be sorted by date in ascending order
+ * @return Returns one element per week, dated to the last day of the week.
+ */
+pub(crate) fn weekly_sums(numbers: &[Numbers]) -> Vec<Numbers>
+{
+  let last_date = match numbers.last().and_then(|n| chrono::NaiveDate::parse_from_str(&n.date, "%Y-%m-%d").ok())
+  {
+    Some(date) => date,
+    None => return numbers.to_vec()
+  };
+  let mut result: Vec<Numbers> = Vec::new();
+  for elem in numbers.iter()
+  {
+    let date = match chrono::NaiveDate::parse_from_str(&elem.date, "%Y-%m-%d")
+    {
+      Ok(d) => d,
+      Err(_) => continue
+    };
+    // Days until the end of the week, counted backwards from the last date.
+    let remaining = (last_date - date).num_days().rem_euclid(7) as u64;
+    let end_of_week = match date.checked_add_days(Days::new(remaining))
+    {
+      Some(d) => d.format("%Y-%m-%d").to_string(),
+      None => continue
+    };
+    match result.last_mut()
+    {
+      Some(week) if week.date == end_of_week =>
+      {
+        week.cases += elem.cases;
+        week.deaths += elem.deaths;
+      },
+      _ => result.push(Numbers { date: end_of_week, cases: elem.cases, deaths: elem.deaths })
+    }
+  }
+
+  result
+}
+
 /**
  * Calculates the total cases and death numbers for a slice of NumbersAndIncidence
  * that are pre-sorted by date in ascending order.
@@ -205,7 +685,7 @@ pub fn calculate_incidence(numbers: &[Numbers], population: &i32) -> Vec<Numbers
  *                 without any gaps
  * @return Returns the numbers with totals calculated.
  */
-pub fn calculate_totals(numbers: &[NumbersAndIncidence]) -> Vec<NumbersAndIncidenceAndTotals>
+pub(crate) fn calculate_totals(numbers: &[NumbersAndIncidence]) -> Vec<NumbersAndIncidenceAndTotals>
 {
   let len = numbers.len();
   let mut result: Vec<NumbersAndIncidenceAndTotals> = Vec::with_capacity(len);
@@ -243,15 +723,17 @@ pub fn calculate_totals(numbers: &[NumbersAndIncidence]) -> Vec<NumbersAndIncide
  * Removes excess dates from a vector of Numbers.
  *
  * @param numbers  vector of numbers
- * @return None. Removes the elements in place.
+ * @return Returns the dates of the removed elements in ascending order.
+ *         Removes the elements in place.
  */
-pub fn cutoff_non_contiguous_dates(numbers: &mut Vec<Numbers>)
+pub(crate) fn cutoff_non_contiguous_dates(numbers: &mut Vec<Numbers>) -> Vec<String>
 {
   numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+  let mut removed = Vec::new();
   let len = numbers.len();
   if len < 2
   {
-    return;
+    return removed;
   }
   for i in (1..len).rev() {
     let old_date = match chrono::NaiveDate::parse_from_str(&numbers[i].date, "%Y-%m-%d")
@@ -271,13 +753,76 @@ pub fn cutoff_non_contiguous_dates(numbers: &mut Vec<Numbers>)
     };
     if calculated_prev_date != prev_date
     {
-      numbers.pop();
+      removed.extend(numbers.pop().map(|n| n.date));
     }
     else
     {
-      return;
+      break;
     }
   }
+  removed.reverse();
+  removed
+}
+
+/**
+ * Adds the missing dates to a vector of Numbers, so that the dates are
+ * contiguous.
+ *
+ * @param numbers      vector of numbers
+ * @param interpolate  If true, the numbers of the first day after a gap are
+ *                     spread evenly over the gap and that day, which is a
+ *                     linear interpolation of the accumulated numbers. If
+ *                     false, the added days get zero cases and deaths.
+ * @return Returns the added dates in ascending order. Adds the elements in
+ *         place.
+ */
+pub(crate) fn fill_gaps(numbers: &mut Vec<Numbers>, interpolate: bool) -> Vec<String>
+{
+  numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+  let mut added = Vec::new();
+  let mut result: Vec<Numbers> = Vec::with_capacity(numbers.len());
+  let mut previous: Option<chrono::NaiveDate> = None;
+  for mut current in numbers.drain(..)
+  {
+    let date = match chrono::NaiveDate::parse_from_str(&current.date, "%Y-%m-%d")
+    {
+      Ok(d) => d,
+      Err(_) =>
+      {
+        result.push(current);
+        continue;
+      }
+    };
+    let missing = previous.map(|prev| (date - prev).num_days() - 1).unwrap_or(0);
+    if let (Some(prev), true) = (previous, missing > 0)
+    {
+      // The gap and the current day share the numbers of the current day.
+      let days = missing + 1;
+      let (cases, deaths) = match interpolate
+      {
+        true => (i64::from(current.cases), i64::from(current.deaths)),
+        false => (0, 0)
+      };
+      // share of the first i days of the gap and the current day
+      let share = |total: i64, i: i64| total * i / days;
+      for i in 1..=missing
+      {
+        let day = (prev + Days::new(i as u64)).format("%Y-%m-%d").to_string();
+        added.push(day.clone());
+        result.push(Numbers {
+          date: day,
+          cases: (share(cases, i) - share(cases, i - 1)) as i32,
+          deaths: (share(deaths, i) - share(deaths, i - 1)) as i32
+        });
+      }
+      current.cases -= share(cases, missing) as i32;
+      current.deaths -= share(deaths, missing) as i32;
+    }
+    previous = Some(date);
+    result.push(current);
+  }
+  *numbers = result;
+  added
 }
 
 #[cfg(test)]
@@ -985,8 +1530,9 @@ mod tests
       Numbers { date: "2020-04-30".to_string(), cases: 10, deaths: 0 },
     ];
 
-    cutoff_non_contiguous_dates(&mut some_numbers);
+    let removed = cutoff_non_contiguous_dates(&mut some_numbers);
 
+    assert_eq!(removed, vec!["2020-01-30", "2020-02-28", "2020-03-31", "2020-04-30"]);
     assert_eq!(6, some_numbers.len());
     assert!(some_numbers.iter().find(|x| x.date == "2020-01-01").is_some());
     assert!(some_numbers.iter().find(|x| x.date == "2020-01-06").is_some());
@@ -996,6 +1542,61 @@ mod tests
     assert!(some_numbers.iter().find(|x| x.date == "2020-04-30").is_none());
   }
 
+  #[test]
+  fn fill_gaps_with_zeros()
+  {
+    let mut some_numbers = vec![
+      Numbers { date: "2020-01-04".to_string(), cases: 9, deaths: 3 },
+      Numbers { date: "2020-01-01".to_string(), cases: 1, deaths: 1 },
+      Numbers { date: "2020-01-02".to_string(), cases: 3, deaths: 1 },
+    ];
+
+    let added = fill_gaps(&mut some_numbers, false);
+
+    assert_eq!(added, vec!["2020-01-03"]);
+    let dates: Vec<&str> = some_numbers.iter().map(|n| n.date.as_str()).collect();
+    assert_eq!(dates, vec!["2020-01-01", "2020-01-02", "2020-01-03", "2020-01-04"]);
+    assert_eq!((some_numbers[2].cases, some_numbers[2].deaths), (0, 0));
+    assert_eq!((some_numbers[3].cases, some_numbers[3].deaths), (9, 3));
+  }
+
+  #[test]
+  fn fill_gaps_with_interpolation()
+  {
+    let mut some_numbers = vec![
+      Numbers { date: "2020-01-01".to_string(), cases: 1, deaths: 1 },
+      Numbers { date: "2020-01-02".to_string(), cases: 3, deaths: 1 },
+      Numbers { date: "2020-01-06".to_string(), cases: 10, deaths: 2 },
+      Numbers { date: "2020-01-07".to_string(), cases: 4, deaths: 0 },
+    ];
+
+    let added = fill_gaps(&mut some_numbers, true);
+
+    assert_eq!(added, vec!["2020-01-03", "2020-01-04", "2020-01-05"]);
+    assert_eq!(7, some_numbers.len());
+    let cases: Vec<i32> = some_numbers.iter().map(|n| n.cases).collect();
+    let deaths: Vec<i32> = some_numbers.iter().map(|n| n.deaths).collect();
+    // The ten cases of 2020-01-06 are spread over four days.
+    assert_eq!(cases, vec![1, 3, 2, 3, 2, 3, 4]);
+    assert_eq!(deaths, vec![1, 1, 0, 1, 0, 1, 0]);
+    // Sums stay the same.
+    assert_eq!(cases.iter().sum::<i32>(), 18);
+    assert_eq!(deaths.iter().sum::<i32>(), 4);
+  }
+
+  #[test]
+  fn fill_gaps_not_required()
+  {
+    let mut some_numbers = vec![
+      Numbers { date: "2020-01-01".to_string(), cases: 1, deaths: 1 },
+      Numbers { date: "2020-01-02".to_string(), cases: 3, deaths: 1 },
+    ];
+    let expected = some_numbers.clone();
+
+    assert!(fill_gaps(&mut some_numbers, true).is_empty());
+    assert_eq!(some_numbers, expected);
+  }
+
   #[test]
   fn no_cutoff_required()
   {
@@ -1009,8 +1610,9 @@ mod tests
       Numbers { date: "2020-01-07".to_string(), cases: 0, deaths: 13 },
     ];
 
-    cutoff_non_contiguous_dates(&mut some_numbers);
+    let removed = cutoff_non_contiguous_dates(&mut some_numbers);
 
+    assert!(removed.is_empty());
     assert_eq!(7, some_numbers.len());
     assert!(some_numbers.iter().find(|x| x.date == "2020-01-01").is_some());
     assert!(some_numbers.iter().find(|x| x.date == "2020-01-02").is_some());
@@ -1019,4 +1621,148 @@ mod tests
     assert!(some_numbers.iter().find(|x| x.date == "2020-01-05").is_some());
     assert!(some_numbers.iter().find(|x| x.date == "2020-01-06").is_some());
   }
+
+  #[test]
+  fn date_range()
+  {
+    let all = DateRange::default();
+    assert!(all.from.is_none());
+    assert!(all.to.is_none());
+    assert!(all.lower() < "1900-01-01");
+    assert!(all.upper() > "2999-12-31");
+
+    let range = DateRange::new(Some(String::from("2021-10-01")), Some(String::from("2022-03-31")));
+    assert!(range.is_ok());
+    let range = range.unwrap();
+    assert_eq!(range.lower(), "2021-10-01");
+    assert_eq!(range.upper(), "2022-03-31");
+
+    // same day for start and end is allowed
+    assert!(DateRange::new(Some(String::from("2021-10-01")), Some(String::from("2021-10-01"))).is_ok());
+    // start after end
+    assert!(DateRange::new(Some(String::from("2022-03-31")), Some(String::from("2021-10-01"))).is_err());
+    // invalid dates
+    assert!(DateRange::new(Some(String::from("2021-13-01")), None).is_err());
+    assert!(DateRange::new(None, Some(String::from("01/10/2021"))).is_err());
+  }
+
+  /**
+   * Creates numbers without deaths.
+   */
+  fn numbers(data: &[(&str, i32)]) -> Vec<Numbers>
+  {
+    data
+      .iter()
+      .map(|(date, cases)| Numbers { date: date.to_string(), cases: *cases, deaths: 0 })
+      .collect()
+  }
+
+  #[test]
+  fn granularity_names()
+  {
+    for granularity in [Granularity::Daily, Granularity::Weekly]
+    {
+      assert_eq!(Granularity::from_name(granularity.name()), Some(granularity));
+    }
+    assert_eq!(Granularity::from_name("monthly"), None);
+    assert_eq!(Granularity::default(), Granularity::Daily);
+  }
+
+  #[test]
+  fn detect_granularity_daily()
+  {
+    let days: Vec<String> = (1..=28).map(|d| format!("2022-02-{d:0>2}")).collect();
+    let daily: Vec<(&str, i32)> = days.iter().map(|d| (d.as_str(), 5)).collect();
+    assert_eq!(detect_granularity(daily), Granularity::Daily);
+    // too few reports
+    assert_eq!(detect_granularity(vec![("2022-02-06", 3), ("2022-02-13", 4)]), Granularity::Daily);
+  }
+
+  #[test]
+  fn detect_granularity_weekly()
+  {
+    // daily at first, weekly (on Sundays) later, with zeros in between
+    let mut data: Vec<(String, i32)> = (1..=10).map(|d| (format!("2022-01-{d:0>2}"), 10)).collect();
+    for day in 11..=31
+    {
+      data.push((format!("2022-01-{day:0>2}"), 0));
+    }
+    for day in 1..=28
+    {
+      let cases = if day % 7 == 6 { 70 } else { 0 };
+      data.push((format!("2022-02-{day:0>2}"), cases));
+    }
+    // one week without a report
+    data.push((String::from("2022-03-13"), 70));
+    data.push((String::from("2022-03-20"), 70));
+    let weekly = data.iter().map(|(d, c)| (d.as_str(), *c));
+    assert_eq!(detect_granularity(weekly), Granularity::Weekly);
+  }
+
+  #[test]
+  fn weekly_since()
+  {
+    let date = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+    // daily until 2022-01-10, weekly (on Sundays) from 2022-02-06 on
+    let mut data: Vec<(String, i32)> = (1..=10).map(|d| (format!("2022-01-{d:0>2}"), 10)).collect();
+    for day in [6, 13, 20, 27]
+    {
+      data.push((format!("2022-02-{day:0>2}"), 70));
+    }
+    data.push((String::from("2022-03-13"), 70));
+    data.push((String::from("2022-03-20"), 70));
+    let switched = data.iter().map(|(d, c)| (d.as_str(), *c));
+    assert_eq!(super::weekly_since(switched), date("2022-01-11"));
+    // weekly from the start, beginning with a day without cases
+    let weekly = data[10..].iter().map(|(d, c)| (d.as_str(), *c)).chain([("2022-02-01", 0)]);
+    assert_eq!(super::weekly_since(weekly), date("2022-02-01"));
+    // daily reports only
+    let daily = data[..10].iter().map(|(d, c)| (d.as_str(), *c));
+    assert_eq!(super::weekly_since(daily), None);
+    // too few weekly reports after the switch
+    let few = data[..15].iter().map(|(d, c)| (d.as_str(), *c));
+    assert_eq!(super::weekly_since(few), None);
+  }
+
+  #[test]
+  fn calculate_incidence_weekly_gaps()
+  {
+    // one row per week
+    let data = numbers(&[("2022-01-02", 100), ("2022-01-09", 200), ("2022-01-16", 300), ("2022-01-30", 50)]);
+    let result = calculate_incidence_weekly(&data, &100_000);
+    assert_eq!(result.len(), 4);
+    // first week is incomplete
+    assert_eq!(result[0].incidence_7d, None);
+    assert_eq!(result[0].incidence_14d, None);
+    assert_eq!(result[1].incidence_7d, Some(200.0));
+    assert_eq!(result[1].incidence_14d, None);
+    assert_eq!(result[2].incidence_7d, Some(300.0));
+    assert_eq!(result[2].incidence_14d, Some(500.0));
+    // the week before was not reported
+    assert_eq!(result[3].incidence_7d, Some(50.0));
+    assert_eq!(result[3].incidence_14d, Some(50.0));
+
+    // unknown population
+    let result = calculate_incidence_weekly(&data, &-1);
+    assert!(result.iter().all(|n| n.incidence_7d.is_none() && n.incidence_14d.is_none()));
+  }
+
+  #[test]
+  fn calculate_incidence_weekly_equals_daily_without_gaps()
+  {
+    let data: Vec<Numbers> = (1..=31)
+      .map(|d| Numbers { date: format!("2022-03-{d:0>2}"), cases: d * 3, deaths: 0 })
+      .collect();
+    assert_eq!(calculate_incidence_weekly(&data, &12345), calculate_incidence(&data, &12345));
+  }
+
+  #[test]
+  fn weekly_sums()
+  {
+    // last day is a Sunday
+    let data = numbers(&[("2022-01-01", 1), ("2022-01-02", 2), ("2022-01-03", 4), ("2022-01-09", 8), ("2022-01-16", 16)]);
+    let weeks = super::weekly_sums(&data);
+    assert_eq!(weeks, numbers(&[("2022-01-02", 3), ("2022-01-09", 12), ("2022-01-16", 16)]));
+    assert!(super::weekly_sums(&[]).is_empty());
+  }
 }
@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2020, 2021, 2022, 2025  Dirk Stolle
+    Copyright (C) 2020, 2021, 2022, 2025, 2026  Dirk Stolle
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
     the Free Software Foundation, either version 3 of the License, or
@@ -15,20 +15,42 @@
  -------------------------------------------------------------------------------
 */
 
+use crate::data;
 use crate::data::{
-  Country, Incidence14, Incidence7, IncidenceWithDay,
-  Numbers, NumbersAndIncidence, NumbersAndIncidenceAndTotals
+  Country, CountryStatistics, DailyRecord, DateRange, Granularity, Incidence14, Incidence7, IncidenceWithDay,
+  Metric, MetricValue, Numbers, NumbersAndIncidence, NumbersAndIncidenceAndTotals, Provenance
 };
+use crate::error::Error;
 use std::collections::HashMap;
 use std::path::Path;
 
 use rusqlite::{named_params, params, Connection};
 
+/// connection to an SQLite database containing Corona case numbers
+///
+/// ```
+/// use corona::{Database, DateRange};
+///
+/// let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/corona-ecdc-2020-12-14.db");
+/// let db = Database::new(path).unwrap();
+/// let germany = db.countries()
+///   .unwrap()
+///   .into_iter()
+///   .find(|c| c.geo_id == "DE")
+///   .unwrap();
+/// let range = DateRange::new(Some(String::from("2020-12-01")), None).unwrap();
+/// let numbers = db.numbers_with_incidence(&germany.country_id, &range).unwrap();
+/// assert!(!numbers.is_empty());
+/// assert!(numbers.iter().all(|n| n.date.as_str() >= "2020-12-01"));
+/// ```
 pub struct Database
 {
   conn: rusqlite::Connection
 }
 
+/// function that performs a single step of the schema migration
+type MigrationStep = fn(&Database, &bool) -> Result<(), Error>;
+
 impl Database
 {
   /**
@@ -36,47 +58,61 @@ impl Database
    *
    * @param     db_path   path of the SQLite database file to open
    * @return    Returns a Result containing the Database object, if successful.
-   *            Returns a string with an error message, if the database could
-   *            not be opened.
+   *            Returns an Error, if the database could not be opened.
    */
-  pub fn new(db_path: &str) -> Result<Database, String>
+  pub fn new(db_path: &str) -> Result<Database, Error>
   {
     let path = Path::new(db_path);
     if !path.is_file() || !path.exists()
     {
-      return Err(String::from("Database file does not exist!"));
+      return Err(Error::io(path, std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "Database file does not exist or is not readable!"
+      )));
     }
 
-    let conn = Connection::open(db_path);
-    match conn
+    let conn = Connection::open(db_path)?;
+    let db = Database { conn };
+    let version = db.schema_version()?;
+    if version > Database::SCHEMA_VERSION
     {
-      Err(_e) => Err(String::from("Failed to open database!")),
-      Ok(c) => Ok(Database { conn: c })
+      return Err(Error::UnsupportedSchema { version, supported: Database::SCHEMA_VERSION });
     }
+    Ok(db)
   }
 
   /**
-   * Creates a new SQLite database containing empty tables.
+   * Creates a new SQLite database containing empty tables of the current
+   * schema version.
    *
    * @param db_path   path of the SQLite database file to create
    * @return Returns a Result containing the Database object, if successful.
-   *         Returns a string with an error message, if the database could not
-   *         be created.
+   *         Returns an Error, if the database could not be created.
    */
-  pub fn create(db_path: &str) -> Result<Database, String>
+  pub fn create(db_path: &str) -> Result<Database, Error>
   {
     let path = Path::new(db_path);
     if path.exists()
     {
-      return Err(format!("The file or directory {db_path} already exists!"));
+      return Err(Error::AlreadyExists(path.to_path_buf()));
     }
     // Create database file.
-    let conn = match Connection::open(db_path)
-    {
-      Err(_e) => return Err(String::from("Failed to create database!")),
-      Ok(c) => c
-    };
-    // Create tables.
+    let conn = Connection::open(db_path)?;
+    Database::create_first_schema(&conn)?;
+    let db = Database { conn };
+    db.migrate(&false)?;
+    Ok(db)
+  }
+
+  /**
+   * Creates the tables as they were in the first version of the schema, i. e.
+   * schema version zero. The migrations do the rest.
+   *
+   * @param conn   connection to an empty database
+   * @return Returns an Error, if the tables could not be created.
+   */
+  fn create_first_schema(conn: &Connection) -> Result<(), Error>
+  {
     let sql = "CREATE TABLE country (\n  \
                countryId INTEGER PRIMARY KEY NOT NULL,\n  \
                name TEXT NOT NULL,\n  \
@@ -85,10 +121,7 @@ impl Database
                countryCode TEXT,\n  \
                continent TEXT\n\
                );";
-    if let Err(e) = conn.execute(sql, params![])
-    {
-      return Err(format!("Could not create table country in database. {e}"));
-    }
+    conn.execute(sql, params![])?;
     let sql = "CREATE TABLE covid19 (\n  \
                countryId INTEGER NOT NULL,\n  \
                date TEXT,\n  \
@@ -97,49 +130,455 @@ impl Database
                incidence14 REAL,\n  \
                incidence7 REAL\n\
                );";
-    if let Err(e) = conn.execute(sql, params![])
+    conn.execute(sql, params![])?;
+    Ok(())
+  }
+
+  /// current version of the database schema
+  pub const SCHEMA_VERSION: i64 = 6;
+
+  /// steps to upgrade the schema, in ascending order: the version after the
+  /// step, a short description and the function that performs the step
+  ///
+  /// Every step checks whether its change is already present, because
+  /// databases created by older versions of the application have no recorded
+  /// schema version, but may already contain some of the changes.
+  const MIGRATIONS: [(i64, &'static str, MigrationStep); 6] = [
+    (1, "accumulated numbers of cases and deaths", Database::calculate_total_numbers),
+    (2, "table for meta information", Database::create_meta_table),
+    (3, "reporting granularity of countries", Database::add_granularity_column),
+    (4, "table for further metrics", Database::create_metric_table),
+    (5, "indexes and uniqueness of days", Database::create_indexes),
+    (6, "table for the provenance of the data", Database::create_provenance_table)
+  ];
+
+  /**
+   * Gets the version of the database schema.
+   *
+   * @return Returns the version recorded in the table of meta information.
+   *         Returns zero, if no version is recorded, i. e. for databases
+   *         created by older versions of the application.
+   */
+  pub fn schema_version(&self) -> Result<i64, Error>
+  {
+    match self.meta("schema_version")?
     {
-      return Err(format!("Could not create table covid19 in database. {e}"));
+      None => Ok(0),
+      Some(value) => value.parse().map_err(|_| Error::InvalidData(format!(
+        "The database contains the invalid schema version '{value}'!"
+      )))
     }
-    Ok(Database { conn })
   }
 
   /**
-   * Lists all countries in the database.
+   * Checks whether the database has the current schema, as required before
+   * data is written into it.
    *
-   * @return Returns a vector of country data.
+   * @return Returns an Error, if the schema is outdated.
    */
-  pub fn countries(&self) -> Vec<Country>
+  pub fn require_current_schema(&self) -> Result<(), Error>
   {
-    let sql = "SELECT countryId, name, population, geoId, countryCode, continent FROM country \
-               WHERE geoId <> '' AND continent <> 'Other' \
-               ORDER BY name ASC;";
-    let mut stmt = match self.conn.prepare(sql)
+    let version = self.schema_version()?;
+    if version != Database::SCHEMA_VERSION
     {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let country_iter = stmt.query_map(params![], |row| {
-      Ok(Country {
-        country_id: row.get(0).unwrap_or(-1),
-        name: row.get(1).unwrap_or_else(|_| String::new()),
-        population: row.get(2).unwrap_or(-1),
-        geo_id: row.get(3).unwrap_or_else(|_| String::new()),
-        country_code: row.get(4).unwrap_or_else(|_| String::new()),
-        continent: row.get(5).unwrap_or_else(|_| String::new())
-      })
-    });
-    let country_iter = match country_iter
+      return Err(Error::UnsupportedSchema { version, supported: Database::SCHEMA_VERSION });
+    }
+    Ok(())
+  }
+
+  /**
+   * Upgrades the database schema to the current version. All steps are
+   * performed in a single transaction, so the database is left unchanged, if
+   * one of them fails.
+   *
+   * @param verbose  whether the progress should be printed to standard output
+   * @return Returns a tuple of the previous and the current schema version in
+   *         case of success. Both are equal, if there was nothing to do.
+   *         Returns an Error, if the migration failed.
+   */
+  pub fn migrate(&self, verbose: &bool) -> Result<(i64, i64), Error>
+  {
+    let previous = self.schema_version()?;
+    if previous > Database::SCHEMA_VERSION
+    {
+      return Err(Error::UnsupportedSchema { version: previous, supported: Database::SCHEMA_VERSION });
+    }
+    if previous == Database::SCHEMA_VERSION
+    {
+      return Ok((previous, previous));
+    }
+    self.batch("BEGIN TRANSACTION;")?;
+    let result = self.run_migrations(previous, verbose);
+    match result
+    {
+      Ok(()) => self.batch("COMMIT;")?,
+      Err(e) =>
+      {
+        let _ = self.batch("ROLLBACK;");
+        return Err(e);
+      }
+    }
+    Ok((previous, Database::SCHEMA_VERSION))
+  }
+
+  /**
+   * Performs all migration steps after a given version and records the new
+   * version.
+   *
+   * @param previous   the current version of the schema
+   * @param verbose    whether the progress should be printed to standard output
+   * @return Returns an Error, if a step failed.
+   */
+  fn run_migrations(&self, previous: i64, verbose: &bool) -> Result<(), Error>
+  {
+    for (version, description, step) in Database::MIGRATIONS.iter().filter(|m| m.0 > previous)
+    {
+      if *verbose
+      {
+        println!("Migrating to schema version {version}: {description}");
+      }
+      step(self, verbose)?;
+    }
+    self.set_meta("schema_version", &Database::SCHEMA_VERSION.to_string())
+  }
+
+  /**
+   * Creates the table for meta information, if it does not exist yet.
+   *
+   * @param _verbose  unused, only there to match the other migration steps
+   * @return Returns an Error, if the table could not be created.
+   */
+  fn create_meta_table(&self, _verbose: &bool) -> Result<(), Error>
+  {
+    self.conn.execute(Database::META_TABLE_SQL, params![])?;
+    Ok(())
+  }
+
+  /**
+   * Adds the column for the granularity to the table country, if it does not
+   * exist yet.
+   *
+   * @param _verbose  unused, only there to match the other migration steps
+   * @return Returns an Error, if the column could not be added.
+   */
+  fn add_granularity_column(&self, _verbose: &bool) -> Result<(), Error>
+  {
+    if !self.has_granularity_column()?
+    {
+      self.conn.execute("ALTER TABLE country ADD COLUMN granularity TEXT NOT NULL DEFAULT 'daily';",
+                        params![])?;
+    }
+    Ok(())
+  }
+
+  /**
+   * Creates the table for further metrics, if it does not exist yet.
+   *
+   * @param _verbose  unused, only there to match the other migration steps
+   * @return Returns an Error, if the table could not be created.
+   */
+  fn create_metric_table(&self, _verbose: &bool) -> Result<(), Error>
+  {
+    self.conn.execute(Database::METRIC_TABLE_SQL, params![])?;
+    Ok(())
+  }
+
+  /**
+   * Creates the indexes on the tables country and covid19, if they do not
+   * exist yet. The index on country id and date of the table covid19 is
+   * unique, so every country can have at most one row per day. Duplicate
+   * rows of older databases are removed before, only the row that has been
+   * inserted last is kept.
+   *
+   * @param verbose  whether to print the number of removed duplicate rows
+   * @return Returns an Error, if the indexes could not be created.
+   */
+  fn create_indexes(&self, verbose: &bool) -> Result<(), Error>
+  {
+    self.remove_duplicates(verbose)?;
+    self.conn.execute_batch(
+      "CREATE UNIQUE INDEX IF NOT EXISTS covid19_country_date ON covid19 (countryId, date);\n\
+       CREATE INDEX IF NOT EXISTS country_geo_id ON country (geoId);\n\
+       CREATE INDEX IF NOT EXISTS country_country_code ON country (countryCode);")?;
+    Ok(())
+  }
+
+  /**
+   * Removes rows of the table covid19 that have the same country and date as
+   * a row that has been inserted later. The accumulated numbers and the
+   * incidence values of the affected countries are calculated again
+   * afterwards, because they were calculated with the duplicates still
+   * present.
+   *
+   * @param verbose  whether to print the number of removed duplicate rows
+   * @return Returns an Error, if the rows could not be removed.
+   */
+  fn remove_duplicates(&self, verbose: &bool) -> Result<(), Error>
+  {
+    let mut stmt = self.conn.prepare(
+      "SELECT DISTINCT countryId FROM covid19 \
+       GROUP BY countryId, date HAVING COUNT(*) > 1;")?;
+    let affected = stmt.query_map(params![], |row| row.get(0))?;
+    let affected = affected.collect::<Result<Vec<i64>, _>>()?;
+    if affected.is_empty()
+    {
+      return Ok(());
+    }
+    let removed = self.conn.execute(
+      "DELETE FROM covid19 WHERE rowid NOT IN \
+       (SELECT MAX(rowid) FROM covid19 GROUP BY countryId, date);",
+      params![])?;
+    if *verbose
     {
-      Ok(iter) => iter,
-      Err(_) => return vec![]
+      println!("Removed {removed} duplicate row(s) from the table covid19. \
+                Calculating accumulated numbers and incidence values of {} \
+                affected country(s) again...", affected.len());
+    }
+    for country_id in affected.iter()
+    {
+      self.recalculate_incidence(country_id)?;
+    }
+    if !self.has_total_numbers()?
+    {
+      return Ok(());
+    }
+    let mut stmt = self.conn.prepare(
+      "UPDATE covid19 AS c1 \
+       SET totalCases=(SELECT SUM(cases) FROM covid19 AS c2 \
+       WHERE c2.countryId = c1.countryId AND c2.date <= c1.date), \
+       totalDeaths=(SELECT SUM(deaths) FROM covid19 AS c2 \
+       WHERE c2.countryId = c1.countryId AND c2.date <= c1.date) \
+       WHERE c1.countryId = ?;")?;
+    for country_id in affected
+    {
+      stmt.execute(params![country_id])?;
+    }
+    Ok(())
+  }
+
+  /**
+   * Calculates the incidence values of a country again from its case numbers
+   * and population, taking into account whether the country reports its
+   * numbers daily or weekly.
+   *
+   * @param country_id   id of the country
+   * @return Returns an Error, if the values could not be updated.
+   */
+  fn recalculate_incidence(&self, country_id: &i64) -> Result<(), Error>
+  {
+    let population: i32 = self.conn.query_row(
+      "SELECT population FROM country WHERE countryId = ?;",
+      params![country_id], |row| row.get(0))?;
+    let id = *country_id as i32;
+    let numbers = self.numbers(&id, &DateRange::default())?;
+    let incidence = match self.granularity(&id)?
+    {
+      Granularity::Daily => data::calculate_incidence(&numbers, &population),
+      Granularity::Weekly => data::calculate_incidence_weekly(&numbers, &population)
     };
-    let mut data: Vec<Country> = Vec::new();
-    for country in country_iter
+    let mut stmt = self.conn.prepare(
+      "UPDATE covid19 SET incidence14 = ?, incidence7 = ? \
+       WHERE countryId = ? AND date = ?;")?;
+    for elem in incidence.iter()
+    {
+      stmt.execute(params![elem.incidence_14d, elem.incidence_7d, country_id, elem.date])?;
+    }
+    Ok(())
+  }
+
+  /**
+   * Creates the table for the provenance of the data, if it does not exist
+   * yet.
+   *
+   * @param _verbose  unused, only there to match the other migration steps
+   * @return Returns an Error, if the table could not be created.
+   */
+  fn create_provenance_table(&self, _verbose: &bool) -> Result<(), Error>
+  {
+    self.conn.execute(Database::PROVENANCE_TABLE_SQL, params![])?;
+    Ok(())
+  }
+
+  /**
+   * Checks whether a table exists in the database.
+   *
+   * @param name   name of the table
+   * @return Returns true, if the table exists.
+   */
+  fn has_table(&self, name: &str) -> Result<bool, Error>
+  {
+    let count: i64 = self.conn.query_row(
+      "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?;",
+      params![name], |row| row.get(0))?;
+    Ok(count > 0)
+  }
+
+  /// SQL statement that creates the table for meta information, if it does
+  /// not exist yet (databases created by older versions do not have it)
+  const META_TABLE_SQL: &'static str = "CREATE TABLE IF NOT EXISTS meta (\n  \
+                                        name TEXT PRIMARY KEY NOT NULL,\n  \
+                                        value TEXT NOT NULL\n\
+                                        );";
+
+  /**
+   * Gets a value from the table of meta information.
+   *
+   * @param name   name of the value, e. g. "source_format"
+   * @return Returns the value, if it is set.
+   *         Returns None, if it is not set or if the database has no table for
+   *         meta information.
+   */
+  pub fn meta(&self, name: &str) -> Result<Option<String>, Error>
+  {
+    if !self.has_table("meta")?
+    {
+      return Ok(None);
+    }
+    let mut stmt = self.conn.prepare("SELECT value FROM meta WHERE name = ?;")?;
+    let mut rows = stmt.query(params![name])?;
+    match rows.next()?
     {
-      data.push(country.unwrap());
+      Some(row) => Ok(Some(row.get(0)?)),
+      None => Ok(None)
     }
-    data
+  }
+
+  /**
+   * Sets a value in the table of meta information.
+   *
+   * @param name    name of the value, e. g. "source_format"
+   * @param value   the new value
+   * @return Returns an Error, if the value could not be written.
+   */
+  pub fn set_meta(&self, name: &str, value: &str) -> Result<(), Error>
+  {
+    self.conn.execute("INSERT OR REPLACE INTO meta (name, value) VALUES (?, ?);",
+                      params![name, value])?;
+    Ok(())
+  }
+
+  /// SQL statement that creates the table for further metrics like hospital
+  /// occupancy, if it does not exist yet (databases created by older versions
+  /// do not have it)
+  const METRIC_TABLE_SQL: &'static str = "CREATE TABLE IF NOT EXISTS metric (\n  \
+                                          countryId INTEGER NOT NULL,\n  \
+                                          date TEXT NOT NULL,\n  \
+                                          name TEXT NOT NULL,\n  \
+                                          value REAL NOT NULL,\n  \
+                                          PRIMARY KEY (countryId, date, name)\n\
+                                          );";
+
+  /// SQL statement that creates the table that records every import of data,
+  /// if it does not exist yet (databases created by older versions do not
+  /// have it)
+  const PROVENANCE_TABLE_SQL: &'static str = "CREATE TABLE IF NOT EXISTS provenance (\n  \
+                                              importId INTEGER PRIMARY KEY,\n  \
+                                              format TEXT NOT NULL,\n  \
+                                              attribution TEXT NOT NULL,\n  \
+                                              file TEXT NOT NULL,\n  \
+                                              sha256 TEXT,\n  \
+                                              importedAt TEXT NOT NULL,\n  \
+                                              version TEXT NOT NULL,\n  \
+                                              rowsRead INTEGER NOT NULL,\n  \
+                                              rowsKept INTEGER NOT NULL,\n  \
+                                              rowsSkipped INTEGER NOT NULL\n\
+                                              );";
+
+  /**
+   * Lists all imports of data into the database.
+   *
+   * @return Returns the imports in the order in which they happened.
+   *         Databases without the provenance table have none.
+   */
+  pub fn provenance(&self) -> Result<Vec<Provenance>, Error>
+  {
+    if !self.has_table("provenance")?
+    {
+      return Ok(Vec::new());
+    }
+    let sql = "SELECT format, attribution, file, sha256, importedAt, version, \
+               rowsRead, rowsKept, rowsSkipped \
+               FROM provenance ORDER BY importId ASC;";
+    let mut stmt = self.conn.prepare(sql)?;
+    let records = stmt.query_map(params![], |row| {
+      Ok(Provenance {
+        format: row.get(0)?,
+        attribution: row.get(1)?,
+        file: row.get(2)?,
+        sha256: row.get(3)?,
+        imported_at: row.get(4)?,
+        version: row.get(5)?,
+        rows_read: row.get(6)?,
+        rows_kept: row.get(7)?,
+        rows_skipped: row.get(8)?
+      })
+    })?;
+    let data = records.collect::<Result<Vec<Provenance>, _>>()?;
+    Ok(data)
+  }
+
+  /**
+   * Records an import of data in the database.
+   *
+   * @param provenance   information about the import
+   * @return Returns an Error, if the record could not be written.
+   */
+  pub fn insert_provenance(&self, provenance: &Provenance) -> Result<(), Error>
+  {
+    self.conn.execute(
+      "INSERT INTO provenance (format, attribution, file, sha256, importedAt, version, \
+       rowsRead, rowsKept, rowsSkipped) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?);",
+      params![provenance.format, provenance.attribution, provenance.file, provenance.sha256,
+              provenance.imported_at, provenance.version, provenance.rows_read,
+              provenance.rows_kept, provenance.rows_skipped])?;
+    Ok(())
+  }
+
+  /**
+   * Gets the latest date of all case numbers in the database.
+   *
+   * @return Returns the date in YYYY-MM-DD format.
+   *         Returns None, if the database contains no case numbers.
+   */
+  pub fn last_date(&self) -> Result<Option<String>, Error>
+  {
+    let date = self.conn.query_row("SELECT MAX(date) FROM covid19;", params![], |row| row.get(0))?;
+    Ok(date)
+  }
+
+  /**
+   * Converts a row with the columns countryId, name, population, geoId,
+   * countryCode and continent into a Country.
+   *
+   * @param row   the database row
+   * @return Returns the country data.
+   */
+  fn row_to_country(row: &rusqlite::Row) -> rusqlite::Result<Country>
+  {
+    Ok(Country {
+      country_id: row.get(0).unwrap_or(-1),
+      name: row.get(1).unwrap_or_else(|_| String::new()),
+      population: row.get(2).unwrap_or(-1),
+      geo_id: row.get(3).unwrap_or_else(|_| String::new()),
+      country_code: row.get(4).unwrap_or_else(|_| String::new()),
+      continent: row.get(5).unwrap_or_else(|_| String::new())
+    })
+  }
+
+  /**
+   * Lists all countries in the database.
+   *
+   * @return Returns a vector of country data.
+   */
+  pub fn countries(&self) -> Result<Vec<Country>, Error>
+  {
+    let sql = "SELECT countryId, name, population, geoId, countryCode, continent FROM country \
+               WHERE geoId <> '' AND continent <> 'Other' \
+               ORDER BY name ASC;";
+    let mut stmt = self.conn.prepare(sql)?;
+    let country_iter = stmt.query_map(params![], Database::row_to_country)?;
+    let data = country_iter.collect::<Result<Vec<Country>, _>>()?;
+    Ok(data)
   }
 
   /**
@@ -151,48 +590,107 @@ impl Database
    * @param population    number of inhabitants of the country
    * @param country_code  ISO-3166 ALPHA-3 country code (e.g. "DEU" for Germany)
    * @param continent     name of the continent (e. g. "Europe")
+   * @return Returns the id of the country.
    */
-  pub fn get_country_id_or_insert(&self, geo_id: &str, name: &str, population: &i64, country_code: &str, continent: &str) -> i64
+  pub fn get_country_id_or_insert(&self, geo_id: &str, name: &str, population: &i64, country_code: &str, continent: &str) -> Result<i64, Error>
   {
-    let mut stmt = match self
+    let mut stmt = self
       .conn
-      .prepare("SELECT countryId FROM country WHERE geoId= ? LIMIT 1;")
-    {
-      Ok(statement) => statement,
-      Err(_) => return -1
-    };
-    let rows = stmt.query_map(params![geo_id], |row| Ok(row.get(0).unwrap_or(-1i64)));
-    let rows = match rows
-    {
-      Ok(mapped_rows) => mapped_rows,
-      Err(_) => return -1
-    };
-    // Allow this loop here, because the non-looping alternative would result
-    // in more obfuscated code.
-    #[allow(clippy::never_loop)]
-    for id in rows.flatten()
+      .prepare("SELECT countryId FROM country WHERE geoId= ? LIMIT 1;")?;
+    let mut rows = stmt.query_map(params![geo_id], |row| row.get::<_, i64>(0))?;
+    if let Some(id) = rows.next()
     {
-      return id;
+      return Ok(id?);
     }
     // The requested geo id was not found - insert new country.
-    let mut stmt = match self.conn.prepare(
+    let mut stmt = self.conn.prepare(
       "INSERT INTO country (name, population, geoId, countryCode, continent) \
        VALUES (@countryname, @pop, @geo, @code, @continent);"
-    )
+    )?;
+    stmt.execute(named_params!{ "@countryname": &name,
+                                "@pop": population,
+                                "@geo": &geo_id,
+                                "@code": &country_code,
+                                "@continent": &continent})?;
+
+    Ok(self.conn.last_insert_rowid())
+  }
+
+  /**
+   * Sets the number of inhabitants of a country.
+   *
+   * @param country_id   id of the country
+   * @param population   new number of inhabitants, -1 if unknown
+   * @return Returns an Error, if the update failed.
+   */
+  pub fn set_population(&self, country_id: &i64, population: &i64) -> Result<(), Error>
+  {
+    self.conn.execute("UPDATE country SET population = ? WHERE countryId = ?;",
+                      params![population, country_id])?;
+    Ok(())
+  }
+
+  /**
+   * Checks whether the table country has the column for the granularity.
+   * Databases created by older versions do not have it.
+   *
+   * @return Returns true, if the column exists.
+   */
+  fn has_granularity_column(&self) -> Result<bool, Error>
+  {
+    let mut stmt = self.conn.prepare("PRAGMA table_info(country);")?;
+    let names = stmt.query_map(params![], |row| row.get::<_, String>(1))?;
+    let names = names.collect::<Result<Vec<String>, _>>()?;
+    Ok(names.iter().any(|name| name == "granularity"))
+  }
+
+  /**
+   * Gets the interval in which a country reports its numbers.
+   *
+   * @param country_id   id of the country
+   * @return Returns the granularity of the country. Databases created by
+   *         older versions do not record it, so it is always daily for them.
+   */
+  pub fn granularity(&self, country_id: &i32) -> Result<Granularity, Error>
+  {
+    Ok(self.granularities()?.get(country_id).copied().unwrap_or_default())
+  }
+
+  /**
+   * Gets the intervals in which the countries report their numbers.
+   *
+   * @return Returns a map from country id to granularity. Countries that are
+   *         not contained in the map report their numbers daily.
+   */
+  pub fn granularities(&self) -> Result<HashMap<i32, Granularity>, Error>
+  {
+    let mut data = HashMap::new();
+    if !self.has_granularity_column()?
     {
-      Ok(statement) => statement,
-      Err(_) => return -1 // failed to prepare statement
-    };
-    if stmt.execute(named_params!{ "@countryname": &name,
-                                   "@pop": population,
-                                   "@geo": &geo_id,
-                                   "@code": &country_code,
-                                   "@continent": &continent}).is_err()
+      return Ok(data);
+    }
+    let mut stmt = self.conn.prepare("SELECT countryId, granularity FROM country;")?;
+    let mut rows = stmt.query(params![])?;
+    while let Some(row) = rows.next()?
     {
-      return -1;
-    };
+      let name: String = row.get(1)?;
+      data.insert(row.get(0)?, Granularity::from_name(&name).unwrap_or_default());
+    }
+    Ok(data)
+  }
 
-    self.conn.last_insert_rowid()
+  /**
+   * Sets the interval in which a country reports its numbers.
+   *
+   * @param country_id    id of the country
+   * @param granularity   the new granularity
+   * @return Returns an Error, if the update failed.
+   */
+  pub fn set_granularity(&self, country_id: &i64, granularity: Granularity) -> Result<(), Error>
+  {
+    self.conn.execute("UPDATE country SET granularity = ? WHERE countryId = ?;",
+                      params![granularity.name(), country_id])?;
+    Ok(())
   }
 
   /**
@@ -200,30 +698,17 @@ impl Database
    *
    * @return Returns an array of continent names.
    */
-  pub fn continents(&self) -> Vec<String>
+  pub fn continents(&self) -> Result<Vec<String>, Error>
   {
     let sql = "SELECT DISTINCT continent FROM country \
                WHERE continent <> 'Other' \
                ORDER BY continent ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
+    let mut stmt = self.conn.prepare(sql)?;
     let continent_iter = stmt.query_map(params![], |row| {
       Ok(row.get(0).unwrap_or_else(|_| String::new()))
-    });
-    let continent_iter = match continent_iter
-    {
-      Ok(iter) => iter,
-      Err(_) => return vec![]
-    };
-    let mut data: Vec<String> = Vec::new();
-    for continent in continent_iter
-    {
-      data.push(continent.unwrap());
-    }
-    data
+    })?;
+    let data = continent_iter.collect::<Result<Vec<String>, _>>()?;
+    Ok(data)
   }
 
   /**
@@ -232,56 +717,175 @@ impl Database
    * @param continent   name of the continent
    * @return Returns a vector of country data.
    */
-  pub fn countries_of_continent(&self, continent: &str) -> Vec<Country>
+  pub fn countries_of_continent(&self, continent: &str) -> Result<Vec<Country>, Error>
   {
     let sql = "SELECT countryId, name, population, geoId, countryCode, continent FROM country \
                WHERE geoId <> '' AND continent = ? \
                ORDER BY name ASC;";
-    let mut stmt = match self.conn.prepare(sql)
+    let mut stmt = self.conn.prepare(sql)?;
+    let country_iter = stmt.query_map(params![&continent], Database::row_to_country)?;
+    let data = country_iter.collect::<Result<Vec<Country>, _>>()?;
+    Ok(data)
+  }
+
+  /**
+   * Gets the date range and the number of rows for every country in the
+   * database, including countries that are not listed by countries().
+   *
+   * @return Returns a vector of statistics, sorted by country name.
+   */
+  pub fn country_statistics(&self) -> Result<Vec<CountryStatistics>, Error>
+  {
+    let sql = "SELECT c.countryId, c.name, c.population, c.geoId, c.countryCode, c.continent, \
+               MIN(v.date), MAX(v.date), COUNT(v.date) \
+               FROM country AS c LEFT JOIN covid19 AS v ON c.countryId = v.countryId \
+               GROUP BY c.countryId \
+               ORDER BY c.name ASC;";
+    let mut stmt = self.conn.prepare(sql)?;
+    let stats_iter = stmt.query_map(params![], |row| {
+      Ok(CountryStatistics {
+        country: Database::row_to_country(row)?,
+        first_date: row.get(6)?,
+        last_date: row.get(7)?,
+        rows: row.get(8)?
+      })
+    })?;
+    let data = stats_iter.collect::<Result<Vec<CountryStatistics>, _>>()?;
+    Ok(data)
+  }
+
+  /**
+   * Gets all rows of the covid19 table, sorted by country id and date.
+   *
+   * @return Returns a vector of all daily records. The accumulated numbers are
+   *         None, if calculate_total_numbers() has not been run yet.
+   */
+  pub fn daily_records(&self) -> Result<Vec<DailyRecord>, Error>
+  {
+    let totals = match self.has_total_numbers()?
     {
-      Ok(x) => x,
-      Err(_) => return vec![]
+      true => "totalCases, totalDeaths",
+      false => "NULL, NULL"
     };
-    let country_iter = stmt.query_map(params![&continent], |row| {
-      Ok(Country {
-        country_id: row.get(0).unwrap_or(-1),
-        name: row.get(1).unwrap_or_else(|_| String::new()),
-        population: row.get(2).unwrap_or(-1),
-        geo_id: row.get(3).unwrap_or_else(|_| String::new()),
-        country_code: row.get(4).unwrap_or_else(|_| String::new()),
-        continent: row.get(5).unwrap_or_else(|_| String::new())
+    let sql = format!("SELECT countryId, IFNULL(date, ''), IFNULL(cases, 0), IFNULL(deaths, 0), {totals} \
+                       FROM covid19 ORDER BY countryId ASC, date ASC;");
+    let mut stmt = self.conn.prepare(&sql)?;
+    let records = stmt.query_map(params![], |row| {
+      Ok(DailyRecord {
+        country_id: row.get(0)?,
+        date: row.get(1)?,
+        cases: row.get(2)?,
+        deaths: row.get(3)?,
+        total_cases: row.get(4)?,
+        total_deaths: row.get(5)?
       })
-    });
-    let country_iter = match country_iter
+    })?;
+    let data = records.collect::<Result<Vec<DailyRecord>, _>>()?;
+    Ok(data)
+  }
+
+  /**
+   * Lists the metrics that are available for a country.
+   *
+   * @param country_id   id of the country
+   * @return Returns the metrics with at least one value, in the order of
+   *         Metric::ALL. Databases without the metric table have none.
+   */
+  pub fn available_metrics(&self, country_id: &i32) -> Result<Vec<Metric>, Error>
+  {
+    if !self.has_table("metric")?
     {
-      Ok(iter) => iter,
-      Err(_) => return vec![]
-    };
-    let mut data: Vec<Country> = Vec::new();
-    for country in country_iter
+      return Ok(Vec::new());
+    }
+    let mut stmt = self.conn.prepare("SELECT DISTINCT name FROM metric WHERE countryId = ?;")?;
+    let names = stmt.query_map(params![country_id], |row| row.get::<_, String>(0))?;
+    let names = names.collect::<Result<Vec<String>, _>>()?;
+    Ok(Metric::ALL
+      .into_iter()
+      .filter(|m| names.iter().any(|name| name == m.name()))
+      .collect())
+  }
+
+  /**
+   * Gets the values of all metrics of a country.
+   *
+   * @param country_id   id of the country
+   * @param range        period of time to get the values for
+   * @return Returns the values sorted by date and metric. Databases without the
+   *         metric table have none.
+   */
+  pub fn metrics(&self, country_id: &i32, range: &DateRange) -> Result<Vec<MetricValue>, Error>
+  {
+    self.query_metrics(country_id, None, range)
+  }
+
+  /**
+   * Gets the values of a single metric of a country.
+   *
+   * @param country_id   id of the country
+   * @param metric       the metric, e. g. Metric::IcuPatients
+   * @param range        period of time to get the values for
+   * @return Returns the values sorted by date. Days without a value are not
+   *         contained.
+   */
+  pub fn metric(&self, country_id: &i32, metric: Metric, range: &DateRange) -> Result<Vec<MetricValue>, Error>
+  {
+    self.query_metrics(country_id, Some(metric), range)
+  }
+
+  /**
+   * Gets the values of one or all metrics of a country.
+   *
+   * @param country_id   id of the country
+   * @param metric       the metric, or None to get all metrics
+   * @param range        period of time to get the values for
+   * @return Returns the values sorted by date and metric.
+   */
+  fn query_metrics(&self, country_id: &i32, metric: Option<Metric>, range: &DateRange) -> Result<Vec<MetricValue>, Error>
+  {
+    if !self.has_table("metric")?
     {
-      data.push(country.unwrap());
+      return Ok(Vec::new());
+    }
+    let sql = "SELECT date, name, value FROM metric \
+               WHERE countryId = :id AND date >= :lower AND date <= :upper \
+                 AND (:name IS NULL OR name = :name) \
+               ORDER BY date ASC;";
+    let mut stmt = self.conn.prepare(sql)?;
+    let mut rows = stmt.query(named_params! {
+      ":id": country_id,
+      ":lower": range.lower(),
+      ":upper": range.upper(),
+      ":name": metric.map(|m| m.name())
+    })?;
+    let mut data: Vec<MetricValue> = Vec::new();
+    while let Some(row) = rows.next()?
+    {
+      let name: String = row.get(1)?;
+      // Names of newer versions are skipped, because they cannot be represented.
+      if let Some(metric) = Metric::from_name(&name)
+      {
+        data.push(MetricValue { date: row.get(0)?, metric, value: row.get(2)? });
+      }
     }
-    data
+    data.sort_by(|a, b| a.date.cmp(&b.date).then(a.metric.cmp(&b.metric)));
+    Ok(data)
   }
 
   /**
    * Get Covid-19 numbers for a specific country.
    *
    * @param country_id   id of the country
+   * @param range        period of time to get the numbers for
    * @return Returns an array of arrays containing the date, infections and deaths on that day.
    */
-  pub fn numbers(&self, country_id: &i32) -> Vec<Numbers>
+  pub fn numbers(&self, country_id: &i32, range: &DateRange) -> Result<Vec<Numbers>, Error>
   {
     let sql = "SELECT date, cases, deaths FROM covid19 \
-               WHERE countryId = ? \
+               WHERE countryId = ? AND date >= ? AND date <= ? \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let rows = stmt.query(params![&country_id]);
+    let mut stmt = self.conn.prepare(sql)?;
+    let rows = stmt.query(params![&country_id, range.lower(), range.upper()])?;
     Database::extract_numbers(rows)
   }
 
@@ -289,50 +893,33 @@ impl Database
    * Get Covid-19 numbers and incidence values for a specific country.
    *
    * @param country_id   id of the country
+   * @param range        period of time to get the numbers for
    * @return Returns an array of objects containing the date, infections and deaths on that day.
    */
-  pub fn numbers_with_incidence(&self, country_id: &i32) -> Vec<NumbersAndIncidence>
-  {
-    let sql = "SELECT date, cases, deaths, IFNULL(incidence14, -1.0), IFNULL(incidence7, -1.0) FROM covid19 \
-               WHERE countryId = ? \
-               ORDER BY date DESC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let mut rows = match stmt.query(params![&country_id])
-    {
-      Ok(r) => r,
-      Err(_) => return vec![]
-    };
+  pub fn numbers_with_incidence(&self, country_id: &i32, range: &DateRange) -> Result<Vec<NumbersAndIncidence>, Error>
+  {
+    let sql = "SELECT date, cases, deaths, IFNULL(incidence14, -1.0), IFNULL(incidence7, -1.0) FROM covid19 \
+               WHERE countryId = ? AND date >= ? AND date <= ? \
+               ORDER BY date DESC;";
+    let mut stmt = self.conn.prepare(sql)?;
+    let mut rows = stmt.query(params![&country_id, range.lower(), range.upper()])?;
 
     const DELTA: f64 = 0.000001;
     let mut data: Vec<NumbersAndIncidence> = Vec::new();
-    // potential infinite loop
-    loop
-    {
-      let row = rows.next();
-      match row
-      {
-        Ok(Some(row)) =>
-        {
-          let i14d = row.get(3).unwrap_or(-1.0f64);
-          let i7d = row.get(4).unwrap_or(-1.0f64);
-          data.push(NumbersAndIncidence {
-            date: row.get(0).unwrap_or_else(|_e| String::from("")),
-            cases: row.get(1).unwrap_or(0),
-            deaths: row.get(2).unwrap_or(0),
-            incidence_14d: if (i14d + 1.0).abs() < DELTA { None } else { Some(i14d) },
-            incidence_7d: if (i7d + 1.0).abs() < DELTA { None } else { Some(i7d) },
-          })
-        },
-        Ok(None) => break,
-        _ => return vec![]
-      }
+    while let Some(row) = rows.next()?
+    {
+      let i14d = row.get(3).unwrap_or(-1.0f64);
+      let i7d = row.get(4).unwrap_or(-1.0f64);
+      data.push(NumbersAndIncidence {
+        date: row.get(0).unwrap_or_else(|_e| String::from("")),
+        cases: row.get(1).unwrap_or(0),
+        deaths: row.get(2).unwrap_or(0),
+        incidence_14d: if (i14d + 1.0).abs() < DELTA { None } else { Some(i14d) },
+        incidence_7d: if (i7d + 1.0).abs() < DELTA { None } else { Some(i7d) },
+      });
     }
 
-    data
+    Ok(data)
   }
 
   /**
@@ -342,91 +929,74 @@ impl Database
    * the number of deaths. Second and third columns must be able to convert to
    * an integer (i32).
    *
-   * @param rows  the return value of rusqlite::Statement::query
+   * @param rows  the rows returned by rusqlite::Statement::query
    * @return  Returns a vector of Numbers.
    */
-  fn extract_numbers(rows: Result<rusqlite::Rows, rusqlite::Error>) -> Vec<Numbers>
+  fn extract_numbers(mut rows: rusqlite::Rows) -> Result<Vec<Numbers>, Error>
   {
-    let mut rows: rusqlite::Rows = match rows
-    {
-      Ok(r) => r,
-      Err(_) => return vec![]
-    };
     let mut data: Vec<Numbers> = Vec::new();
-    // potential infinite loop
-    loop
+    while let Some(row) = rows.next()?
     {
-      let row = rows.next();
-      match row
-      {
-        Ok(Some(row)) => data.push(Numbers {
-          date: row.get(0).unwrap_or_else(|_e| String::from("")),
-          cases: row.get(1).unwrap_or(0),
-          deaths: row.get(2).unwrap_or(0)
-        }),
-        Ok(None) => break,
-        _ => return vec![]
-      }
+      data.push(Numbers {
+        date: row.get(0).unwrap_or_else(|_e| String::from("")),
+        cases: row.get(1).unwrap_or(0),
+        deaths: row.get(2).unwrap_or(0)
+      });
     }
 
-    data
+    Ok(data)
   }
 
   /**
    * Get total Covid-19 numbers worldwide.
    *
+   * @param range   period of time to get the numbers for
    * @return Returns an array of arrays containing the date, infections and deaths on that day.
    */
-  pub fn numbers_world(&self) -> Vec<Numbers>
+  pub fn numbers_world(&self, range: &DateRange) -> Result<Vec<Numbers>, Error>
   {
     let sql = "SELECT date, SUM(cases), SUM(deaths) FROM covid19 \
+               WHERE date >= ? AND date <= ? \
                GROUP BY date \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let rows = stmt.query(params![]);
+    let mut stmt = self.conn.prepare(sql)?;
+    let rows = stmt.query(params![range.lower(), range.upper()])?;
     Database::extract_numbers(rows)
   }
 
   /**
    * Get accumulated Covid-19 numbers for a specific country.
+   * The totals always include all numbers since the beginning of the data,
+   * even if the range starts at a later date.
    *
    * @param countryId   id of the country
+   * @param range       period of time to get the numbers for
    * @return Returns an array of arrays containing the date, total infections and total deaths until this date.
    */
-  pub fn accumulated_numbers(&self, country_id: &i32) -> Vec<Numbers>
+  pub fn accumulated_numbers(&self, country_id: &i32, range: &DateRange) -> Result<Vec<Numbers>, Error>
   {
     let sql = "SELECT date, totalCases, totalDeaths FROM covid19 \
-               WHERE countryId = ? \
+               WHERE countryId = ? AND date >= ? AND date <= ? \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let rows = stmt.query(params![&country_id]);
+    let mut stmt = self.conn.prepare(sql)?;
+    let rows = stmt.query(params![&country_id, range.lower(), range.upper()])?;
     Database::extract_numbers(rows)
   }
 
   /**
    * Get accumulated total Covid-19 numbers worldwide.
    *
+   * @param range   period of time to get the numbers for
    * @return Returns a vector of Numbers containing the date, infections and deaths up to that date.
    */
-  pub fn accumulated_numbers_world(&self) -> Vec<Numbers>
+  pub fn accumulated_numbers_world(&self, range: &DateRange) -> Result<Vec<Numbers>, Error>
   {
     let sql = "SELECT date, SUM(totalCases), SUM(totalDeaths) FROM covid19 \
+               WHERE date >= ? AND date <= ? \
                GROUP BY date \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let rows = stmt.query(params![]);
+    let mut stmt = self.conn.prepare(sql)?;
+    let rows = stmt.query(params![range.lower(), range.upper()])?;
     Database::extract_numbers(rows)
   }
 
@@ -434,42 +1004,28 @@ impl Database
    * Get the 7-day incidence values of Covid-19 for a specific country.
    *
    * @param countryId   id of the country
+   * @param range       period of time to get the incidence values for
    * @return Returns a vector of Incidences.
    *         This may be an empty vector, if no values are known.
    */
-  pub fn incidence7(&self, country_id: &i32) -> Vec<Incidence7>
+  pub fn incidence7(&self, country_id: &i32, range: &DateRange) -> Result<Vec<Incidence7>, Error>
   {
     let sql = "SELECT date, round(incidence7, 2) FROM covid19 \
                WHERE countryId = ? AND ABS(IFNULL(incidence7, -1.0)+1.0) > 0.000001 \
+               AND date >= ? AND date <= ? \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let rows = stmt.query(params![&country_id]);
-    let mut rows = match rows
-    {
-      Ok(r) => r,
-      Err(_) => return vec![]
-    };
+    let mut stmt = self.conn.prepare(sql)?;
+    let mut rows = stmt.query(params![&country_id, range.lower(), range.upper()])?;
     let mut data: Vec<Incidence7> = Vec::new();
-    // potential infinite loop
-    loop
+    while let Some(row) = rows.next()?
     {
-      let row = rows.next();
-      match row
-      {
-        Ok(Some(row)) => data.push(Incidence7 {
-          date: row.get(0).unwrap_or_else(|_e| String::from("")),
-          incidence_7d: row.get(1).unwrap_or(0.0)
-        }),
-        Ok(None) => break,
-        _ => return vec![]
-      }
+      data.push(Incidence7 {
+        date: row.get(0).unwrap_or_else(|_e| String::from("")),
+        incidence_7d: row.get(1).unwrap_or(0.0)
+      });
     }
 
-    data
+    Ok(data)
   }
 
   /**
@@ -479,58 +1035,41 @@ impl Database
    * sorted in ascending order by day of year (ranges from 1 to 366).
    *
    * @param countryId   id of the country
+   * @param range       period of time to get the incidence values for
    * @return Returns a map of vectors of incidences.
    *         This may be an empty map, if no values are known.
    */
-  pub fn incidence7_by_year(&self, country_id: &i32) -> HashMap<u16, Vec<IncidenceWithDay>>
+  pub fn incidence7_by_year(&self, country_id: &i32, range: &DateRange) -> Result<HashMap<u16, Vec<IncidenceWithDay>>, Error>
   {
     let sql = "SELECT CAST(strftime('%Y', date) AS INTEGER), \
                             CAST(ltrim(strftime('%j', date), '0') AS INTEGER), \
                             round(incidence7, 2) FROM covid19 \
                WHERE countryId = ? AND ABS(IFNULL(incidence7, -1.0)+1.0) > 0.000001 \
+               AND date >= ? AND date <= ? \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return HashMap::new()
-    };
-    let rows = stmt.query(params![&country_id]);
-    let mut rows = match rows
-    {
-      Ok(r) => r,
-      Err(_) => return HashMap::new()
-    };
+    let mut stmt = self.conn.prepare(sql)?;
+    let mut rows = stmt.query(params![&country_id, range.lower(), range.upper()])?;
 
     let mut data = HashMap::new();
     let mut current_year = 0u16;
     let mut current_data = Vec::new();
 
-    // potential infinite loop
-    loop
+    while let Some(row) = rows.next()?
     {
-      let row = rows.next();
-      match row
+      let year = row.get(0).unwrap_or(0u16);
+      if current_year != year
       {
-        Ok(Some(row)) =>
+        if !current_data.is_empty()
         {
-          let year = row.get(0).unwrap_or(0u16);
-          if current_year != year
-          {
-            if !current_data.is_empty()
-            {
-              data.insert(current_year, current_data);
-            }
-            current_year = year;
-            current_data = Vec::new();
-          }
-          current_data.push(IncidenceWithDay {
-            day_of_year: row.get(1).unwrap_or(0u16),
-            incidence: row.get(2).unwrap_or(0.0)
-          });
-        },
-        Ok(None) => break,
-        _ => return HashMap::new()
+          data.insert(current_year, current_data);
+        }
+        current_year = year;
+        current_data = Vec::new();
       }
+      current_data.push(IncidenceWithDay {
+        day_of_year: row.get(1).unwrap_or(0u16),
+        incidence: row.get(2).unwrap_or(0.0)
+      });
     }
 
     if !current_data.is_empty()
@@ -538,49 +1077,35 @@ impl Database
       data.insert(current_year, current_data);
     }
 
-    data
+    Ok(data)
   }
 
   /**
    * Get the 14-day incidence values of Covid-19 for a specific country.
    *
    * @param countryId   id of the country
+   * @param range       period of time to get the incidence values for
    * @return Returns a vector of Incidences.
    *         This may be an empty vector, if no values are known.
    */
-  pub fn incidence14(&self, country_id: &i32) -> Vec<Incidence14>
+  pub fn incidence14(&self, country_id: &i32, range: &DateRange) -> Result<Vec<Incidence14>, Error>
   {
     let sql = "SELECT date, round(incidence14, 2) FROM covid19 \
                WHERE countryId = ? AND ABS(IFNULL(incidence14, -1.0)+1.0) > 0.000001 \
+               AND date >= ? AND date <= ? \
                ORDER BY date ASC;";
-    let mut stmt = match self.conn.prepare(sql)
-    {
-      Ok(x) => x,
-      Err(_) => return vec![]
-    };
-    let rows = stmt.query(params![&country_id]);
-    let mut rows = match rows
-    {
-      Ok(r) => r,
-      Err(_) => return vec![]
-    };
+    let mut stmt = self.conn.prepare(sql)?;
+    let mut rows = stmt.query(params![&country_id, range.lower(), range.upper()])?;
     let mut data: Vec<Incidence14> = Vec::new();
-    // potential infinite loop
-    loop
+    while let Some(row) = rows.next()?
     {
-      let row = rows.next();
-      match row
-      {
-        Ok(Some(row)) => data.push(Incidence14 {
-          date: row.get(0).unwrap_or_else(|_e| String::from("")),
-          incidence_14d: row.get(1).unwrap_or(0.0)
-        }),
-        Ok(None) => break,
-        _ => return vec![]
-      }
+      data.push(Incidence14 {
+        date: row.get(0).unwrap_or_else(|_e| String::from("")),
+        incidence_14d: row.get(1).unwrap_or(0.0)
+      });
     }
 
-    data
+    Ok(data)
   }
 
   /**
@@ -588,82 +1113,63 @@ impl Database
    * totalDeaths, and creates them, if they are missing.
    *
    * @param verbose  whether the progress should be printed to standard output
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the operation failed.
    */
-  pub fn calculate_total_numbers(&self, verbose: &bool) -> bool
+  pub fn calculate_total_numbers(&self, verbose: &bool) -> Result<(), Error>
   {
-    let mut has_total_cases = false;
-    let mut has_total_deaths = false;
-    let mut stmt = match self.conn.prepare("PRAGMA table_info(covid19);")
-    {
-      Ok(x) => x,
-      Err(_) => return false
-    };
-    let mut rows = match stmt.query(params![])
-    {
-      Ok(r) => r,
-      Err(_) => return false
-    };
-    // potential infinite loop
-    loop
-    {
-      match rows.next()
-      {
-        Ok(Some(row)) =>
-        {
-          let name = row.get(1).unwrap_or_else(|_e| String::new());
-          if name == "totalCases"
-          {
-            has_total_cases = true;
-          }
-          else if name == "totalDeaths"
-          {
-            has_total_deaths = true;
-          }
-        },
-        Ok(None) => break,
-        _ => return false
-      }
-    }
+    let columns = self.covid19_columns()?;
+    let has_total_cases = columns.iter().any(|c| c == "totalCases");
+    let has_total_deaths = columns.iter().any(|c| c == "totalDeaths");
 
-    if !has_total_cases && !self.calculate_total_cases(verbose)
+    if !has_total_cases
     {
-      return false;
+      self.calculate_total_cases(verbose)?;
     }
-    if !has_total_deaths && !self.calculate_total_deaths(verbose)
+    if !has_total_deaths
     {
-      return false;
+      self.calculate_total_deaths(verbose)?;
     }
 
-    true
+    Ok(())
+  }
+
+  /**
+   * Checks whether the columns totalCases and totalDeaths exist, i. e. whether
+   * calculate_total_numbers() has already been run on the database.
+   *
+   * @return Returns true, if both columns exist.
+   */
+  pub fn has_total_numbers(&self) -> Result<bool, Error>
+  {
+    let columns = self.covid19_columns()?;
+    Ok(columns.iter().any(|c| c == "totalCases")
+       && columns.iter().any(|c| c == "totalDeaths"))
+  }
+
+  /**
+   * Gets the names of the columns in the covid19 table.
+   *
+   * @return Returns the column names in case of success.
+   */
+  fn covid19_columns(&self) -> Result<Vec<String>, Error>
+  {
+    let mut stmt = self.conn.prepare("PRAGMA table_info(covid19);")?;
+    let names = stmt.query_map(params![], |row| row.get(1))?;
+    let names = names.collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
   }
 
   /**
    * Gets the number of records in the covid19 table.
    *
    * @return Returns the number of columns in case of success.
-   *         Returns an error message otherwise.
+   *         Returns an Error otherwise.
    */
-  fn covid19_records(&self) -> Result<i64, String>
+  fn covid19_records(&self) -> Result<i64, Error>
   {
-    let mut stmt = match self.conn.prepare("SELECT COUNT(*) FROM covid19;")
-    {
-      Ok(x) => x,
-      Err(_) => return Err(String::from("Failed to prepare database statement!"))
-    };
-    let iter = stmt.query_map(params![], |row| {
-      Ok(row.get(0).unwrap_or(-1))
-    });
-    let mut iter = match iter
-    {
-      Ok(iter) => iter,
-      Err(_) => return Err(String::from("Failed to retrieve result of database query!"))
-    };
-    match iter.next()
-    {
-      Some(n) => Ok(n.unwrap()),
-      None => Err(String::from("Failed to retrieve result of database query!"))
-    }
+    let count = self.conn.query_row("SELECT COUNT(*) FROM covid19;", params![],
+                                    |row| row.get(0))?;
+    Ok(count)
   }
 
   /**
@@ -671,41 +1177,24 @@ impl Database
    * This may take quite a while.
    *
    * @param verbose  whether the progress should be printed to standard output
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the operation failed.
    */
-  fn calculate_total_cases(&self, verbose: &bool) -> bool
+  fn calculate_total_cases(&self, verbose: &bool) -> Result<(), Error>
   {
     // add new column
-    match self.conn.execute(
+    self.conn.execute(
       "ALTER TABLE covid19 ADD COLUMN totalCases INTEGER;",
       params![]
-    )
+    )?;
+    if *verbose
     {
-      Ok(_) => if *verbose
-      {
-        println!("Info: Added column totalCases to table.");
-      },
-      Err(e) => {
-        eprintln!("Could not add column totalCases to table covid19! {e}");
-        return false;
-      }
-    };
+      println!("Info: Added column totalCases to table.");
+    }
     // Some older SQLite versions do not work with the UPDATE statement below,
     // if the table is empty, so check for that and leave early, if necessary.
-    match self.covid19_records()
+    if self.covid19_records()? <= 0
     {
-      Ok(0) => return true,
-      Ok(i64::MIN..=-1_i64) => {
-        eprintln!("Failed to get rows of table covid19!");
-        return false;
-      },
-      Ok(1_i64..) => {
-        // Do not return here, rows need to be updated.
-      },
-      Err(e) => {
-        eprintln!("Failed to get rows of table covid19! {e}");
-        return false;
-      }
+      return Ok(());
     }
     // perform actual calculation
     if *verbose
@@ -713,24 +1202,18 @@ impl Database
       println!("Calculating accumulated number of cases for each day and \
                 country. This may take a while...");
     }
-    match self.conn.execute(
+    let affected = self.conn.execute(
       "UPDATE covid19 AS c1 \
        SET totalCases=(SELECT SUM(cases) FROM covid19 AS c2 \
        WHERE c2.countryId = c1.countryId AND c2.date <= c1.date);",
       params![]
-    )
+    )?;
+    if *verbose
     {
-      Ok(affected) => if *verbose
-      {
-        println!("{affected} rows have been updated.");
-      },
-      Err(e)=> {
-        eprintln!("Could not update totalCases in table covid19! {e}");
-        return false;
-      }
-    };
+      println!("{affected} rows have been updated.");
+    }
 
-    true
+    Ok(())
   }
 
   /**
@@ -738,41 +1221,24 @@ impl Database
    * This may take quite a while.
    *
    * @param verbose  whether the progress should be printed to standard output
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the operation failed.
    */
-  fn calculate_total_deaths(&self, verbose: &bool) -> bool
+  fn calculate_total_deaths(&self, verbose: &bool) -> Result<(), Error>
   {
     // add new column
-    match self.conn.execute(
+    self.conn.execute(
       "ALTER TABLE covid19 ADD COLUMN totalDeaths INTEGER;",
       params![]
-    )
+    )?;
+    if *verbose
     {
-      Ok(_) => if *verbose
-      {
-        println!("Info: Added column totalDeaths to table.");
-      },
-      Err(e)=> {
-        eprintln!("Could not add column totalDeaths to table covid19! {e}");
-        return false;
-      }
-    };
+      println!("Info: Added column totalDeaths to table.");
+    }
     // Some older SQLite versions do not work with the UPDATE statement below,
     // if the table is empty, so check for that and leave early, if necessary.
-    match self.covid19_records()
+    if self.covid19_records()? <= 0
     {
-      Ok(0) => return true,
-      Ok(i64::MIN..=-1_i64) => {
-        eprintln!("Failed to get rows of table covid19!");
-        return false;
-      },
-      Ok(1_i64..) => {
-        // Do not return here, rows need to be updated.
-      },
-      Err(e) => {
-        eprintln!("Failed to get rows of table covid19! {e}");
-        return false;
-      }
+      return Ok(());
     }
     // Update may take ca. two minutes.
     if *verbose
@@ -780,67 +1246,67 @@ impl Database
       println!("Calculating accumulated number of deaths for each day and country. \
                 This may take a while...");
     }
-    match self.conn.execute(
+    let affected = self.conn.execute(
       "UPDATE covid19 AS c1 \
        SET totalDeaths=(SELECT SUM(deaths) FROM covid19 AS c2 \
        WHERE c2.countryId = c1.countryId AND c2.date <= c1.date);",
       params![]
-    )
+    )?;
+    if *verbose
     {
-      Ok(affected) => if *verbose
-      {
-        println!("{affected} rows have been updated.");
-      },
-      Err(e)=> {
-        eprintln!("Could not update totalDeaths in table covid19! {e}");
-        return false;
-      }
+      println!("{affected} rows have been updated.");
     }
 
-    true
+    Ok(())
   }
 
   /**
    * Executes a batch SQL statement.
    *
    * @param sql   the SQL statement(s) to execute
-   * @return  Returns whether the statements were executed successfully.
+   * @return  Returns an Error, if the statements could not be executed.
    */
-  pub fn batch(&self, sql: &str) -> bool
+  pub(crate) fn batch(&self, sql: &str) -> Result<(), Error>
   {
-    let res = self.conn.execute_batch(sql);
-    if let Err(e) = res
-    {
-      eprintln!("Error: Batch statement failed! {e}");
-      eprintln!("Statement is:\n{sql}\n");
-      return false;
-    }
-    true
+    self.conn.execute_batch(sql)?;
+    Ok(())
+  }
+
+  /**
+   * Deletes all case numbers of a country. The country itself is kept.
+   *
+   * @param country_id   id of the country
+   * @return Returns an Error, if the deletion failed.
+   */
+  pub fn delete_numbers(&self, country_id: &i64) -> Result<(), Error>
+  {
+    self.conn.execute("DELETE FROM covid19 WHERE countryId = ?;", params![country_id])?;
+    Ok(())
   }
 
   /**
-   * Inserts data for a given country id into the database.
+   * Inserts data for a given country id into the database. Existing rows of
+   * the country for the same days are replaced.
    *
    * @param country_id   id of an existing country in the database
    * @param data         slice of data to insert into the database
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the operation failed.
    */
-  pub fn insert_data(&self, country_id: &i64, data: &[NumbersAndIncidenceAndTotals]) -> bool
+  pub fn insert_data(&self, country_id: &i64, data: &[NumbersAndIncidenceAndTotals]) -> Result<(), Error>
   {
     if data.is_empty()
     {
       // No data means there is nothing to do here.
-      return true;
+      return Ok(());
     }
     if country_id <= &0
     {
-      eprintln!("Error: Country id must be a positive number!");
-      return false;
+      return Err(Error::NoData(format!("Country id must be a positive number, but it is {country_id}!")));
     }
 
     // Build insert statement.
     let mut batch = String::from(
-      "INSERT INTO covid19 (countryId, date, cases, deaths, incidence14, \
+      "INSERT OR REPLACE INTO covid19 (countryId, date, cases, deaths, incidence14, \
      incidence7, totalCases, totalDeaths) VALUES "
     );
     // Reserve 60 bytes for every data record to avoid frequent reallocation.
@@ -883,13 +1349,55 @@ impl Database
     self.batch(&batch)
   }
 
+  /**
+   * Inserts values of metrics for a given country id into the database. Existing
+   * values of the same day and metric are replaced.
+   *
+   * @param country_id   id of an existing country in the database
+   * @param data         slice of values to insert into the database
+   * @return Returns an Error, if the operation failed.
+   */
+  pub fn insert_metrics(&self, country_id: &i64, data: &[MetricValue]) -> Result<(), Error>
+  {
+    if data.is_empty()
+    {
+      return Ok(());
+    }
+    if country_id <= &0
+    {
+      return Err(Error::NoData(format!("Country id must be a positive number, but it is {country_id}!")));
+    }
+
+    let mut batch = String::from("INSERT OR REPLACE INTO metric (countryId, date, name, value) VALUES ");
+    batch.reserve(50 * data.len());
+    let country_id = country_id.to_string();
+    for elem in data.iter()
+    {
+      batch.push('(');
+      batch.push_str(&country_id);
+      batch.push_str(", ");
+      batch.push_str(&Database::quote(&elem.date));
+      batch.push_str(", ");
+      batch.push_str(&Database::quote(elem.metric.name()));
+      batch.push_str(", ");
+      batch.push_str(&elem.value.to_string());
+      batch.push_str("),");
+    }
+
+    // replace last ',' with ';' to make it valid SQL syntax
+    batch.truncate(batch.len() - 1);
+    batch.push(';');
+
+    self.batch(&batch)
+  }
+
   /**
    * Quotes an ASCII string for use in an SQLite statement.
    *
    * @param s   the string that shall be quoted.
    * @return  Returns the quoted string.
    */
-  pub fn quote(s: &str) -> String
+  pub(crate) fn quote(s: &str) -> String
   {
     let mut result = String::from("'");
     for c in s.chars()
@@ -934,6 +1442,21 @@ mod tests
     return db.unwrap();
   }
 
+  /**
+   * Checks whether an index exists in the database.
+   *
+   * @param db     the database
+   * @param name   name of the index
+   * @return Returns true, if the index exists.
+   */
+  fn has_index(db: &Database, name: &str) -> bool
+  {
+    let count: i64 = db.conn.query_row(
+      "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = ?;",
+      params![name], |row| row.get(0)).unwrap();
+    count > 0
+  }
+
   /**
    * Gets a database instance connected to the old corona.db file which still
    * used the RKI data for Germany in the data directory.
@@ -986,6 +1509,17 @@ mod tests
       assert!(created.is_ok());
       assert!(path.exists());
       assert!(path.is_file());
+      // New databases have the current schema.
+      let db = created.unwrap();
+      assert_eq!(db.schema_version().unwrap(), Database::SCHEMA_VERSION);
+      assert!(db.require_current_schema().is_ok());
+      assert!(db.has_total_numbers().unwrap());
+      assert!(db.has_granularity_column().unwrap());
+      assert!(db.has_table("metric").unwrap());
+      assert!(db.has_table("provenance").unwrap());
+      assert!(has_index(&db, "covid19_country_date"));
+      assert!(has_index(&db, "country_geo_id"));
+      assert!(has_index(&db, "country_country_code"));
     }
     // clean up
     assert!(std::fs::remove_file(path).is_ok());
@@ -1009,12 +1543,77 @@ mod tests
     assert!(std::fs::remove_file(path).is_ok());
   }
 
+  #[test]
+  fn migrate_old_database()
+  {
+    let path = std::env::temp_dir().join("migration_test_old.db");
+    let _ = std::fs::remove_file(&path);
+    // The ECDC database was created before the schema version was recorded.
+    let original = Path::new(file!())
+      .parent()
+      .unwrap()
+      .join("..")
+      .join("data")
+      .join("corona-ecdc-2020-12-14.db");
+    assert!(std::fs::copy(original, &path).is_ok());
+    // scope for db
+    {
+      let db = Database::new(path.to_str().unwrap()).unwrap();
+      assert_eq!(db.schema_version().unwrap(), 0);
+      assert!(matches!(db.require_current_schema(),
+                       Err(Error::UnsupportedSchema { version: 0, supported: Database::SCHEMA_VERSION })));
+      assert!(!db.has_table("meta").unwrap());
+      let rows = db.daily_records().unwrap().len();
+
+      assert_eq!(db.migrate(&false).unwrap(), (0, Database::SCHEMA_VERSION));
+      assert_eq!(db.schema_version().unwrap(), Database::SCHEMA_VERSION);
+      assert!(db.require_current_schema().is_ok());
+      assert!(db.has_table("meta").unwrap());
+      assert!(db.has_table("metric").unwrap());
+      assert!(db.has_table("provenance").unwrap());
+      assert!(db.provenance().unwrap().is_empty());
+      assert!(db.has_granularity_column().unwrap());
+      assert!(has_index(&db, "covid19_country_date"));
+      // Existing data is kept.
+      assert_eq!(db.daily_records().unwrap().len(), rows);
+      // A second migration has nothing to do.
+      assert_eq!(db.migrate(&false).unwrap(), (Database::SCHEMA_VERSION, Database::SCHEMA_VERSION));
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
+  #[test]
+  fn refuse_newer_schema()
+  {
+    let path = std::env::temp_dir().join("migration_test_newer.db");
+    let _ = std::fs::remove_file(&path);
+    // scope for db
+    {
+      let db = Database::create(path.to_str().unwrap()).unwrap();
+      let newer = Database::SCHEMA_VERSION + 1;
+      assert!(db.set_meta("schema_version", &newer.to_string()).is_ok());
+      assert!(matches!(db.migrate(&false), Err(Error::UnsupportedSchema { .. })));
+    }
+    match Database::new(path.to_str().unwrap())
+    {
+      Err(Error::UnsupportedSchema { version, supported }) =>
+      {
+        assert_eq!(version, Database::SCHEMA_VERSION + 1);
+        assert_eq!(supported, Database::SCHEMA_VERSION);
+      },
+      _ => panic!("Expected error about the schema version!")
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
   #[test]
   fn continents()
   {
     let db = get_sqlite_db_ecdc();
 
-    let continents = db.continents();
+    let continents = db.continents().unwrap();
     // Vector of continents must not be empty.
     assert!(!continents.is_empty());
     // Some continents shall be contained in the vector.
@@ -1032,7 +1631,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let countries = db.countries();
+    let countries = db.countries().unwrap();
     // Vector of countries must not be empty.
     assert!(!countries.is_empty());
     // There should be more than 200 countries.
@@ -1062,7 +1661,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let countries = db.countries_of_continent("Europe");
+    let countries = db.countries_of_continent("Europe").unwrap();
     // Vector of countries must not be empty.
     assert!(!countries.is_empty());
     // There should be less than 200 countries, because unlike countries() it
@@ -1091,7 +1690,7 @@ mod tests
     assert!(not_found.is_none());
 
     // Check for another continent.
-    let countries = db.countries_of_continent("Asia");
+    let countries = db.countries_of_continent("Asia").unwrap();
     // Vector of countries must not be empty.
     assert!(!countries.is_empty());
     // There should be less than 200 countries, because unlike countries() it
@@ -1129,13 +1728,13 @@ mod tests
       let db = Database::create(&path.to_str().unwrap()).unwrap();
 
       // geo_id: &str, name: &str, population: &i64, country_code: &str, continent
-      let id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia");
+      let id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia").unwrap();
       // Id -1 means an error occurred.
       assert!(id != -1);
       // First country usually gets id one.
       assert_eq!(1i64, id);
       // Country list should now contain the country.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       let wonderland = Country {
         country_id: 1,
         geo_id: String::from("XX"),
@@ -1167,15 +1766,15 @@ mod tests
       let db = Database::create(&path.to_str().unwrap()).unwrap();
 
       // geo_id: &str, name: &str, population: &i64, country_code: &str, continent
-      let first_id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia");
+      let first_id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia").unwrap();
       // Id -1 means an error occurred.
       assert!(first_id != -1);
       // Inserting the same country again should return the same id.
-      let second_id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia");
+      let second_id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia").unwrap();
       assert!(second_id != -1);
       assert_eq!(first_id, second_id);
       // But inserting another country should not return the same id.
-      let third_id = db.get_country_id_or_insert("ZZ", "Neuland", &42, "TBL", "Internet");
+      let third_id = db.get_country_id_or_insert("ZZ", "Neuland", &42, "TBL", "Internet").unwrap();
       assert!(third_id != -1);
       assert!(first_id != third_id);
     }
@@ -1183,12 +1782,64 @@ mod tests
     assert!(std::fs::remove_file(path).is_ok());
   }
 
+  #[test]
+  fn granularity()
+  {
+    let path = std::env::temp_dir().join("granularity_test.db");
+    let _ = std::fs::remove_file(&path);
+    // scope for db
+    {
+      let db = Database::create(path.to_str().unwrap()).unwrap();
+      let first_id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia").unwrap();
+      let second_id = db.get_country_id_or_insert("ZZ", "Neuland", &42, "TBL", "Internet").unwrap();
+      assert_eq!(db.granularity(&(first_id as i32)).unwrap(), Granularity::Daily);
+      assert!(db.set_granularity(&second_id, Granularity::Weekly).is_ok());
+      assert_eq!(db.granularity(&(first_id as i32)).unwrap(), Granularity::Daily);
+      assert_eq!(db.granularity(&(second_id as i32)).unwrap(), Granularity::Weekly);
+      assert_eq!(db.granularities().unwrap().len(), 2);
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
+  #[test]
+  fn granularity_of_old_database()
+  {
+    let path = std::env::temp_dir().join("granularity_test_old.db");
+    let _ = std::fs::remove_file(&path);
+    // The ECDC database was created before the granularity was recorded.
+    let original = Path::new(file!())
+      .parent()
+      .unwrap()
+      .join("..")
+      .join("data")
+      .join("corona-ecdc-2020-12-14.db");
+    assert!(std::fs::copy(original, &path).is_ok());
+    // scope for db
+    {
+      let db = Database::new(path.to_str().unwrap()).unwrap();
+      assert!(!db.has_granularity_column().unwrap());
+      assert!(db.granularities().unwrap().is_empty());
+      assert_eq!(db.granularity(&1).unwrap(), Granularity::Daily);
+      // The column is added by the migration.
+      assert!(db.migrate(&false).is_ok());
+      assert!(db.has_granularity_column().unwrap());
+      assert_eq!(db.granularity(&1).unwrap(), Granularity::Daily);
+      assert!(db.set_granularity(&1, Granularity::Weekly).is_ok());
+      assert!(db.has_granularity_column().unwrap());
+      assert_eq!(db.granularity(&1).unwrap(), Granularity::Weekly);
+      assert_eq!(db.granularity(&2).unwrap(), Granularity::Daily);
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
   #[test]
   fn numbers()
   {
     let db = get_sqlite_db_ecdc();
 
-    let numbers = db.numbers(&76);
+    let numbers = db.numbers(&76, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!numbers.is_empty());
     // There should be more than 300 entries, ...
@@ -1226,7 +1877,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let numbers = db.numbers_with_incidence(&76);
+    let numbers = db.numbers_with_incidence(&76, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!numbers.is_empty());
     // There should be more than 300 entries, ...
@@ -1275,7 +1926,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let numbers = db.numbers_world();
+    let numbers = db.numbers_world(&DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!numbers.is_empty());
     // There should be more than 300 entries, ...
@@ -1307,12 +1958,47 @@ mod tests
     assert_eq!(world_2020_01_30.deaths, found.deaths);
   }
 
+  #[test]
+  fn numbers_in_date_range()
+  {
+    let db = get_sqlite_db_ecdc();
+    let range = DateRange::new(Some(String::from("2020-03-28")),
+                               Some(String::from("2020-06-30"))).unwrap();
+
+    let numbers = db.numbers(&76, &range).unwrap();
+    assert_eq!(numbers.len(), 95);
+    assert_eq!(numbers.first().unwrap().date, "2020-03-28");
+    assert_eq!(numbers.last().unwrap().date, "2020-06-30");
+    assert!(numbers.iter().all(|n| n.date.as_str() >= "2020-03-28" && n.date.as_str() <= "2020-06-30"));
+
+    let world = db.numbers_world(&range).unwrap();
+    assert_eq!(world.len(), 95);
+
+    // Totals are still calculated from the full history.
+    let accumulated = db.accumulated_numbers(&76, &range).unwrap();
+    assert_eq!(accumulated.len(), 95);
+    let found = accumulated.iter().find(|&n| n.date == "2020-03-30");
+    assert!(found.is_some());
+    assert_eq!(found.unwrap().cases, 57298);
+    assert_eq!(found.unwrap().deaths, 455);
+
+    let incidence = db.incidence14(&76, &range).unwrap();
+    assert!(!incidence.is_empty());
+    assert!(incidence.iter().all(|i| i.date.as_str() >= "2020-03-28" && i.date.as_str() <= "2020-06-30"));
+
+    // range only with upper limit
+    let range = DateRange::new(None, Some(String::from("2020-01-31"))).unwrap();
+    let numbers = db.numbers_with_incidence(&76, &range).unwrap();
+    assert!(!numbers.is_empty());
+    assert!(numbers.iter().all(|n| n.date.as_str() <= "2020-01-31"));
+  }
+
   #[test]
   fn accumulated_numbers()
   {
     let db = get_sqlite_db_ecdc();
 
-    let numbers = db.accumulated_numbers(&76);
+    let numbers = db.accumulated_numbers(&76, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!numbers.is_empty());
     // There should be more than 300 entries, ...
@@ -1350,7 +2036,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let numbers = db.accumulated_numbers_world();
+    let numbers = db.accumulated_numbers_world(&DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!numbers.is_empty());
     // There should be more than 300 entries, ...
@@ -1387,7 +2073,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let incidences = db.incidence14(&76);
+    let incidences = db.incidence14(&76, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!incidences.is_empty());
     // There should be more than 300 entries, ...
@@ -1421,7 +2107,7 @@ mod tests
   {
     let db = get_sqlite_db_ecdc();
 
-    let incidences = db.incidence14(&118);
+    let incidences = db.incidence14(&118, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!incidences.is_empty());
     // There should be more than 300 entries, ...
@@ -1457,7 +2143,7 @@ mod tests
     let db = get_sqlite_db_rki();
 
     // Country id 77 is Germany in the current DB.
-    let incidences = db.incidence7(&77);
+    let incidences = db.incidence7(&77, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!incidences.is_empty());
     // There should be more than 300 entries, ...
@@ -1492,7 +2178,7 @@ mod tests
     let db = get_sqlite_db_jhu();
 
     // Country id 121 is Luxembourg in the JHU DB.
-    let incidences = db.incidence7(&121);
+    let incidences = db.incidence7(&121, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!incidences.is_empty());
     // There should be more than 300 entries, ...
@@ -1528,7 +2214,7 @@ mod tests
     let db = get_sqlite_db_rki();
 
     // Country id 77 is Germany in the current DB.
-    let incidences = db.incidence7_by_year(&77);
+    let incidences = db.incidence7_by_year(&77, &DateRange::default()).unwrap();
     // Vector of data must not be empty.
     assert!(!incidences.is_empty());
     // Data for 2020 and 2021 should exist.
@@ -1571,7 +2257,7 @@ mod tests
     // This is a no-op on the existing database, because it already has the
     // columns with the total numbers. However, this test checks that the
     // function works (i. e. returns true) in that case anyway.
-    assert!(db.calculate_total_numbers(&true));
+    assert!(db.calculate_total_numbers(&true).is_ok());
   }
 
   #[test]
@@ -1582,7 +2268,7 @@ mod tests
     {
       let db = Database::create(&path.to_str().unwrap()).unwrap();
       // Update of structure should succeed.
-      assert!(db.calculate_total_numbers(&true));
+      assert!(db.calculate_total_numbers(&true).is_ok());
     }
     // clean up
     assert!(std::fs::remove_file(path).is_ok());
@@ -1592,36 +2278,40 @@ mod tests
   fn calculate_total_numbers()
   {
     let path = std::env::temp_dir().join("test_calculate_totals.db");
+    let _ = std::fs::remove_file(&path);
     // scope for db
     {
-      let db = Database::create(&path.to_str().unwrap()).unwrap();
+      // Databases of the first schema have no columns for the total numbers.
+      let conn = Connection::open(&path).unwrap();
+      assert!(Database::create_first_schema(&conn).is_ok());
+      let db = Database { conn };
 
       // Insert some test data.
       let sql = "INSERT INTO country (\
           countryId, name, population, geoId, countryCode, continent) VALUES \
           (1, 'Wonderland', 42, 'XX', 'WON', 'Utopia'),\
           (2, 'Neuland', 1337, 'ZZ', 'TBL', 'Internet');";
-      assert!(db.batch(&sql));
+      assert!(db.batch(&sql).is_ok());
       let sql = "INSERT INTO covid19 (\
           countryId, date, cases, deaths, incidence14, incidence7) VALUES \
           (1, '2020-10-01', 123, 1, 23.45, 12.3),\
           (1, '2020-10-02', 234, 2, 34.56, 17.3),\
           (2, '2020-10-01', 3, 0, 11.22, 5.4),\
           (2, '2020-10-02', 5, 1, 22.33, 12.3);";
-      assert!(db.batch(&sql));
+      assert!(db.batch(&sql).is_ok());
 
       // Update of structure should succeed.
-      assert!(db.calculate_total_numbers(&true));
+      assert!(db.calculate_total_numbers(&true).is_ok());
 
       // Check accumulated numbers of 1st country.
-      let accumulated = db.accumulated_numbers(&1);
+      let accumulated = db.accumulated_numbers(&1, &DateRange::default()).unwrap();
       assert_eq!(2, accumulated.len());
       assert_eq!(123, accumulated[0].cases);
       assert_eq!(1, accumulated[0].deaths);
       assert_eq!(357, accumulated[1].cases);
       assert_eq!(3, accumulated[1].deaths);
       // Check accumulated numbers of 2nd country.
-      let accumulated = db.accumulated_numbers(&2);
+      let accumulated = db.accumulated_numbers(&2, &DateRange::default()).unwrap();
       assert_eq!(2, accumulated.len());
       assert_eq!(3, accumulated[0].cases);
       assert_eq!(0, accumulated[0].deaths);
@@ -1655,9 +2345,9 @@ mod tests
           (1, 'Wonderland', 42, 'XX', 'WON', 'Utopia'),\
           (2, 'Neuland', 1337, 'ZZ', 'TBL', 'Internet');";
       // Batch statement should execute successfully.
-      assert!(db.batch(&sql));
+      assert!(db.batch(&sql).is_ok());
       // Countries should exist.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       let wonderland = Country {
         country_id: 1,
         geo_id: String::from("XX"),
@@ -1707,9 +2397,9 @@ mod tests
       // Creation should be successful and file should exist.
       assert!(db.is_ok());
       let db = db.unwrap();
-      assert!(db.calculate_total_numbers(&false));
+      assert!(db.calculate_total_numbers(&false).is_ok());
       // Insert some country.
-      let id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia");
+      let id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia").unwrap();
       // Id -1 means an error occurred.
       assert!(id != -1);
       let data = vec![
@@ -1733,10 +2423,10 @@ mod tests
         },
       ];
       // Insert should succeed.
-      assert!(db.insert_data(&id, &data));
+      assert!(db.insert_data(&id, &data).is_ok());
       // Inserted data should exist.
       let id = id as i32;
-      let numbers = db.numbers(&id);
+      let numbers = db.numbers(&id, &DateRange::default()).unwrap();
       assert_eq!(2, numbers.len());
       assert_eq!("2020-10-01", numbers[0].date);
       assert_eq!(12345, numbers[0].cases);
@@ -1745,17 +2435,17 @@ mod tests
       assert_eq!(54321, numbers[1].cases);
       assert_eq!(1234, numbers[1].deaths);
       // Incidence (14-day) should exist - but only for one value.
-      let incidence = db.incidence14(&id);
+      let incidence = db.incidence14(&id, &DateRange::default()).unwrap();
       assert_eq!(1, incidence.len());
       assert_eq!("2020-10-02", incidence[0].date);
       assert_eq!(234.5, incidence[0].incidence_14d);
       // Incidence (7-day) should exist - but only for one value.
-      let incidence = db.incidence7(&id);
+      let incidence = db.incidence7(&id, &DateRange::default()).unwrap();
       assert_eq!(1, incidence.len());
       assert_eq!("2020-10-02", incidence[0].date);
       assert_eq!(112.3, incidence[0].incidence_7d);
       // Check total numbers.
-      let numbers = db.accumulated_numbers(&id);
+      let numbers = db.accumulated_numbers(&id, &DateRange::default()).unwrap();
       assert_eq!(2, numbers.len());
       assert_eq!("2020-10-01", numbers[0].date);
       assert_eq!(12345, numbers[0].cases);
@@ -1763,6 +2453,196 @@ mod tests
       assert_eq!("2020-10-02", numbers[1].date);
       assert_eq!(66666, numbers[1].cases);
       assert_eq!(1777, numbers[1].deaths);
+      // Inserting the same day again replaces the existing row.
+      let revised = vec![
+        NumbersAndIncidenceAndTotals {
+          date: "2020-10-02".to_string(),
+          cases: 54000,
+          deaths: 1200,
+          incidence_14d: Some(230.0),
+          incidence_7d: Some(110.0),
+          total_cases: 66345,
+          total_deaths: 1743
+        },
+      ];
+      assert!(db.insert_data(&(id as i64), &revised).is_ok());
+      let numbers = db.numbers(&id, &DateRange::default()).unwrap();
+      assert_eq!(2, numbers.len());
+      assert_eq!("2020-10-02", numbers[1].date);
+      assert_eq!(54000, numbers[1].cases);
+      assert_eq!(1200, numbers[1].deaths);
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
+  #[test]
+  fn remove_duplicates_on_migration()
+  {
+    let path = std::env::temp_dir().join("migration_test_duplicates.db");
+    let _ = std::fs::remove_file(&path);
+    // scope for db
+    {
+      let conn = Connection::open(&path).unwrap();
+      assert!(Database::create_first_schema(&conn).is_ok());
+      let db = Database { conn };
+      let sql = "INSERT INTO country (\
+          countryId, name, population, geoId, countryCode, continent) VALUES \
+          (1, 'Wonderland', 42, 'XX', 'WON', 'Utopia');";
+      assert!(db.batch(sql).is_ok());
+      let sql = "INSERT INTO covid19 (\
+          countryId, date, cases, deaths, incidence14, incidence7) VALUES \
+          (1, '2020-10-01', 123, 1, 23.45, 12.3),\
+          (1, '2020-10-02', 234, 2, 34.56, 17.3),\
+          (1, '2020-10-02', 345, 3, 45.67, 18.4);";
+      assert!(db.batch(sql).is_ok());
+
+      assert!(db.migrate(&false).is_ok());
+      assert!(has_index(&db, "covid19_country_date"));
+      // Only the row that has been inserted last is kept.
+      let numbers = db.numbers(&1, &DateRange::default()).unwrap();
+      assert_eq!(2, numbers.len());
+      assert_eq!("2020-10-02", numbers[1].date);
+      assert_eq!(345, numbers[1].cases);
+      assert_eq!(3, numbers[1].deaths);
+      // Accumulated numbers do not include the removed row.
+      let totals = db.accumulated_numbers(&1, &DateRange::default()).unwrap();
+      assert_eq!(2, totals.len());
+      assert_eq!(123, totals[0].cases);
+      assert_eq!(468, totals[1].cases);
+      assert_eq!(1, totals[0].deaths);
+      assert_eq!(4, totals[1].deaths);
+      // The unique index prevents further duplicates.
+      let sql = "INSERT INTO covid19 (countryId, date, cases, deaths) VALUES (1, '2020-10-01', 5, 0);";
+      assert!(db.batch(sql).is_err());
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
+  #[test]
+  fn remove_duplicates_recalculates_incidence()
+  {
+    let path = std::env::temp_dir().join("migration_test_duplicates_incidence.db");
+    let _ = std::fs::remove_file(&path);
+    // scope for db
+    {
+      let conn = Connection::open(&path).unwrap();
+      assert!(Database::create_first_schema(&conn).is_ok());
+      let db = Database { conn };
+      let sql = "INSERT INTO country (\
+          countryId, name, population, geoId, countryCode, continent) VALUES \
+          (1, 'Wonderland', 100000, 'XX', 'WON', 'Utopia');";
+      assert!(db.batch(sql).is_ok());
+      // The incidence values were calculated with the first row of the last
+      // day, which is a duplicate.
+      let sql = "INSERT INTO covid19 (\
+          countryId, date, cases, deaths, incidence14, incidence7) VALUES \
+          (1, '2020-10-01', 10, 0, NULL, NULL),\
+          (1, '2020-10-02', 10, 0, NULL, NULL),\
+          (1, '2020-10-03', 10, 0, NULL, NULL),\
+          (1, '2020-10-04', 10, 0, NULL, NULL),\
+          (1, '2020-10-05', 10, 0, NULL, NULL),\
+          (1, '2020-10-06', 10, 0, NULL, NULL),\
+          (1, '2020-10-07', 10, 0, NULL, 70.0),\
+          (1, '2020-10-07', 20, 0, NULL, 70.0);";
+      assert!(db.batch(sql).is_ok());
+
+      assert!(db.migrate(&false).is_ok());
+      let incidence = db.incidence7(&1, &DateRange::default()).unwrap();
+      assert_eq!(1, incidence.len());
+      assert_eq!("2020-10-07", incidence[0].date);
+      assert!((incidence[0].incidence_7d - 80.0).abs() < 0.000001);
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
+  #[test]
+  fn provenance()
+  {
+    // Old databases have no provenance table.
+    let db = get_sqlite_db_ecdc();
+    assert!(db.provenance().unwrap().is_empty());
+    assert_eq!(db.last_date().unwrap(), Some(String::from("2020-12-14")));
+
+    let path = std::env::temp_dir().join("provenance_test.db");
+    let _ = std::fs::remove_file(&path);
+    // scope for db
+    {
+      let db = Database::create(path.to_str().unwrap()).unwrap();
+      assert!(db.provenance().unwrap().is_empty());
+      assert_eq!(db.last_date().unwrap(), None);
+      let first = Provenance {
+        format: String::from("OWID"),
+        attribution: String::from("Our World in Data"),
+        file: String::from("owid-covid-data.csv"),
+        sha256: Some(String::from("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")),
+        imported_at: String::from("2026-10-18T12:34:56Z"),
+        version: String::from("0.20.1"),
+        rows_read: 1000,
+        rows_kept: 990,
+        rows_skipped: 10
+      };
+      let second = Provenance {
+        format: String::from("WHO"),
+        attribution: String::from("World Health Organization (WHO)"),
+        file: String::from("-"),
+        sha256: None,
+        imported_at: String::from("2026-10-18T13:00:00Z"),
+        version: String::from("0.20.1"),
+        rows_read: 5,
+        rows_kept: 5,
+        rows_skipped: 0
+      };
+      assert!(db.insert_provenance(&first).is_ok());
+      assert!(db.insert_provenance(&second).is_ok());
+      // Imports are listed in the order in which they happened.
+      assert_eq!(db.provenance().unwrap(), vec![first, second]);
+    }
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+  }
+
+  #[test]
+  fn metrics_of_old_database()
+  {
+    let db = get_sqlite_db_ecdc();
+    assert!(db.available_metrics(&1).unwrap().is_empty());
+    assert!(db.metrics(&1, &DateRange::default()).unwrap().is_empty());
+  }
+
+  #[test]
+  fn insert_metrics()
+  {
+    let path = std::env::temp_dir().join("insert_metrics_test.db");
+    // scope for db
+    {
+      let db = Database::create(path.to_str().unwrap()).unwrap();
+      let id = db.get_country_id_or_insert("XX", "Wonderland", &421337, "WON", "Utopia").unwrap();
+      let value = |date: &str, metric: Metric, value: f64| MetricValue { date: date.to_string(), metric, value };
+      let data = vec![
+        value("2021-05-02", Metric::IcuPatients, 12.0),
+        value("2021-05-01", Metric::PositiveRate, 0.05),
+        value("2021-05-01", Metric::IcuPatients, 10.0)
+      ];
+      assert!(db.insert_metrics(&id, &data).is_ok());
+      // Values of the same day and metric are replaced.
+      assert!(db.insert_metrics(&id, &[value("2021-05-02", Metric::IcuPatients, 14.0)]).is_ok());
+
+      let id = id as i32;
+      assert_eq!(db.available_metrics(&id).unwrap(), vec![Metric::IcuPatients, Metric::PositiveRate]);
+      let all = db.metrics(&id, &DateRange::default()).unwrap();
+      assert_eq!(all, vec![
+        value("2021-05-01", Metric::IcuPatients, 10.0),
+        value("2021-05-01", Metric::PositiveRate, 0.05),
+        value("2021-05-02", Metric::IcuPatients, 14.0)
+      ]);
+      let range = DateRange::new(Some(String::from("2021-05-02")), None).unwrap();
+      assert_eq!(db.metric(&id, Metric::IcuPatients, &range).unwrap(), vec![value("2021-05-02", Metric::IcuPatients, 14.0)]);
+      assert!(db.metric(&id, Metric::NewTests, &DateRange::default()).unwrap().is_empty());
+      // Other countries have no metrics.
+      assert!(db.available_metrics(&(id + 1)).unwrap().is_empty());
     }
     // clean up
     assert!(std::fs::remove_file(path).is_ok());
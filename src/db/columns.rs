@@ -0,0 +1,173 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::error::Error;
+use csv::StringRecord;
+
+/// Names of the columns of a CSV file, as given in its header line.
+///
+/// Importers use it to find the position of a column by its name, so that
+/// files with reordered or additional columns can still be read.
+///
+/// ```
+/// use corona::db::Columns;
+///
+/// let columns = Columns::from_line("date,region,cases,deaths");
+/// let [cases, date] = columns.require(["cases", "date"])?;
+/// assert_eq!((cases, date), (2, 0));
+/// assert!(columns.require(["date", "population"]).is_err());
+/// # Ok::<(), corona::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Columns
+{
+  names: Vec<String>
+}
+
+impl Columns
+{
+  /**
+   * Creates the columns from the headers of a CSV file.
+   *
+   * @param headers   the header record
+   * @return Returns the columns.
+   */
+  pub fn new(headers: &StringRecord) -> Columns
+  {
+    let names = headers
+      .iter()
+      .enumerate()
+      .map(|(idx, name)| {
+        // A byte order mark may stick to the first header.
+        let name = match idx
+        {
+          0 => name.trim_start_matches('\u{feff}'),
+          _ => name
+        };
+        name.trim().to_string()
+      })
+      .collect();
+    Columns { names }
+  }
+
+  /**
+   * Creates the columns from the first line of a CSV file.
+   *
+   * @param line   the first line of the file
+   * @return Returns the columns. If the line cannot be parsed as CSV, the
+   *         result contains no columns.
+   */
+  pub fn from_line(line: &str) -> Columns
+  {
+    let mut reader = csv::ReaderBuilder::new()
+      .has_headers(false)
+      .from_reader(line.as_bytes());
+    match reader.records().next()
+    {
+      Some(Ok(headers)) => Columns::new(&headers),
+      _ => Columns { names: Vec::new() }
+    }
+  }
+
+  /**
+   * Gets the position of a column.
+   *
+   * @param name   name of the column, e. g. "date"
+   * @return Returns the zero-based position of the first column with that name.
+   *         Returns None, if there is no such column.
+   */
+  pub fn position(&self, name: &str) -> Option<usize>
+  {
+    self.names.iter().position(|n| n == name)
+  }
+
+  /**
+   * Checks whether all of the given columns exist.
+   *
+   * @param names   names of the columns
+   * @return Returns true, if every column exists.
+   */
+  pub fn contains_all(&self, names: &[&str]) -> bool
+  {
+    names.iter().all(|name| self.position(name).is_some())
+  }
+
+  /**
+   * Gets the positions of columns that are required to read a file.
+   *
+   * @param names   names of the required columns
+   * @return Returns the zero-based positions in the same order as the names.
+   *         Returns Error::MissingColumns listing every missing column, if
+   *         at least one of them does not exist.
+   */
+  pub fn require<const N: usize>(&self, names: [&str; N]) -> Result<[usize; N], Error>
+  {
+    let missing: Vec<String> = names
+      .iter()
+      .filter(|name| self.position(name).is_none())
+      .map(|name| name.to_string())
+      .collect();
+    if !missing.is_empty()
+    {
+      return Err(Error::MissingColumns(missing));
+    }
+    Ok(names.map(|name| self.position(name).unwrap_or_default()))
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  #[test]
+  fn from_headers_with_bom()
+  {
+    let headers = StringRecord::from(vec!["\u{feff}Date_reported", "Country_code", " Country "]);
+    let columns = Columns::new(&headers);
+    assert_eq!(columns.position("Date_reported"), Some(0));
+    assert_eq!(columns.position("Country"), Some(2));
+    assert_eq!(columns.position("WHO_region"), None);
+  }
+
+  #[test]
+  fn from_line()
+  {
+    let columns = Columns::from_line("\u{feff}a,\"b,c\",d");
+    assert_eq!(columns.position("a"), Some(0));
+    assert_eq!(columns.position("b,c"), Some(1));
+    assert_eq!(columns.position("d"), Some(2));
+    assert!(columns.contains_all(&["d", "a"]));
+    assert!(!columns.contains_all(&["a", "b"]));
+    assert!(!Columns::from_line("").contains_all(&["a"]));
+  }
+
+  #[test]
+  fn require()
+  {
+    let columns = Columns::from_line("country,date,new_cases,new_deaths,code,continent");
+    let positions = columns.require(["code", "continent", "date"]);
+    assert_eq!(positions.ok(), Some([4, 5, 1]));
+
+    let missing = columns.require(["date", "iso_code", "population"]);
+    match missing
+    {
+      Err(Error::MissingColumns(names)) => assert_eq!(names, vec!["iso_code", "population"]),
+      _ => panic!("Expected missing columns!")
+    }
+  }
+}
@@ -0,0 +1,229 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::database::Database;
+use crate::db::jhu::{CumulativeNumbers, DbJhu};
+use crate::db::{CsvType, ImportReport, Importer, Input};
+use crate::error::Error;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// historical data of one country (or province) as returned by disease.sh
+#[derive(Deserialize)]
+struct Historical
+{
+  /// name of the country as used by the Johns Hopkins University
+  country: String,
+  /// accumulated numbers
+  timeline: Timeline
+}
+
+/// accumulated numbers, indexed by dates like "3/9/23"
+#[derive(Deserialize)]
+struct Timeline
+{
+  #[serde(default)]
+  cases: BTreeMap<String, i64>,
+  #[serde(default)]
+  deaths: BTreeMap<String, i64>
+}
+
+/// content of a JSON file: either a list of countries, as returned by
+/// /v3/covid-19/historical, or a single country, as returned by
+/// /v3/covid-19/historical/{country}
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Document
+{
+  List(Vec<Historical>),
+  Single(Historical)
+}
+
+/// importer for JSON files with the historical data from the disease.sh API
+///
+/// The API is not queried, the files have to be downloaded beforehand, e. g.
+/// from <https://disease.sh/v3/covid-19/historical?lastdays=all>.
+pub struct DbDiseaseSh;
+
+impl Importer for DbDiseaseSh
+{
+  fn csv_type(&self) -> CsvType
+  {
+    CsvType::DiseaseSh
+  }
+
+  fn detect(&self, first_line: &str) -> bool
+  {
+    // None of the CSV formats can start with a bracket or a brace, but other
+    // JSON data is left to other importers, so the key of the time series
+    // has to be present, too.
+    let data = first_line.trim_start_matches('\u{feff}').trim_start();
+    (data.starts_with('[') || data.starts_with('{')) && data.contains("\"timeline\"")
+  }
+
+  fn detect_input(&self, input: &Input) -> bool
+  {
+    // Pretty-printed files have the key in one of the following lines.
+    self.detect(&input.head())
+  }
+
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
+  {
+    use std::io::Read;
+
+    let mut input = input;
+    let mut content = String::new();
+    input.read_to_string(&mut content).map_err(|e| Error::io(std::path::Path::new(input.name()), e))?;
+    let (confirmed, deaths) = DbDiseaseSh::parse(&content).map_err(|e| Error::InvalidData(format!(
+      "File {} does not contain historical data from disease.sh: {e}", input.name()
+    )))?;
+    DbJhu::save_into_db(db, confirmed, deaths, report)
+  }
+}
+
+impl DbDiseaseSh
+{
+  /**
+   * Parses the JSON data and adds up the numbers of all provinces of a
+   * country.
+   *
+   * @param content   content of the JSON file
+   * @return Returns the accumulated cases and deaths per country and date in
+   *         case of success. Returns an error message otherwise.
+   */
+  fn parse(content: &str) -> Result<(CumulativeNumbers, CumulativeNumbers), String>
+  {
+    let content = content.trim_start_matches('\u{feff}');
+    let entries = match serde_json::from_str(content).map_err(|e| e.to_string())?
+    {
+      Document::List(list) => list,
+      Document::Single(single) => vec![single]
+    };
+    let mut confirmed = CumulativeNumbers::new();
+    let mut deaths = CumulativeNumbers::new();
+    for entry in entries.iter()
+    {
+      let timelines = [(&entry.timeline.cases, &mut confirmed), (&entry.timeline.deaths, &mut deaths)];
+      for (timeline, numbers) in timelines
+      {
+        let country = numbers.entry(entry.country.clone()).or_default();
+        for (date, value) in timeline.iter()
+        {
+          let date = NaiveDate::parse_from_str(date, "%m/%d/%y")
+            .map_err(|_| format!("'{date}' is not a valid date."))?
+            .format("%Y-%m-%d")
+            .to_string();
+          *country.entry(date).or_insert(0) += value;
+        }
+      }
+    }
+    Ok((confirmed, deaths))
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::data::DateRange;
+  use crate::db::Db;
+
+  #[test]
+  fn detect()
+  {
+    assert!(DbDiseaseSh.detect("[{\"country\":\"Germany\",\"province\":null,\"timeline\":{\"cases\":{}}}]"));
+    assert!(DbDiseaseSh.detect("\u{feff}  {\"country\":\"Germany\",\"timeline\":{\"cases\":{}}}"));
+    assert!(!DbDiseaseSh.detect("Province/State,Country/Region,Lat,Long,1/22/20"));
+    // Other JSON data is not claimed.
+    assert!(!DbDiseaseSh.detect("["));
+    assert!(!DbDiseaseSh.detect("[{\"country\":\"Germany\",\"cases\":1}]"));
+    assert!(!DbDiseaseSh.detect("{\"region\":\"XX\",\"values\":[1,2,3]}"));
+  }
+
+  #[test]
+  fn parse_single_country()
+  {
+    let json = r#"{
+  "country": "Germany",
+  "province": ["mainland"],
+  "timeline": {
+    "cases": { "3/7/23": 100, "3/8/23": 120, "3/9/23": 125 },
+    "deaths": { "3/7/23": 1, "3/8/23": 1, "3/9/23": 2 },
+    "recovered": { "3/7/23": 0, "3/8/23": 0, "3/9/23": 0 }
+  }
+}"#;
+    let parsed = DbDiseaseSh::parse(json);
+    assert!(parsed.is_ok());
+    let (confirmed, deaths) = parsed.unwrap();
+    assert_eq!(confirmed["Germany"]["2023-03-09"], 125);
+    assert_eq!(deaths["Germany"]["2023-03-08"], 1);
+  }
+
+  #[test]
+  fn parse_errors()
+  {
+    assert!(DbDiseaseSh::parse("[{\"country\": \"Germany\"}]").is_err());
+    assert!(DbDiseaseSh::parse("{\"message\": \"Country not found\"}").is_err());
+    let invalid_date = "[{\"country\": \"Germany\", \"timeline\": {\"cases\": {\"2023-03-09\": 1}}}]";
+    assert!(matches!(DbDiseaseSh::parse(invalid_date), Err(e) if e.contains("2023-03-09")));
+  }
+
+  #[test]
+  fn import()
+  {
+    let dir = std::env::temp_dir();
+    let json_path = dir.join("test_disease_sh_import.json");
+    let db_path = dir.join("test_disease_sh_import.db");
+    let _ = std::fs::remove_file(&db_path);
+    let json = r#"[
+{"country":"Australia","province":"new south wales","timeline":{"cases":{"3/7/23":10,"3/8/23":15},"deaths":{"3/7/23":1,"3/8/23":1}}},
+{"country":"Australia","province":"victoria","timeline":{"cases":{"3/7/23":5,"3/8/23":9},"deaths":{"3/7/23":0,"3/8/23":1}}},
+{"country":"Korea, South","province":null,"timeline":{"cases":{"3/7/23":7,"3/8/23":8},"deaths":{"3/7/23":0,"3/8/23":0}}}
+]"#;
+    assert!(std::fs::write(&json_path, json).is_ok());
+    let config = DbConfiguration {
+      csv_input_file: json_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert_eq!(Db::get_csv_type(&config.csv_input_file).ok(), Some(CsvType::DiseaseSh));
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      assert_eq!(db.meta("source_format").unwrap(), Some(String::from("disease.sh JSON")));
+      let countries = db.countries().unwrap();
+      assert_eq!(countries.len(), 2);
+      let australia = countries.iter().find(|c| c.geo_id == "AU").unwrap();
+      let mut numbers = db.numbers(&australia.country_id, &DateRange::default()).unwrap();
+      numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+      assert_eq!(numbers.len(), 2);
+      assert_eq!((numbers[0].cases, numbers[0].deaths), (15, 1));
+      assert_eq!((numbers[1].cases, numbers[1].deaths), (9, 1));
+      assert!(countries.iter().any(|c| c.geo_id == "KR"));
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(json_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+}
@@ -15,130 +15,50 @@
  -------------------------------------------------------------------------------
 */
 
-use crate::configuration::DbConfiguration;
 use crate::data::NumbersAndIncidence;
 use crate::database::Database;
-use crate::db::save;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input};
+use crate::error::Error;
 use csv::Reader;
 
-pub struct DbEcdc
-{
-  config: DbConfiguration
-}
+/// importer for ECDC's CSV format
+pub struct DbEcdc;
 
-impl DbEcdc
+impl Importer for DbEcdc
 {
-  /**
-   * Creates a new instance.
-   *
-   * @config   application configuration
-   * @return   Returns a Result containing the Db object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
-   */
-  pub fn new(config: &DbConfiguration) -> Result<DbEcdc, String>
+  fn csv_type(&self) -> CsvType
   {
-    if config.db_path.is_empty()
-    {
-      return Err("Path for SQLite database must not be an empty string!".to_string());
-    }
-    if config.csv_input_file.is_empty()
-    {
-      return Err("Path of CSV file must be set to a non-empty string!".to_string());
-    }
-
-    Ok(DbEcdc
-    {
-      config: DbConfiguration
-      {
-        csv_input_file: config.csv_input_file.clone(),
-        db_path: config.db_path.clone()
-      }
-    })
+    CsvType::Ecdc
   }
 
-  /**
-   * Creates the SQLite database from a CSV file.
-   *
-   * @return Returns whether the operation was successful.
-   */
-  pub fn create_db(&self) -> bool
+  fn detect(&self, first_line: &str) -> bool
   {
-    match crate::checks::sqlite_check()
-    {
-      crate::checks::Status::Error(msg) =>
-      {
-        eprintln!("{msg}");
-        return false;
-      },
-      crate::checks::Status::Warn(msg) => println!("Warning: {msg}"),
-      _ => ()
-    }
-    let db = match Database::create(&self.config.db_path)
-    {
-      Err(e) =>
-      {
-        eprintln!("Error while creating database: {e}");
-        return false;
-      }
-      Ok(base) => base
-    };
-    if !db.calculate_total_numbers(&false)
-    {
-      eprintln!("Error: Could not add columns for accumulated numbers to database table!");
-      return false;
-    }
-    self.read_csv(&db)
+    Columns::from_line(first_line).contains_all(&["dateRep", "day", "month", "year", "cases", "deaths", "geoId"])
   }
 
-  /**
-   * Fills the SQLite database with values from the CSV file.
-   *
-   * @param db    an open SQLite database with existing tables
-   * @param
-   */
-  fn read_csv(&self, db: &Database) -> bool
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
   {
-    let mut reader = match Reader::from_path(&self.config.csv_input_file)
-    {
-      Ok(rdr) => rdr,
-      Err(e) => {
-        eprintln!("Error: Could not open CSV file {}: {}",
-                  self.config.csv_input_file, e);
-        return false;
-      }
-    };
-    // Check headers.
-    if !self.check_headers(&mut reader)
-    {
-      return false;
-    }
-    // parse CSV values
-    DbEcdc::parse_csv_into_db(db, &mut reader)
+    let mut reader = Reader::from_reader(input);
+    DbEcdc::parse_csv_into_db(db, &mut reader, report)
   }
+}
 
+impl DbEcdc
+{
   /**
-   * Checks whether the CSV headers match the expected headers.
+   * Parses the CSV data and writes it into the database.
    *
+   * @param db        an open SQLite database with existing tables
    * @param reader    an opened CSV reader
-   * @return Returns true, if the headers are correct. Returns false otherwise.
+   * @param report    report that collects statistics about the rows
+   * @return Returns an Error, if the data could not be parsed or written to
+   *         the database.
    */
-  fn check_headers(&self, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn parse_csv_into_db<R: std::io::Read>(db: &Database, reader: &mut csv::Reader<R>, report: &mut ImportReport) -> Result<(), Error>
   {
-    let headers = match reader.headers()
-    {
-      Ok(head) => head,
-      Err(e) =>
-      {
-        eprintln!(
-          "Error: Could not read header of CSV file {}: {}",
-          self.config.csv_input_file, e
-        );
-        return false;
-      }
-    };
-    let expected_headers = vec![
-      "dateRep",
+    let columns = Columns::new(reader.headers()?);
+    let [idx_day, idx_month, idx_year, idx_cases, idx_deaths, idx_name, idx_geo_id,
+         idx_country_code, idx_population, idx_continent, idx_incidence14] = columns.require([
       "day",
       "month",
       "year",
@@ -149,77 +69,32 @@ impl DbEcdc
       "countryterritoryCode",
       "popData2019",
       "continentExp",
-      "Cumulative_number_for_14_days_of_COVID-19_cases_per_100000",
-      "Cumulative_number_for_7_days_of_COVID-19_cases_per_100000",
-    ];
-    if headers != expected_headers && headers != expected_headers[0..12]
-    {
-      eprintln!("Error: CSV headers do not match the expected headers. \
-                 Found the following headers: {headers:?}");
-      return false;
-    }
-    // Headers match. :)
-    true
-  }
-
-  /**
-   * Parses the CSV data and writes it into the database.
-   *
-   * @param db        an open SQLite database with existing tables
-   * @param reader    an opened CSV reader
-   * @return Returns true, if all data was parsed and written to the database
-   *         successfully. Returns false otherwise.
-   */
-  fn parse_csv_into_db(db: &Database, reader: &mut csv::Reader<std::fs::File>) -> bool
-  {
+      "Cumulative_number_for_14_days_of_COVID-19_cases_per_100000"
+    ])?;
+    // Older files do not have the 7-day incidence.
+    let idx_incidence7 = columns.position("Cumulative_number_for_7_days_of_COVID-19_cases_per_100000");
     let mut last_geo_id = String::new();
     let mut country_id: i64 = -1;
     let mut record = csv::StringRecord::new();
     let mut numbers = Vec::<NumbersAndIncidence>::new();
-    loop
+    while reader.read_record(&mut record)?
     {
-      match reader.read_record(&mut record)
-      {
-        Ok(success) =>
-        {
-          if !success
-          {
-            // No more records to read.
-            break;
-          }
-        },
-        Err(e) => {
-          // Failed to read.
-          eprintln!("Failed to read CSV record! {e}");
-          return false;
-        }
-      }
-      let r_len = record.len();
-      if r_len != 13 && r_len != 12
-      {
-        eprintln!("Error: A line of CSV data does not have twelve or thirteen data elements, \
-                   but {} elements instead!\nThe line position is '{}'. It will be skipped.",
-                   r_len, record.position().unwrap().line());
-        return false;
-      }
-      let current_geo_id = record.get(7).unwrap();
+      let current_geo_id = &record[idx_geo_id];
       if current_geo_id != last_geo_id
       {
         // Insert data of previous country.
         if country_id != -1
-          && !save::numbers_and_incidence_into_db(db, &country_id, &mut numbers)
         {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
+          save::numbers_and_incidence_into_db(db, &country_id, &mut numbers)?;
         }
         // Clear vector, because the data in it was already saved.
         numbers.clear();
         // new country
-        let name = record.get(6).unwrap().replace('_', " ");
-        let country_code = record.get(8).unwrap();
+        let name = record[idx_name].replace('_', " ");
+        let country_code = &record[idx_country_code];
         // Default for population values that cannot be parsed is -1.
-        let population: i64 = record.get(9).unwrap().parse().unwrap_or(-1);
-        let continent = record.get(10).unwrap();
+        let population: i64 = record[idx_population].parse().unwrap_or(-1);
+        let continent = &record[idx_continent];
         // Get country id or insert country.
         country_id = db.get_country_id_or_insert(
           current_geo_id,
@@ -227,39 +102,30 @@ impl DbEcdc
           &population,
           country_code,
           continent
-        );
-        if country_id == -1
-        {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
-        }
+        )?;
         last_geo_id = current_geo_id.to_string();
       }
       // Add current record.
-      let day = record.get(1).unwrap();
-      let month = record.get(2).unwrap();
-      let year = record.get(3).unwrap();
-      let cases: i32 = match record.get(4).unwrap().is_empty()
+      let day = &record[idx_day];
+      let month = &record[idx_month];
+      let year = &record[idx_year];
+      let cases: i32 = match record[idx_cases].is_empty()
       {
-        false => record.get(4).unwrap().parse().unwrap_or(i32::MIN),
+        false => record[idx_cases].parse().unwrap_or(i32::MIN),
         true => 0
       };
-      let deaths: i32 = match record.get(5).unwrap().is_empty()
+      let deaths: i32 = match record[idx_deaths].is_empty()
       {
-        false => record.get(5).unwrap().parse().unwrap_or(i32::MIN),
+        false => record[idx_deaths].parse().unwrap_or(i32::MIN),
         true => 0
       };
       if cases == i32::MIN || deaths == i32::MIN
       {
-        eprintln!(
-          "Error: Got invalid case numbers on line {}.",
-          record.position().unwrap().line()
-        );
-        return false;
+        return Err(Error::csv_parse(&record, "Got invalid case numbers."));
       }
-      let incidence14: f64 = match record.get(11).unwrap().is_empty()
+      let incidence14: f64 = match record[idx_incidence14].is_empty()
       {
-        false => record.get(11).unwrap().parse().unwrap_or(f64::NAN /* NaN */),
+        false => record[idx_incidence14].parse().unwrap_or(f64::NAN /* NaN */),
         true => f64::NAN /* NaN */
       };
       let incidence_14d = match incidence14.is_nan()
@@ -267,9 +133,10 @@ impl DbEcdc
         false => Some(incidence14),
         true => None
       };
-      let incidence7: f64 = match record.get(12).unwrap_or_default().is_empty()
+      let incidence7_value = idx_incidence7.and_then(|idx| record.get(idx)).unwrap_or_default();
+      let incidence7: f64 = match incidence7_value.is_empty()
       {
-        false => record.get(12).unwrap().parse().unwrap_or(f64::NAN /* NaN */),
+        false => incidence7_value.parse().unwrap_or(f64::NAN /* NaN */),
         true => f64::NAN /* NaN */
       };
       let incidence_7d = match incidence7.is_nan()
@@ -287,17 +154,16 @@ impl DbEcdc
       numbers.push(NumbersAndIncidence{
         date, cases, deaths, incidence_14d, incidence_7d
       });
+      report.kept(&record[idx_name], 1);
     }
     // Execute remaining batch inserts, if any are left.
     if !numbers.is_empty()
-      && !save::numbers_and_incidence_into_db(db, &country_id, &mut numbers)
     {
-      eprintln!("Error: Could not batch-insert case numbers into database!");
-      return false;
-    } // if data remains
+      save::numbers_and_incidence_into_db(db, &country_id, &mut numbers)?;
+    }
 
     // Done.
-    true
+    Ok(())
   }
 }
 
@@ -305,7 +171,9 @@ impl DbEcdc
 mod tests
 {
   use super::*;
-  use crate::data::Country;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::db::Db;
+  use crate::data::{Country, DateRange};
 
   /**
    * Gets path to the corona_daily.csv file in data directory.
@@ -335,18 +203,22 @@ mod tests
     let db_file_name = env::temp_dir().join("test_csv_corona_ecdc.db");
     let config = DbConfiguration {
       db_path: db_file_name.to_str().unwrap().to_string(),
-      csv_input_file: get_csv_path()
+      csv_input_file: get_csv_path(),
+      quiet: false,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
     };
     // scope for db
     {
-      let db = DbEcdc::new(&config).unwrap();
-      assert!(db.create_db());
+      let db = Db::new(&config).unwrap();
+      assert!(db.create_db_with(&DbEcdc).is_ok());
       // Check that DB file exists.
       assert!(db_file_name.exists());
       // Check some content.
       let db = Database::new(&config.db_path).unwrap();
       // Check a country.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       let wf = Country
       {
         country_id: 210,
@@ -366,7 +238,7 @@ mod tests
       assert_eq!(wf.country_code, found.country_code);
       assert_eq!(wf.continent, found.continent);
       // Check some numbers.
-      let numbers = db.numbers_with_incidence(&wf.country_id);
+      let numbers = db.numbers_with_incidence(&wf.country_id, &DateRange::default()).unwrap();
       let found = numbers.iter().find(|&n| n.date == "2020-11-26");
       assert!(found.is_some());
       let found = found.unwrap();
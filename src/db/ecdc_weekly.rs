@@ -0,0 +1,328 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::data::{Granularity, NumbersAndIncidence};
+use crate::database::Database;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input, SkipReason};
+use crate::error::Error;
+use crate::world::World;
+use chrono::{Days, NaiveDate, Weekday};
+use csv::Reader;
+use std::collections::BTreeMap;
+
+/// numbers of one country for a single week
+#[derive(Default)]
+struct Week
+{
+  /// number of new cases in that week
+  cases: i32,
+  /// number of new deaths in that week
+  deaths: i32,
+  /// 14-day notification rate of cases per 100000 inhabitants as given by ECDC
+  rate_14_day: Option<f64>
+}
+
+/// data of one country as given in the file
+struct CountryWeeks
+{
+  /// name of the country
+  name: String,
+  /// number of inhabitants, -1 if unknown
+  population: i64,
+  /// name of the continent
+  continent: String,
+  /// numbers per week, indexed by the last day of the week
+  weeks: BTreeMap<String, Week>
+}
+
+/// importer for ECDC's weekly CSV format as used after 14th December 2020
+///
+/// Every row contains the numbers of one indicator (cases or deaths) of one
+/// country for a whole ISO week. The numbers are stored as one row per week,
+/// dated to the last day (Sunday) of the week.
+pub struct DbEcdcWeekly;
+
+impl Importer for DbEcdcWeekly
+{
+  fn csv_type(&self) -> CsvType
+  {
+    CsvType::EcdcWeekly
+  }
+
+  fn detect(&self, first_line: &str) -> bool
+  {
+    Columns::from_line(first_line).contains_all(&["country_code", "indicator", "weekly_count", "year_week"])
+  }
+
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
+  {
+    let mut reader = Reader::from_reader(input);
+    DbEcdcWeekly::parse_csv_into_db(db, &mut reader, report)
+  }
+}
+
+impl DbEcdcWeekly
+{
+  /**
+   * Gets the last day of an ISO week.
+   *
+   * @param year_week   year and week, e. g. "2021-07" or "2021-W07"
+   * @return Returns the date of the Sunday of that week in YYYY-MM-DD format.
+   *         Returns None, if the value is not a valid week.
+   */
+  fn end_of_week(year_week: &str) -> Option<String>
+  {
+    let (year, week) = year_week.split_once('-')?;
+    let week = week.strip_prefix('W').unwrap_or(week);
+    let date = NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Sun)?;
+    Some(date.format("%Y-%m-%d").to_string())
+  }
+
+  /**
+   * Calculates the incidence values of the weeks of a country.
+   *
+   * The 7-day incidence is the weekly number of cases per 100000 inhabitants.
+   * The 14-day incidence is taken from the file, if it is given there, or it
+   * is calculated from the cases of the week and of the week before.
+   *
+   * @param weeks        numbers per week, indexed by the last day of the week
+   * @param population   number of inhabitants, -1 if unknown
+   * @return Returns the weekly numbers with incidence values.
+   */
+  fn weekly_incidence(weeks: &BTreeMap<String, Week>, population: i64) -> Vec<NumbersAndIncidence>
+  {
+    let mut result = Vec::with_capacity(weeks.len());
+    for (date, week) in weeks.iter()
+    {
+      let (incidence_14d, incidence_7d) = if population > 0
+      {
+        let previous_week = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+          .ok()
+          .and_then(|d| d.checked_sub_days(Days::new(7)))
+          .and_then(|d| weeks.get(&d.format("%Y-%m-%d").to_string()));
+        let incidence_14d = week.rate_14_day.or_else(|| {
+          previous_week.map(|previous| f64::from(week.cases + previous.cases) * 100_000.0 / population as f64)
+        });
+        (incidence_14d, Some(f64::from(week.cases) * 100_000.0 / population as f64))
+      }
+      else
+      {
+        (week.rate_14_day, None)
+      };
+      result.push(NumbersAndIncidence {
+        date: date.clone(),
+        cases: week.cases,
+        deaths: week.deaths,
+        incidence_14d,
+        incidence_7d
+      });
+    }
+    result
+  }
+
+  /**
+   * Parses the CSV data and writes it into the database.
+   *
+   * @param db        an open SQLite database with existing tables
+   * @param reader    an opened CSV reader
+   * @param report    report that collects statistics about the rows
+   * @return Returns an Error, if the data could not be parsed or written to
+   *         the database.
+   */
+  fn parse_csv_into_db<R: std::io::Read>(db: &Database, reader: &mut csv::Reader<R>, report: &mut ImportReport) -> Result<(), Error>
+  {
+    let columns = Columns::new(reader.headers()?);
+    let [idx_name, idx_country_code, idx_continent, idx_population, idx_indicator,
+         idx_count, idx_year_week] = columns.require([
+      "country",
+      "country_code",
+      "continent",
+      "population",
+      "indicator",
+      "weekly_count",
+      "year_week"
+    ])?;
+    let idx_rate = columns.position("rate_14_day");
+    let mut countries: BTreeMap<String, CountryWeeks> = BTreeMap::new();
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record)?
+    {
+      let country_code = record[idx_country_code].trim();
+      // Aggregates like "EU/EEA (total)" have no country code.
+      if country_code.is_empty()
+      {
+        report.skipped(&record[idx_name], SkipReason::MissingCode);
+        continue;
+      }
+      let date = match DbEcdcWeekly::end_of_week(record[idx_year_week].trim())
+      {
+        Some(date) => date,
+        None => return Err(Error::csv_parse(&record, "Got invalid year and week."))
+      };
+      let count: i32 = match record[idx_count].trim()
+      {
+        "" => 0,
+        value => value.parse().unwrap_or(i32::MIN)
+      };
+      if count == i32::MIN
+      {
+        return Err(Error::csv_parse(&record, "Got invalid case numbers."));
+      }
+      let country = countries
+        .entry(country_code.to_string())
+        .or_insert_with(|| CountryWeeks {
+          name: record[idx_name].to_string(),
+          // Default for population values that cannot be parsed is -1.
+          population: record[idx_population].parse().unwrap_or(-1),
+          continent: record[idx_continent].to_string(),
+          weeks: BTreeMap::new()
+        });
+      let week = country.weeks.entry(date).or_default();
+      match record[idx_indicator].trim()
+      {
+        "cases" =>
+        {
+          week.cases = count;
+          // The rate of the deaths is per one million inhabitants, so only
+          // the rate of the cases is an incidence.
+          week.rate_14_day = idx_rate.and_then(|idx| record[idx].trim().parse().ok());
+        },
+        "deaths" => week.deaths = count,
+        _ => return Err(Error::csv_parse(&record, "Got unknown indicator."))
+      }
+      report.kept(&record[idx_name], 1);
+    }
+
+    let world = World::new();
+    for (country_code, country) in countries.iter()
+    {
+      // The file only contains ISO-3 codes, so the geo id comes from the list
+      // of known countries.
+      let known = world.find_by_country_code(country_code);
+      if known.is_none()
+      {
+        report.not_found(&country.name);
+      }
+      let geo_id = known.map(|c| c.geo_id.as_str()).unwrap_or(country_code);
+      let country_id = db.get_country_id_or_insert(
+        geo_id,
+        &country.name,
+        &country.population,
+        country_code,
+        &country.continent
+      )?;
+      let mut numbers = DbEcdcWeekly::weekly_incidence(&country.weeks, country.population);
+      save::numbers_and_incidence_into_db(db, &country_id, &mut numbers)?;
+      // Detection needs several weeks of data, but the format is always weekly.
+      db.set_granularity(&country_id, Granularity::Weekly)?;
+    }
+
+    // Done.
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::data::DateRange;
+  use crate::db::Db;
+
+  #[test]
+  fn end_of_week()
+  {
+    assert_eq!(DbEcdcWeekly::end_of_week("2021-07"), Some(String::from("2021-02-21")));
+    assert_eq!(DbEcdcWeekly::end_of_week("2020-W53"), Some(String::from("2021-01-03")));
+    assert_eq!(DbEcdcWeekly::end_of_week("2021-53"), None);
+    assert_eq!(DbEcdcWeekly::end_of_week("2021"), None);
+  }
+
+  #[test]
+  fn import()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_ecdc_weekly_import.csv");
+    let db_path = dir.join("test_ecdc_weekly_import.db");
+    let _ = std::fs::remove_file(&db_path);
+    let csv = "country,country_code,continent,population,indicator,weekly_count,year_week,rate_14_day,cumulative_count,source
+Austria,AUT,Europe,8901064,cases,1000,2021-01,,1000,TESSy COVID-19
+Austria,AUT,Europe,8901064,deaths,10,2021-01,,10,TESSy COVID-19
+Austria,AUT,Europe,8901064,cases,2000,2021-02,33.7,3000,TESSy COVID-19
+Austria,AUT,Europe,8901064,deaths,20,2021-02,3.37,30,TESSy COVID-19
+Austria,AUT,Europe,8901064,cases,500,2021-04,,3500,TESSy COVID-19
+EU/EEA (total),,Europe,447319916,cases,99999,2021-01,,99999,TESSy COVID-19
+";
+    assert!(std::fs::write(&csv_path, csv).is_ok());
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      assert_eq!(db.meta("source_format").unwrap(), Some(String::from("ECDC weekly")));
+      let countries = db.countries().unwrap();
+      assert_eq!(countries.len(), 1);
+      assert_eq!(countries[0].geo_id, "AT");
+      assert_eq!(countries[0].country_code, "AUT");
+      assert_eq!(db.granularity(&countries[0].country_id).unwrap(), Granularity::Weekly);
+      let mut numbers = db.numbers_with_incidence(&countries[0].country_id, &DateRange::default()).unwrap();
+      assert_eq!(numbers.len(), 3);
+      numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+      // first week: no previous week for the 14-day incidence
+      assert_eq!(numbers[0].date, "2021-01-10");
+      assert_eq!(numbers[0].cases, 1000);
+      assert_eq!(numbers[0].deaths, 10);
+      assert!(numbers[0].incidence_14d.is_none());
+      assert!((numbers[0].incidence_7d.unwrap() - 11.2346).abs() < 0.001);
+      // second week: 14-day incidence from file
+      assert_eq!(numbers[1].date, "2021-01-17");
+      assert_eq!(numbers[1].incidence_14d, Some(33.7));
+      // fourth week: week three is missing, so there is no 14-day incidence
+      assert_eq!(numbers[2].date, "2021-01-31");
+      assert_eq!(numbers[2].deaths, 0);
+      assert!(numbers[2].incidence_14d.is_none());
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn weekly_incidence_calculated()
+  {
+    let mut weeks = BTreeMap::new();
+    weeks.insert(String::from("2021-01-10"), Week { cases: 100, deaths: 1, rate_14_day: None });
+    weeks.insert(String::from("2021-01-17"), Week { cases: 300, deaths: 2, rate_14_day: None });
+    let numbers = DbEcdcWeekly::weekly_incidence(&weeks, 100_000);
+    assert_eq!(numbers[0].incidence_7d, Some(100.0));
+    assert_eq!(numbers[0].incidence_14d, None);
+    assert_eq!(numbers[1].incidence_7d, Some(300.0));
+    assert_eq!(numbers[1].incidence_14d, Some(400.0));
+
+    let numbers = DbEcdcWeekly::weekly_incidence(&weeks, -1);
+    assert!(numbers.iter().all(|n| n.incidence_7d.is_none() && n.incidence_14d.is_none()));
+  }
+}
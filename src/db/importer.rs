@@ -0,0 +1,349 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::configuration::GapHandling;
+use crate::database::Database;
+use crate::db::disease_sh::DbDiseaseSh;
+use crate::db::ecdc::DbEcdc;
+use crate::db::ecdc_weekly::DbEcdcWeekly;
+use crate::db::jhu::DbJhu;
+use crate::db::owid::DbOwid;
+use crate::db::owid_etl_compact::DbOwidEtlCompact;
+use crate::db::who::DbWho;
+use crate::db::{CsvType, ImportReport, Input};
+use crate::error::Error;
+
+/// Reads one source format and writes its data into a database.
+///
+/// Implementations are registered in an [`ImporterRegistry`], which picks the
+/// first importer whose [`detect_input`](Importer::detect_input) method accepts
+/// the file, which by default asks [`detect`](Importer::detect) about the first
+/// line of the file. Registered importers are asked before the built-in ones.
+pub trait Importer
+{
+  /**
+   * Gets the source format that is handled by this importer.
+   *
+   * @return Returns the format. Importers outside of this crate should use
+   *         CsvType::Other with a short, human-readable name.
+   */
+  fn csv_type(&self) -> CsvType;
+
+  /**
+   * Checks whether the importer can handle a file.
+   *
+   * @param first_line   first line of the file, i. e. usually the CSV headers;
+   *                     a byte order mark may still be present
+   * @return Returns true, if the importer can read the file.
+   */
+  fn detect(&self, first_line: &str) -> bool;
+
+  /**
+   * Checks whether the importer can handle the input. The default
+   * implementation passes the first line to detect(). Importers of formats
+   * that cannot be recognized by their first line alone, e. g. JSON, can
+   * inspect the beginning of the data instead.
+   *
+   * @param input   the opened input; nothing has been consumed from it yet
+   * @return Returns true, if the importer can read the input.
+   */
+  fn detect_input(&self, input: &Input) -> bool
+  {
+    self.detect(input.first_line())
+  }
+
+  /**
+   * Reads the data and writes it into the database.
+   *
+   * @param input   the opened, already decompressed data, starting with the
+   *                first line
+   * @param db      an open SQLite database with existing tables, including
+   *                the columns for the accumulated numbers
+   * @param report  report that collects statistics about the rows, e. g. the
+   *                number of skipped rows
+   * @return Returns an Error, if the data could not be read or written.
+   */
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>;
+}
+
+/// List of importers that are used to detect and read source formats.
+///
+/// A new registry contains the importers for all formats that are supported
+/// out of the box. Further formats can be added with
+/// [`register`](ImporterRegistry::register):
+///
+/// ```
+/// use corona::configuration::{DbConfiguration, GapHandling};
+/// use corona::db::{CsvType, Db, ImportReport, Importer, ImporterRegistry, Input};
+/// use corona::{Database, Error};
+///
+/// struct RegionImporter;
+///
+/// impl Importer for RegionImporter
+/// {
+///   fn csv_type(&self) -> CsvType
+///   {
+///     CsvType::Other(String::from("Regional"))
+///   }
+///
+///   fn detect(&self, first_line: &str) -> bool
+///   {
+///     first_line.starts_with("region,date,cases,deaths")
+///   }
+///
+///   fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
+///   {
+///     // Read the data, e. g. with csv::Reader::from_reader(input), and write the numbers, e. g. with
+///     // corona::db::save::numbers_into_db().
+///     Ok(())
+///   }
+/// }
+///
+/// let mut importers = ImporterRegistry::new();
+/// importers.register(Box::new(RegionImporter));
+/// let config = DbConfiguration {
+///   csv_input_file: String::from("regions.csv"),
+///   db_path: String::from("regions.db"),
+///   quiet: false,
+///   mapping: None,
+///   report: None,
+///   gaps: GapHandling::Truncate
+/// };
+/// let db = Db::with_importers(&config, importers)?;
+/// # Ok::<(), corona::Error>(())
+/// ```
+pub struct ImporterRegistry
+{
+  /// registered importers first, followed by the built-in importers
+  importers: Vec<Box<dyn Importer>>,
+  /// number of importers added via register()
+  registered: usize
+}
+
+impl ImporterRegistry
+{
+  /**
+   * Creates a registry containing the built-in importers.
+   */
+  pub fn new() -> ImporterRegistry
+  {
+    ImporterRegistry::with_gap_handling(GapHandling::default())
+  }
+
+  /**
+   * Creates a registry containing the built-in importers, where importers of
+   * files with missing days handle those days as given.
+   *
+   * @param gaps   handling of missing days
+   */
+  pub fn with_gap_handling(gaps: GapHandling) -> ImporterRegistry
+  {
+    ImporterRegistry {
+      importers: vec![
+        Box::new(DbEcdc),
+        Box::new(DbEcdcWeekly),
+        Box::new(DbOwid),
+        Box::new(DbOwidEtlCompact::new(gaps)),
+        Box::new(DbWho),
+        Box::new(DbJhu),
+        Box::new(DbDiseaseSh)
+      ],
+      registered: 0
+    }
+  }
+
+  /**
+   * Adds an importer. Registered importers are asked before the built-in
+   * importers, so they can also handle files that a built-in importer would
+   * accept. Among each other, they are asked in the order of their
+   * registration.
+   *
+   * @param importer   the importer to add
+   */
+  pub fn register(&mut self, importer: Box<dyn Importer>)
+  {
+    self.importers.insert(self.registered, importer);
+    self.registered += 1;
+  }
+
+  /**
+   * Gets all registered importers, including the built-in ones.
+   *
+   * @return Returns an iterator over the importers in the order in which they
+   *         are asked during detection.
+   */
+  pub fn importers(&self) -> impl Iterator<Item = &dyn Importer>
+  {
+    self.importers.iter().map(|importer| importer.as_ref())
+  }
+
+  /**
+   * Finds the importer for the input by checking its first line.
+   *
+   * @param input   the opened input
+   * @return Returns the first importer that accepts the input.
+   *         Returns an Error, if the input is empty or if no importer accepts
+   *         it.
+   */
+  pub fn detect(&self, input: &Input) -> Result<&dyn Importer, Error>
+  {
+    let file_path = input.name();
+    if input.is_empty()
+    {
+      return Err(Error::UnknownFormat(format!("File {file_path} is empty.")));
+    }
+
+    match self.importers().find(|importer| importer.detect_input(input))
+    {
+      Some(importer) => Ok(importer),
+      // Unknown CSV type.
+      None => Err(Error::UnknownFormat(format!(
+        "File {file_path} does not seem to contain a known CSV format! Only CSV \
+         formats as used by the ECDC, Our World In Data, the WHO or the Johns Hopkins \
+         University and JSON data from disease.sh can be detected."
+      )))
+    }
+  }
+}
+
+impl Default for ImporterRegistry
+{
+  fn default() -> Self
+  {
+    ImporterRegistry::new()
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  /// importer for a made-up format, used to test the registration
+  struct TestImporter;
+
+  impl Importer for TestImporter
+  {
+    fn csv_type(&self) -> CsvType
+    {
+      CsvType::Other(String::from("Test"))
+    }
+
+    fn detect(&self, first_line: &str) -> bool
+    {
+      first_line == "region,date,cases,deaths"
+    }
+
+    fn import(&self, _input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
+    {
+      report.kept("Wonderland", 1);
+      let id = db.get_country_id_or_insert("XX", "Wonderland", &42, "WON", "Utopia")?;
+      let mut numbers = vec![crate::data::Numbers { date: String::from("2021-01-01"), cases: 1, deaths: 0 }];
+      crate::db::save::numbers_into_db(db, &id, &42, &mut numbers)
+    }
+  }
+
+  #[test]
+  fn built_in_importers()
+  {
+    let registry = ImporterRegistry::new();
+    let types: Vec<CsvType> = registry.importers().map(|i| i.csv_type()).collect();
+    assert_eq!(types, vec![
+      CsvType::Ecdc, CsvType::EcdcWeekly, CsvType::Owid, CsvType::OwidEtlCompact, CsvType::Who,
+      CsvType::Jhu, CsvType::DiseaseSh
+    ]);
+  }
+
+  #[test]
+  fn register_and_detect()
+  {
+    let path = std::env::temp_dir().join("test_importer_registry.csv");
+    let path_str = path.to_str().unwrap();
+    assert!(std::fs::write(&path, "region,date,cases,deaths\nXX,2021-01-01,1,0\n").is_ok());
+
+    let mut registry = ImporterRegistry::new();
+    let input = Input::open(path_str).unwrap();
+    assert!(matches!(registry.detect(&input), Err(Error::UnknownFormat(_))));
+    registry.register(Box::new(TestImporter));
+    let detected = registry.detect(&input);
+    assert!(detected.is_ok());
+    assert_eq!(detected.unwrap().csv_type(), CsvType::Other(String::from("Test")));
+
+    // The registered importer is used to create a database, too.
+    let db_path = std::env::temp_dir().join("test_importer_registry.db");
+    let _ = std::fs::remove_file(&db_path);
+    let config = crate::configuration::DbConfiguration {
+      csv_input_file: path_str.to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: crate::configuration::GapHandling::Truncate
+    };
+    assert!(crate::db::Db::with_importers(&config, registry).unwrap().create_db().is_ok());
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      assert_eq!(db.meta("source_format").unwrap(), Some(String::from("Test")));
+      assert_eq!(db.countries().unwrap().len(), 1);
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  /// importer that accepts everything, used to test the order of detection
+  struct CatchAllImporter(&'static str);
+
+  impl Importer for CatchAllImporter
+  {
+    fn csv_type(&self) -> CsvType
+    {
+      CsvType::Other(self.0.to_string())
+    }
+
+    fn detect(&self, _first_line: &str) -> bool
+    {
+      true
+    }
+
+    fn import(&self, _input: Input, _db: &Database, _report: &mut ImportReport) -> Result<(), Error>
+    {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn registered_importers_come_first()
+  {
+    let mut registry = ImporterRegistry::new();
+    registry.register(Box::new(CatchAllImporter("first")));
+    registry.register(Box::new(CatchAllImporter("second")));
+    let types: Vec<CsvType> = registry.importers().map(|i| i.csv_type()).collect();
+    assert_eq!(types.len(), 9);
+    assert_eq!(types[0], CsvType::Other(String::from("first")));
+    assert_eq!(types[1], CsvType::Other(String::from("second")));
+    assert_eq!(types[2], CsvType::Ecdc);
+
+    // Even files in a built-in format go to the registered importer.
+    let header = "Date_reported,Country_code,Country,WHO_region,New_cases,Cumulative_cases,New_deaths,Cumulative_deaths\n";
+    let input = Input::from_reader("who.csv", Box::new(header.as_bytes())).unwrap();
+    let detected = registry.detect(&input).unwrap();
+    assert_eq!(detected.csv_type(), CsvType::Other(String::from("first")));
+  }
+}
@@ -0,0 +1,495 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::error::Error;
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Chain, Cursor, Read};
+use std::path::Path;
+use std::rc::Rc;
+
+/// name of the input that stands for the standard input
+pub const STDIN: &str = "-";
+
+/// minimum number of bytes that are read in advance for the detection of the
+/// format, unless the data is shorter
+const HEAD_SIZE: usize = 4096;
+
+/// first bytes of a gzip file
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// first bytes of a ZIP archive, i. e. the signature of a local file header
+const ZIP_MAGIC: [u8; 4] = [b'P', b'K', 0x03, 0x04];
+
+/// Data that shall be imported, read from a plain file, a gzip file, a ZIP
+/// archive or the standard input.
+///
+/// Compressed data is decompressed while it is read, so the importers always
+/// see the plain CSV or JSON data. The first line, and at least the first few
+/// kilobytes, are read in advance to allow the detection of the format without
+/// consuming the data, because the standard input cannot be read twice.
+pub struct Input
+{
+  /// path of the file, or "-" for the standard input
+  name: String,
+  /// first line of the decompressed data, without the line break
+  first_line: String,
+  /// the complete decompressed data, starting with the data read in advance
+  data: Chain<Cursor<Vec<u8>>, BufReader<Box<dyn Read>>>,
+  /// hash of the data that is read from the file
+  digest: InputDigest
+}
+
+impl Input
+{
+  /**
+   * Opens a file for reading. Files compressed with gzip and ZIP archives are
+   * recognized by their content, not by their name. Of a ZIP archive only the
+   * first file is read.
+   *
+   * @param path   path of the file, or "-" to read from the standard input
+   * @return Returns the opened input in case of success.
+   *         Returns an Error, if the file could not be opened or read.
+   */
+  pub fn open(path: &str) -> Result<Input, Error>
+  {
+    let source: Box<dyn Read> = if path == STDIN
+    {
+      Box::new(std::io::stdin())
+    }
+    else
+    {
+      let file = std::fs::File::open(path).map_err(|e| Error::io(Path::new(path), e))?;
+      Box::new(file)
+    };
+    Input::from_reader(path, source)
+  }
+
+  /**
+   * Creates an input from arbitrary data, e. g. data that is already in
+   * memory. Compressed data is recognized like in open().
+   *
+   * @param name     name of the input, used in error messages
+   * @param source   the data
+   * @return Returns the input in case of success.
+   *         Returns an Error, if the data could not be read.
+   */
+  pub fn from_reader(name: &str, source: Box<dyn Read>) -> Result<Input, Error>
+  {
+    let io_error = |e| Error::io(Path::new(name), e);
+    // The hash covers the data as it is stored, i. e. before decompression.
+    let digest = InputDigest
+    {
+      name: name.to_string(),
+      source: Rc::new(RefCell::new(HashingReader { inner: source, hash: Sha256::new() }))
+    };
+    let mut source: Box<dyn Read> = Box::new(SharedReader(Rc::clone(&digest.source)));
+    let mut magic = Vec::with_capacity(ZIP_MAGIC.len());
+    source.by_ref().take(ZIP_MAGIC.len() as u64).read_to_end(&mut magic).map_err(io_error)?;
+    let source = Cursor::new(magic.clone()).chain(source);
+    let decompressed: Box<dyn Read> = if magic.starts_with(&GZIP_MAGIC)
+    {
+      Box::new(MultiGzDecoder::new(source))
+    }
+    else if magic.starts_with(&ZIP_MAGIC)
+    {
+      Input::first_zip_entry(name, source)?
+    }
+    else
+    {
+      Box::new(source)
+    };
+
+    let mut rest = BufReader::new(decompressed);
+    let mut head = Vec::new();
+    rest.read_until(b'\n', &mut head).map_err(io_error)?;
+    let first_line = String::from_utf8_lossy(&head)
+      .trim_end_matches('\n')
+      .trim_end_matches('\r')
+      .to_string();
+    // Some formats, e. g. pretty-printed JSON, cannot be recognized by their
+    // first line alone.
+    while head.len() < HEAD_SIZE
+    {
+      if rest.read_until(b'\n', &mut head).map_err(io_error)? == 0
+      {
+        break;
+      }
+    }
+    Ok(Input
+    {
+      name: name.to_string(),
+      first_line,
+      data: Cursor::new(head).chain(rest),
+      digest
+    })
+  }
+
+  /**
+   * Gets the content of the first file in a ZIP archive. The archive is read
+   * as a stream, i. e. only the local file headers are used, and the central
+   * directory at the end of the archive is not needed.
+   *
+   * @param name      name of the archive, used in error messages
+   * @param archive   data of the archive, starting with the first header
+   * @return Returns the decompressed content of the first file in case of
+   *         success. Returns an Error, if the archive contains no file or if
+   *         the file is encrypted or uses an unsupported compression method.
+   */
+  fn first_zip_entry<R: Read + 'static>(name: &str, mut archive: R) -> Result<Box<dyn Read>, Error>
+  {
+    let io_error = |e| Error::io(Path::new(name), e);
+    loop
+    {
+      let mut header = [0u8; 30];
+      archive.read_exact(&mut header[0..4]).map_err(io_error)?;
+      if header[0..4] != ZIP_MAGIC
+      {
+        // Reached the central directory without finding a file.
+        return Err(Error::UnknownFormat(format!("The ZIP archive {name} contains no file.")));
+      }
+      archive.read_exact(&mut header[4..]).map_err(io_error)?;
+      let flags = u16::from_le_bytes([header[6], header[7]]);
+      let method = u16::from_le_bytes([header[8], header[9]]);
+      let compressed_size = u32::from_le_bytes([header[18], header[19], header[20], header[21]]);
+      let name_length = u16::from_le_bytes([header[26], header[27]]);
+      let extra_length = u16::from_le_bytes([header[28], header[29]]);
+      let mut file_name = vec![0u8; usize::from(name_length)];
+      archive.read_exact(&mut file_name).map_err(io_error)?;
+      std::io::copy(&mut archive.by_ref().take(u64::from(extra_length)), &mut std::io::sink()).map_err(io_error)?;
+
+      if file_name.ends_with(b"/")
+      {
+        // Directories have no content worth reading, skip to the next entry.
+        std::io::copy(&mut archive.by_ref().take(u64::from(compressed_size)), &mut std::io::sink()).map_err(io_error)?;
+        continue;
+      }
+      if flags & 1 != 0
+      {
+        return Err(Error::UnknownFormat(format!("The file in the ZIP archive {name} is encrypted.")));
+      }
+      // Bit 3 of the flags means that the size is only known after the data,
+      // which is no problem for deflate, because it marks its own end.
+      return match method
+      {
+        0 if flags & 8 == 0 => Ok(Box::new(archive.take(u64::from(compressed_size)))),
+        8 => Ok(Box::new(DeflateDecoder::new(archive))),
+        _ => Err(Error::UnknownFormat(format!(
+          "The file in the ZIP archive {name} uses an unsupported compression method ({method})."
+        )))
+      };
+    }
+  }
+
+  /**
+   * Gets the name of the input.
+   *
+   * @return Returns the path of the file, or "-" for the standard input.
+   */
+  pub fn name(&self) -> &str
+  {
+    &self.name
+  }
+
+  /**
+   * Checks whether the input is read from the standard input.
+   *
+   * @return Returns true, if the data comes from the standard input.
+   */
+  pub fn is_stdin(&self) -> bool
+  {
+    self.name == STDIN
+  }
+
+  /**
+   * Gets the first line of the decompressed data. A byte order mark may still
+   * be present.
+   *
+   * @return Returns the first line without the line break.
+   */
+  pub fn first_line(&self) -> &str
+  {
+    &self.first_line
+  }
+
+  /**
+   * Gets the beginning of the decompressed data, i. e. the first line and
+   * further lines up to a size of at least a few kilobytes, if the data is
+   * that long. A byte order mark may still be present.
+   *
+   * @return Returns the beginning of the data. Invalid UTF-8 sequences are
+   *         replaced.
+   */
+  pub fn head(&self) -> std::borrow::Cow<'_, str>
+  {
+    String::from_utf8_lossy(self.data.get_ref().0.get_ref())
+  }
+
+  /**
+   * Gets the hash of the data that is read from the input. The hash covers
+   * the data as it is stored, i. e. the compressed data of compressed files,
+   * including the parts that are never decompressed, like further files of a
+   * ZIP archive.
+   *
+   * @return Returns a handle to the hash, which stays valid after the input
+   *         has been consumed.
+   */
+  pub fn digest(&self) -> InputDigest
+  {
+    self.digest.clone()
+  }
+
+  /**
+   * Checks whether the input contains no data at all.
+   *
+   * @return Returns true, if there is no data.
+   */
+  pub fn is_empty(&self) -> bool
+  {
+    self.data.get_ref().0.get_ref().is_empty()
+  }
+}
+
+/// SHA-256 hash of the data of an input
+///
+/// The underlying source is shared with the input, so the hash can still be
+/// taken after the input has been handed over to an importer and has been
+/// consumed.
+#[derive(Clone)]
+pub struct InputDigest
+{
+  /// path of the file, or "-" for the standard input
+  name: String,
+  /// the data as it is stored, before decompression
+  source: Rc<RefCell<HashingReader>>
+}
+
+impl InputDigest
+{
+  /**
+   * Gets the hash of the whole input. Importers stop reading at the end of the
+   * data they need, e. g. before the remaining files and the central directory
+   * of a ZIP archive, so the rest of the source is read first. Therefore this
+   * is called after the import.
+   *
+   * @return Returns the hash as hexadecimal string in case of success.
+   *         Returns an Error, if the rest of the input could not be read.
+   */
+  pub fn finish(&self) -> Result<String, Error>
+  {
+    let mut source = self.source.borrow_mut();
+    std::io::copy(&mut *source, &mut std::io::sink()).map_err(|e| Error::io(Path::new(&self.name), e))?;
+    Ok(to_hex(source.hash.clone()))
+  }
+}
+
+/**
+ * Formats a hash as hexadecimal string.
+ *
+ * @param hash   the hash
+ * @return Returns the hash as string of lowercase hexadecimal digits.
+ */
+fn to_hex(hash: Sha256) -> String
+{
+  hash.finalize()[..].iter().fold(String::new(), |mut hex, x| {
+    hex.push_str(&format!("{x:02x}"));
+    hex
+  })
+}
+
+/// reader that passes all data it reads to a hash
+struct HashingReader
+{
+  /// the underlying reader
+  inner: Box<dyn Read>,
+  /// hash of all the data read so far
+  hash: Sha256
+}
+
+impl Read for HashingReader
+{
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+  {
+    let count = self.inner.read(buf)?;
+    self.hash.update(&buf[..count]);
+    Ok(count)
+  }
+}
+
+/// reader that reads from a source which is shared with an InputDigest
+struct SharedReader(Rc<RefCell<HashingReader>>);
+
+impl Read for SharedReader
+{
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+  {
+    self.0.borrow_mut().read(buf)
+  }
+}
+
+impl Read for Input
+{
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+  {
+    self.data.read(buf)
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::db::tests::zip_archive;
+  use flate2::write::GzEncoder;
+  use flate2::Compression;
+  use std::io::Write;
+
+  const CSV: &str = "date,country,cases\r\n2021-01-01,Germany,5\r\n2021-01-02,Germany,7\r\n";
+
+  /**
+   * Reads the complete input into a string.
+   */
+  fn content(input: Input) -> String
+  {
+    let mut input = input;
+    let mut content = String::new();
+    assert!(input.read_to_string(&mut content).is_ok());
+    content
+  }
+
+  #[test]
+  fn plain()
+  {
+    let input = Input::from_reader("plain.csv", Box::new(CSV.as_bytes())).unwrap();
+    assert_eq!(input.name(), "plain.csv");
+    assert!(!input.is_stdin());
+    assert!(!input.is_empty());
+    assert_eq!(input.first_line(), "date,country,cases");
+    assert_eq!(content(input), CSV);
+  }
+
+  #[test]
+  fn head()
+  {
+    let input = Input::from_reader("plain.csv", Box::new(CSV.as_bytes())).unwrap();
+    assert_eq!(input.first_line(), "date,country,cases");
+    // Short data is read completely in advance.
+    assert_eq!(input.head(), CSV);
+    // Reading in advance does not consume anything.
+    assert_eq!(content(input), CSV);
+
+    let long = CSV.repeat(1000);
+    let input = Input::from_reader("long.csv", Box::new(Cursor::new(long.clone().into_bytes()))).unwrap();
+    assert!(input.head().len() >= HEAD_SIZE);
+    assert!(input.head().len() < long.len());
+    assert!(long.starts_with(input.head().as_ref()));
+    assert_eq!(content(input), long);
+  }
+
+  #[test]
+  fn empty()
+  {
+    let input = Input::from_reader("empty.csv", Box::new(&b""[..])).unwrap();
+    assert!(input.is_empty());
+    assert_eq!(input.first_line(), "");
+    assert_eq!(content(input), "");
+  }
+
+  #[test]
+  fn gzip()
+  {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    assert!(encoder.write_all(CSV.as_bytes()).is_ok());
+    let compressed = encoder.finish().unwrap();
+    let input = Input::from_reader("data.csv.gz", Box::new(Cursor::new(compressed))).unwrap();
+    assert_eq!(input.first_line(), "date,country,cases");
+    assert_eq!(content(input), CSV);
+  }
+
+  #[test]
+  fn zip_deflate()
+  {
+    let archive = zip_archive(8, CSV.as_bytes());
+    let input = Input::from_reader("data.zip", Box::new(Cursor::new(archive))).unwrap();
+    assert_eq!(input.first_line(), "date,country,cases");
+    assert_eq!(content(input), CSV);
+  }
+
+  #[test]
+  fn zip_stored()
+  {
+    let archive = zip_archive(0, CSV.as_bytes());
+    let input = Input::from_reader("data.zip", Box::new(Cursor::new(archive))).unwrap();
+    assert_eq!(content(input), CSV);
+  }
+
+  #[test]
+  fn zip_errors()
+  {
+    // unsupported compression method, e. g. bzip2
+    let archive = zip_archive(12, CSV.as_bytes());
+    let input = Input::from_reader("data.zip", Box::new(Cursor::new(archive)));
+    assert!(matches!(input, Err(Error::UnknownFormat(_))));
+    // archive with a directory only
+    let mut archive = zip_archive(0, b"");
+    archive.truncate(30 + 4);
+    archive.extend_from_slice(&[b'P', b'K', 0x01, 0x02]);
+    let input = Input::from_reader("data.zip", Box::new(Cursor::new(archive)));
+    assert!(matches!(input, Err(Error::UnknownFormat(_))));
+  }
+
+  #[test]
+  fn open_file()
+  {
+    let path = std::env::temp_dir().join("test_input_open_file.csv.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    assert!(encoder.write_all(CSV.as_bytes()).is_ok());
+    assert!(std::fs::write(&path, encoder.finish().unwrap()).is_ok());
+    let input = Input::open(path.to_str().unwrap()).unwrap();
+    assert_eq!(content(input), CSV);
+    assert!(std::fs::remove_file(&path).is_ok());
+
+    let input = Input::open("/this/file/does/not/exist.csv");
+    assert!(matches!(input, Err(Error::Io { .. })));
+  }
+
+  #[test]
+  fn digest()
+  {
+    let input = Input::from_reader("abc.csv", Box::new(&b"abc"[..])).unwrap();
+    let digest = input.digest();
+    assert_eq!(content(input), "abc");
+    assert_eq!(digest.finish().unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+    // Compressed data is hashed as it is stored.
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    assert!(encoder.write_all(CSV.as_bytes()).is_ok());
+    let compressed = encoder.finish().unwrap();
+    let input = Input::from_reader("data.csv.gz", Box::new(Cursor::new(compressed.clone()))).unwrap();
+    let digest = input.digest();
+    assert_eq!(content(input), CSV);
+    assert_eq!(digest.finish().unwrap(), to_hex(Sha256::new_with_prefix(&compressed)));
+
+    // Parts of a ZIP archive that are not read by the importer are hashed, too.
+    let archive = zip_archive(0, CSV.as_bytes());
+    let input = Input::from_reader("data.zip", Box::new(Cursor::new(archive.clone()))).unwrap();
+    let digest = input.digest();
+    assert_eq!(content(input), CSV);
+    assert_eq!(digest.finish().unwrap(), to_hex(Sha256::new_with_prefix(&archive)));
+  }
+}
@@ -0,0 +1,407 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::data::Country;
+use crate::database::Database;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input, InputFile, SkipReason};
+use crate::error::Error;
+use crate::world::World;
+use chrono::NaiveDate;
+use csv::Reader;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// names of countries in the JHU data that differ from the names of the
+/// countries in World, and the corresponding geo ids
+const JHU_NAMES: [(&str, &str); 13] = [
+  ("Brunei", "BN"),
+  ("Burma", "MM"),
+  ("Congo (Brazzaville)", "CG"),
+  ("Congo (Kinshasa)", "CD"),
+  ("Cote d'Ivoire", "CI"),
+  ("Guinea-Bissau", "GW"),
+  ("Korea, North", "KP"),
+  ("Korea, South", "KR"),
+  ("Taiwan*", "TW"),
+  ("Tanzania", "TZ"),
+  ("Timor-Leste", "TL"),
+  ("US", "US"),
+  ("West Bank and Gaza", "PS")
+];
+
+/// cumulative numbers of one file, indexed by country name and date
+pub(crate) type CumulativeNumbers = BTreeMap<String, BTreeMap<String, i64>>;
+
+/// importer for the time series CSV files of the Johns Hopkins University
+///
+/// The data is split into two files with the same layout, one for confirmed
+/// cases and one for deaths, e. g. `time_series_covid19_confirmed_global.csv`
+/// and `time_series_covid19_deaths_global.csv`. Either one of them may be
+/// given, the other one is expected in the same directory.
+pub struct DbJhu;
+
+impl Importer for DbJhu
+{
+  fn csv_type(&self) -> CsvType
+  {
+    CsvType::Jhu
+  }
+
+  fn detect(&self, first_line: &str) -> bool
+  {
+    Columns::from_line(first_line).contains_all(&["Province/State", "Country/Region", "Lat", "Long"])
+  }
+
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
+  {
+    if input.is_stdin()
+    {
+      return Err(Error::Configuration(String::from(
+        "JHU data consists of two files, so it cannot be read from standard input."
+      )));
+    }
+    let (confirmed_file, deaths_file) = DbJhu::file_pair(input.name())?;
+    // Only the other file of the pair needs to be opened.
+    let is_confirmed = input.name() == confirmed_file;
+    let other = Input::open(if is_confirmed { &deaths_file } else { &confirmed_file })?;
+    let other_name = other.name().to_string();
+    let other_digest = other.digest();
+    let (confirmed, deaths) = match is_confirmed
+    {
+      true => (input, other),
+      false => (other, input)
+    };
+    let confirmed = DbJhu::read_file(confirmed)?;
+    let deaths = DbJhu::read_file(deaths)?;
+    // The file given by the user is recorded by the caller.
+    report.inputs.push(InputFile { name: other_name, sha256: other_digest.finish()? });
+    DbJhu::save_into_db(db, confirmed, deaths, report)
+  }
+}
+
+impl DbJhu
+{
+  /**
+   * Gets the paths of the files for confirmed cases and for deaths.
+   *
+   * @param csv_input_file   path of one of the two files
+   * @return Returns the paths of the confirmed cases file and of the deaths
+   *         file in case of success.
+   *         Returns an Error, if the name of the file contains neither
+   *         "confirmed" nor "deaths", or if the other file does not exist.
+   */
+  fn file_pair(csv_input_file: &str) -> Result<(String, String), Error>
+  {
+    let path = std::path::Path::new(csv_input_file);
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let (confirmed, deaths) = if file_name.contains("confirmed")
+    {
+      (file_name.to_string(), file_name.replace("confirmed", "deaths"))
+    }
+    else if file_name.contains("deaths")
+    {
+      (file_name.replace("deaths", "confirmed"), file_name.to_string())
+    }
+    else
+    {
+      return Err(Error::UnknownFormat(format!(
+        "The name of the JHU file {csv_input_file} contains neither 'confirmed' \
+         nor 'deaths', so the second file of the pair cannot be found."
+      )));
+    };
+    let confirmed = path.with_file_name(confirmed);
+    let deaths = path.with_file_name(deaths);
+    for file in [&confirmed, &deaths]
+    {
+      if !file.is_file()
+      {
+        return Err(Error::io(file, std::io::Error::new(
+          std::io::ErrorKind::NotFound,
+          "The JHU data needs both the confirmed cases file and the deaths file."
+        )));
+      }
+    }
+    Ok((confirmed.to_string_lossy().into_owned(), deaths.to_string_lossy().into_owned()))
+  }
+
+  /**
+   * Reads one time series file and adds up the numbers of all provinces of a
+   * country.
+   *
+   * @param input   the opened CSV file
+   * @return Returns the cumulative numbers per country and date in case of
+   *         success. Returns an Error, if the file could not be read.
+   */
+  fn read_file(input: Input) -> Result<CumulativeNumbers, Error>
+  {
+    let csv_input_file = input.name().to_string();
+    let mut reader = Reader::from_reader(input);
+    let headers = reader.headers()?.clone();
+    let [idx_country] = Columns::new(&headers).require(["Country/Region"])?;
+    // Every column whose header is a date like "3/9/23" holds the numbers of
+    // that day.
+    let dates: Vec<(usize, String)> = headers
+      .iter()
+      .enumerate()
+      .filter_map(|(idx, header)| {
+        NaiveDate::parse_from_str(header.trim(), "%m/%d/%y")
+          .ok()
+          .map(|date| (idx, date.format("%Y-%m-%d").to_string()))
+      })
+      .collect();
+    if dates.is_empty()
+    {
+      return Err(Error::UnknownFormat(format!("File {csv_input_file} contains no date columns.")));
+    }
+
+    let mut numbers = CumulativeNumbers::new();
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record)?
+    {
+      let country = numbers.entry(record[idx_country].trim().to_string()).or_default();
+      for (idx, date) in dates.iter()
+      {
+        let value = record[*idx].trim();
+        if value.is_empty()
+        {
+          continue;
+        }
+        let value: i64 = match value.parse::<f64>()
+        {
+          Ok(number) if number.is_finite() => number as i64,
+          _ => return Err(Error::csv_parse(&record, &format!("'{value}' is not a valid number.")))
+        };
+        *country.entry(date.clone()).or_insert(0) += value;
+      }
+    }
+    Ok(numbers)
+  }
+
+  /**
+   * Gets the country for a name as used in the JHU data.
+   *
+   * @param world    list of known countries
+   * @param name     name of the country in the JHU data, e. g. "Korea, South"
+   * @param report   report where unknown countries are recorded
+   * @return Returns the known country. Returns None for names that are not
+   *         countries, e. g. cruise ships, because they have no code that
+   *         could be used as geo id.
+   */
+  fn find_country(world: &World, name: &str, report: &mut ImportReport) -> Option<Country>
+  {
+    let known = match JHU_NAMES.iter().find(|(jhu_name, _)| *jhu_name == name)
+    {
+      Some((_, geo_id)) => world.find_by_geo_id(geo_id),
+      None => world.find_by_name(name)
+    };
+    if known.is_none()
+    {
+      report.not_found(name);
+    }
+    known.cloned()
+  }
+
+  /**
+   * Converts the cumulative numbers into daily numbers and writes them into
+   * the database.
+   *
+   * @param db          an open SQLite database with existing tables
+   * @param confirmed   cumulative number of confirmed cases
+   * @param deaths      cumulative number of deaths
+   * @param report      report that collects statistics about the data, where
+   *                    every date of a country counts as one row
+   * @return Returns an Error, if the data could not be written to the database.
+   */
+  pub(crate) fn save_into_db(db: &Database, confirmed: CumulativeNumbers, deaths: CumulativeNumbers, report: &mut ImportReport) -> Result<(), Error>
+  {
+    let world = World::new();
+    let names: BTreeSet<&String> = confirmed.keys().chain(deaths.keys()).collect();
+    let empty = BTreeMap::new();
+    // Several names may belong to the same country, so sums are indexed by the
+    // geo id of the country.
+    let mut countries: BTreeMap<String, (Country, save::DailySums)> = BTreeMap::new();
+    for name in names
+    {
+      let country_cases = confirmed.get(name).unwrap_or(&empty);
+      let country_deaths = deaths.get(name).unwrap_or(&empty);
+      let dates: BTreeSet<&String> = country_cases.keys().chain(country_deaths.keys()).collect();
+      let country = match DbJhu::find_country(&world, name, report)
+      {
+        Some(country) => country,
+        None =>
+        {
+          for _ in dates.iter()
+          {
+            report.skipped(name, SkipReason::MissingCode);
+          }
+          continue;
+        }
+      };
+      let (_, days) = countries
+        .entry(country.geo_id.clone())
+        .or_insert_with(|| (country, BTreeMap::new()));
+      // A day that is missing in one of the files keeps the numbers of the
+      // day before, because the numbers are cumulative.
+      let mut last = (0, 0);
+      report.kept(name, dates.len() as u64);
+      for date in dates
+      {
+        last.0 = country_cases.get(date).copied().unwrap_or(last.0);
+        last.1 = country_deaths.get(date).copied().unwrap_or(last.1);
+        let sums = days.entry(date.clone()).or_insert((0, 0));
+        sums.0 += last.0;
+        sums.1 += last.1;
+      }
+    }
+
+    for (country, days) in countries.into_values()
+    {
+      let mut numbers = save::daily_numbers(days, true, true);
+      let country_id = db.get_country_id_or_insert(
+        &country.geo_id,
+        &country.name,
+        &i64::from(country.population),
+        &country.country_code,
+        &country.continent
+      )?;
+      save::numbers_into_db(db, &country_id, &country.population, &mut numbers)?;
+    }
+
+    // Done.
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::data::DateRange;
+  use crate::db::Db;
+
+  const HEADER: &str = "Province/State,Country/Region,Lat,Long,1/22/20,1/23/20,1/24/20";
+
+  #[test]
+  fn detect()
+  {
+    assert!(DbJhu.detect(HEADER));
+    assert!(!DbJhu.detect("Date_reported,Country_code,Country,WHO_region,New_cases"));
+  }
+
+  #[test]
+  fn file_pair()
+  {
+    let dir = std::env::temp_dir();
+    let confirmed = dir.join("test_jhu_pair_confirmed_global.csv");
+    let deaths = dir.join("test_jhu_pair_deaths_global.csv");
+    assert!(std::fs::write(&confirmed, HEADER).is_ok());
+
+    // deaths file is missing
+    assert!(DbJhu::file_pair(confirmed.to_str().unwrap()).is_err());
+    assert!(std::fs::write(&deaths, HEADER).is_ok());
+    let expected = Some((confirmed.to_str().unwrap().to_string(), deaths.to_str().unwrap().to_string()));
+    assert_eq!(DbJhu::file_pair(confirmed.to_str().unwrap()).ok(), expected);
+    assert_eq!(DbJhu::file_pair(deaths.to_str().unwrap()).ok(), expected);
+    assert!(matches!(DbJhu::file_pair("/tmp/time_series.csv"), Err(Error::UnknownFormat(_))));
+
+    // clean up
+    assert!(std::fs::remove_file(confirmed).is_ok());
+    assert!(std::fs::remove_file(deaths).is_ok());
+  }
+
+  #[test]
+  fn find_country()
+  {
+    let world = World::new();
+    let mut report = ImportReport::new();
+    assert_eq!(DbJhu::find_country(&world, "Korea, South", &mut report).unwrap().geo_id, "KR");
+    assert_eq!(DbJhu::find_country(&world, "US", &mut report).unwrap().geo_id, "US");
+    assert_eq!(DbJhu::find_country(&world, "Germany", &mut report).unwrap().geo_id, "DE");
+    assert!(report.unknown_countries.is_empty());
+    // Unknown names have no code that could be used as geo id.
+    assert!(DbJhu::find_country(&world, "Diamond Princess", &mut report).is_none());
+    assert!(report.unknown_countries.contains("Diamond Princess"));
+  }
+
+  #[test]
+  fn import()
+  {
+    let dir = std::env::temp_dir();
+    let confirmed = dir.join("test_jhu_import_confirmed_global.csv");
+    let deaths = dir.join("test_jhu_import_deaths_global.csv");
+    let db_path = dir.join("test_jhu_import.db");
+    let _ = std::fs::remove_file(&db_path);
+    let confirmed_csv = format!("{HEADER}
+Australian Capital Territory,Australia,-35.4735,149.0124,0,2,3
+New South Wales,Australia,-33.8688,151.2093,1,4,4
+,\"Korea, South\",35.9078,127.7669,1,1,2
+,Diamond Princess,0.0,0.0,10,10,10
+");
+    let deaths_csv = format!("{HEADER}
+Australian Capital Territory,Australia,-35.4735,149.0124,0,0,1
+New South Wales,Australia,-33.8688,151.2093,0,1,1
+,\"Korea, South\",35.9078,127.7669,0,0,0
+,Diamond Princess,0.0,0.0,0,1,1
+");
+    assert!(std::fs::write(&confirmed, &confirmed_csv).is_ok());
+    assert!(std::fs::write(&deaths, &deaths_csv).is_ok());
+
+    let config = DbConfiguration {
+      csv_input_file: confirmed.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      assert_eq!(db.meta("source_format").unwrap(), Some(String::from("JHU")));
+      // The cruise ship is skipped, because it is not a country.
+      let countries = db.country_statistics().unwrap();
+      assert_eq!(countries.len(), 2);
+      assert!(!countries.iter().any(|c| c.country.name == "Diamond Princess"));
+      let known = db.countries().unwrap();
+      assert_eq!(known.len(), 2);
+      let australia = known.iter().find(|c| c.geo_id == "AU").unwrap();
+      assert!(australia.population > 0);
+      let numbers = db.numbers(&australia.country_id, &DateRange::default()).unwrap();
+      let cases: Vec<i32> = numbers.iter().map(|n| n.cases).collect();
+      let deaths: Vec<i32> = numbers.iter().map(|n| n.deaths).collect();
+      assert_eq!(numbers[0].date, "2020-01-22");
+      assert_eq!(cases, vec![1, 5, 1]);
+      assert_eq!(deaths, vec![0, 1, 1]);
+      assert!(known.iter().any(|c| c.geo_id == "KR"));
+      // Both files of the pair are recorded.
+      let provenance = db.provenance().unwrap();
+      assert_eq!(provenance.len(), 1);
+      assert_eq!(provenance[0].file, "test_jhu_import_confirmed_global.csv, test_jhu_import_deaths_global.csv");
+      let hash = |data: &str| {
+        use sha2::Digest;
+        sha2::Sha256::digest(data.as_bytes())[..].iter().map(|x| format!("{x:02x}")).collect::<String>()
+      };
+      assert_eq!(provenance[0].sha256, Some(format!("{}, {}", hash(&confirmed_csv), hash(&deaths_csv))));
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(confirmed).is_ok());
+    assert!(std::fs::remove_file(deaths).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+}
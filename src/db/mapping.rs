@@ -0,0 +1,596 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::data::Country;
+use crate::database::Database;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input, SkipReason};
+use crate::error::Error;
+use crate::world::World;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// description of the columns of a CSV file in an otherwise unknown format
+///
+/// Mappings are written in TOML:
+///
+/// ```toml
+/// name = "RKI"
+/// delimiter = ";"
+///
+/// [date]
+/// column = "Meldedatum"
+/// format = "%d.%m.%Y"
+///
+/// [country]
+/// code = "DE"
+///
+/// [cases]
+/// column = "AnzahlFall"
+///
+/// [deaths]
+/// column = "AnzahlTodesfall"
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mapping
+{
+  /// name of the source, recorded as source format of the database
+  pub name: Option<String>,
+  /// character that separates the fields, a comma if not set
+  pub delimiter: Option<char>,
+  /// column containing the date
+  pub date: DateColumn,
+  /// columns or fixed code identifying the country
+  pub country: CountryColumns,
+  /// column containing the number of cases
+  pub cases: NumberColumn,
+  /// column containing the number of deaths
+  pub deaths: NumberColumn
+}
+
+/// the `[date]` section of a mapping
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DateColumn
+{
+  /// name of the column
+  pub column: String,
+  /// format of the date as used by chrono, "%Y-%m-%d" if not set
+  pub format: Option<String>
+}
+
+/// the `[country]` section of a mapping
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CountryColumns
+{
+  /// name of the column containing ISO-3166 two- or three-letter codes
+  pub code_column: Option<String>,
+  /// name of the column containing English country names
+  pub name_column: Option<String>,
+  /// ISO-3166 code of the country for files that contain only one country,
+  /// or for rows with an empty code column
+  pub code: Option<String>
+}
+
+/// the `[cases]` and `[deaths]` sections of a mapping
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NumberColumn
+{
+  /// name of the column
+  pub column: String,
+  /// whether the column contains accumulated numbers instead of daily numbers
+  #[serde(default)]
+  pub cumulative: bool
+}
+
+impl Mapping
+{
+  /**
+   * Loads a mapping file.
+   *
+   * @param path   path of the TOML file
+   * @return Returns the mapping in case of success.
+   *         Returns an Error, if the file cannot be read or is invalid.
+   */
+  pub fn load(path: &str) -> Result<Mapping, Error>
+  {
+    let file_path = std::path::Path::new(path);
+    let content = std::fs::read_to_string(file_path).map_err(|e| Error::io(file_path, e))?;
+    Mapping::parse(&content)
+      .map_err(|e| Error::Configuration(format!("Mapping file {path} is invalid: {e}")))
+  }
+
+  /**
+   * Parses the content of a mapping file.
+   *
+   * @param content   TOML content of the file
+   * @return Returns the mapping in case of success.
+   *         Returns an error message otherwise.
+   */
+  pub fn parse(content: &str) -> Result<Mapping, String>
+  {
+    let mapping: Mapping = toml::from_str(content).map_err(|e| e.to_string())?;
+    let country = &mapping.country;
+    if country.code_column.is_none() && country.name_column.is_none() && country.code.is_none()
+    {
+      return Err(String::from("The section [country] needs at least one of the \
+                               entries code_column, name_column or code."));
+    }
+    if let Some(delimiter) = mapping.delimiter
+    {
+      if !delimiter.is_ascii()
+      {
+        return Err(format!("The delimiter '{delimiter}' is not an ASCII character."));
+      }
+    }
+    Ok(mapping)
+  }
+
+  /**
+   * Gets the format of the dates.
+   *
+   * @return Returns the format as used by chrono.
+   */
+  fn date_format(&self) -> &str
+  {
+    self.date.format.as_deref().unwrap_or("%Y-%m-%d")
+  }
+
+  /**
+   * Gets the character that separates the fields.
+   *
+   * @return Returns the delimiter as byte.
+   */
+  fn delimiter(&self) -> u8
+  {
+    // Non-ASCII delimiters are rejected when parsing the mapping.
+    self.delimiter.map_or(b',', |c| c as u8)
+  }
+}
+
+/// importer for CSV files whose columns are described by a mapping
+pub struct MappingImporter
+{
+  mapping: Mapping
+}
+
+impl MappingImporter
+{
+  /**
+   * Creates an importer for a mapping.
+   *
+   * @param mapping   description of the CSV file
+   */
+  pub fn new(mapping: Mapping) -> MappingImporter
+  {
+    MappingImporter { mapping }
+  }
+
+  /**
+   * Creates an importer from a mapping file.
+   *
+   * @param path   path of the TOML file
+   * @return Returns the importer in case of success.
+   *         Returns an Error, if the file cannot be read or is invalid.
+   */
+  pub fn load(path: &str) -> Result<MappingImporter, Error>
+  {
+    Ok(MappingImporter::new(Mapping::load(path)?))
+  }
+
+  /**
+   * Gets the names of the columns that have to exist in the CSV file.
+   *
+   * @return Returns the column names.
+   */
+  fn required_columns(&self) -> Vec<&str>
+  {
+    let m = &self.mapping;
+    let mut names = vec![m.date.column.as_str(), m.cases.column.as_str(), m.deaths.column.as_str()];
+    names.extend(m.country.code_column.as_deref());
+    names.extend(m.country.name_column.as_deref());
+    names
+  }
+
+  /**
+   * Gets the country for a code or a name as given in the CSV file.
+   *
+   * @param world    list of known countries
+   * @param code     ISO-3166 two- or three-letter code, may be empty
+   * @param name     name of the country, may be empty
+   * @param report   report where unknown countries are recorded
+   * @return Returns the known country, or a new country with the given data.
+   *         Returns None for unknown countries without a code, because the
+   *         geo id of a country must not be its full name.
+   */
+  fn find_country(world: &World, code: &str, name: &str, report: &mut ImportReport) -> Option<Country>
+  {
+    let code = code.to_uppercase();
+    let known = match code.len()
+    {
+      2 => world.find_by_geo_id(&code),
+      3 => world.find_by_country_code(&code),
+      _ => None
+    };
+    let known = match (known, name.is_empty())
+    {
+      (None, false) => world.find_by_name(name),
+      (known, _) => known
+    };
+    if known.is_none()
+    {
+      report.not_found(if name.is_empty() { &code } else { name });
+    }
+    match known
+    {
+      Some(country) => Some(country.clone()),
+      None if code.is_empty() => None,
+      None => Some(Country {
+        country_id: -1,
+        name: if name.is_empty() { code.clone() } else { name.to_string() },
+        population: -1,
+        geo_id: code.clone(),
+        country_code: if code.len() == 3 { code } else { String::new() },
+        continent: String::new()
+      })
+    }
+  }
+
+  /**
+   * Parses a number of cases or deaths.
+   *
+   * @param record   the current CSV record
+   * @param idx      zero-based position of the column
+   * @param column   name of the column
+   * @return Returns the number. Empty fields count as zero.
+   *         Returns an Error, if the field is not a number.
+   */
+  fn parse_number(record: &csv::StringRecord, idx: usize, column: &str) -> Result<i64, Error>
+  {
+    let value = record[idx].trim();
+    if value.is_empty()
+    {
+      return Ok(0);
+    }
+    match value.parse::<f64>()
+    {
+      Ok(number) if number.is_finite() => Ok(number as i64),
+      _ => Err(Error::csv_parse(record, &format!(
+        "'{value}' in column '{column}' is not a valid number."
+      )))
+    }
+  }
+}
+
+impl Importer for MappingImporter
+{
+  fn csv_type(&self) -> CsvType
+  {
+    CsvType::Other(self.mapping.name.clone().unwrap_or_else(|| String::from("custom mapping")))
+  }
+
+  fn detect(&self, first_line: &str) -> bool
+  {
+    let mut reader = csv::ReaderBuilder::new()
+      .has_headers(false)
+      .delimiter(self.mapping.delimiter())
+      .from_reader(first_line.as_bytes());
+    match reader.records().next()
+    {
+      Some(Ok(headers)) => Columns::new(&headers).contains_all(&self.required_columns()),
+      _ => false
+    }
+  }
+
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
+  {
+    let m = &self.mapping;
+    let mut reader = csv::ReaderBuilder::new()
+      .delimiter(m.delimiter())
+      .from_reader(input);
+    let columns = Columns::new(reader.headers()?);
+    let [idx_date, idx_cases, idx_deaths] = columns.require([&m.date.column, &m.cases.column, &m.deaths.column].map(|c| c.as_str()))?;
+    let idx_code = match &m.country.code_column
+    {
+      Some(column) => Some(columns.require([column.as_str()])?[0]),
+      None => None
+    };
+    let idx_name = match &m.country.name_column
+    {
+      Some(column) => Some(columns.require([column.as_str()])?[0]),
+      None => None
+    };
+
+    let world = World::new();
+    let format = m.date_format();
+    // Countries as found in the file, indexed by their code and name. Unknown
+    // countries without a code are None.
+    let mut found: HashMap<(String, String), Option<Country>> = HashMap::new();
+    // Sums of cases and deaths per date, indexed by the geo id of the country.
+    // Files with regional data contain several rows per country and date.
+    let mut countries: BTreeMap<String, (Country, save::DailySums)> = BTreeMap::new();
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record)?
+    {
+      let date = record[idx_date].trim();
+      let date = match NaiveDate::parse_from_str(date, format)
+      {
+        Ok(d) => d.format("%Y-%m-%d").to_string(),
+        Err(_) => return Err(Error::csv_parse(&record, &format!(
+          "'{date}' is not a date in the format '{format}'."
+        )))
+      };
+      let code = idx_code.map(|idx| record[idx].trim()).unwrap_or_default();
+      let code = match (code.is_empty(), &m.country.code)
+      {
+        (true, Some(fixed)) => fixed.as_str(),
+        _ => code
+      };
+      let name = idx_name.map(|idx| record[idx].trim()).unwrap_or_default();
+      if code.is_empty() && name.is_empty()
+      {
+        return Err(Error::csv_parse(&record, "The line contains neither a country code nor a country name."));
+      }
+      let cases = MappingImporter::parse_number(&record, idx_cases, &m.cases.column)?;
+      let deaths = MappingImporter::parse_number(&record, idx_deaths, &m.deaths.column)?;
+
+      let country = found
+        .entry((code.to_string(), name.to_string()))
+        .or_insert_with(|| MappingImporter::find_country(&world, code, name, report));
+      let country = match country
+      {
+        Some(country) => country,
+        None =>
+        {
+          report.skipped(name, SkipReason::MissingCode);
+          continue;
+        }
+      };
+      report.kept(if name.is_empty() { code } else { name }, 1);
+      let (_, days) = countries
+        .entry(country.geo_id.clone())
+        .or_insert_with(|| (country.clone(), BTreeMap::new()));
+      let sums = days.entry(date).or_insert((0, 0));
+      sums.0 += cases;
+      sums.1 += deaths;
+    }
+
+    for (country, days) in countries.into_values()
+    {
+      let mut numbers = save::daily_numbers(days, m.cases.cumulative, m.deaths.cumulative);
+      let country_id = db.get_country_id_or_insert(
+        &country.geo_id,
+        &country.name,
+        &i64::from(country.population),
+        &country.country_code,
+        &country.continent
+      )?;
+      save::numbers_into_db(db, &country_id, &country.population, &mut numbers)?;
+    }
+
+    // Done.
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::data::DateRange;
+  use crate::db::Db;
+
+  #[test]
+  fn parse_mapping()
+  {
+    let mapping = Mapping::parse(r#"
+name = "Agency"
+delimiter = ";"
+
+[date]
+column = "Day"
+format = "%d.%m.%Y"
+
+[country]
+code_column = "Code"
+
+[cases]
+column = "Cases"
+cumulative = true
+
+[deaths]
+column = "Deaths"
+"#);
+    assert!(mapping.is_ok());
+    let mapping = mapping.unwrap();
+    assert_eq!(mapping.date_format(), "%d.%m.%Y");
+    assert_eq!(mapping.delimiter(), b';');
+    assert!(mapping.cases.cumulative);
+    assert!(!mapping.deaths.cumulative);
+    assert_eq!(MappingImporter::new(mapping).csv_type(), CsvType::Other(String::from("Agency")));
+  }
+
+  #[test]
+  fn parse_mapping_errors()
+  {
+    // no country
+    let result = Mapping::parse("[date]\ncolumn = \"d\"\n[country]\n[cases]\ncolumn = \"c\"\n[deaths]\ncolumn = \"x\"\n");
+    assert!(matches!(result, Err(e) if e.contains("[country]")));
+    // unknown entry
+    let result = Mapping::parse("foo = 1\n[date]\ncolumn = \"d\"\n[country]\ncode = \"DE\"\n[cases]\ncolumn = \"c\"\n[deaths]\ncolumn = \"x\"\n");
+    assert!(result.is_err());
+    // missing section
+    assert!(Mapping::parse("[date]\ncolumn = \"d\"\n").is_err());
+  }
+
+  #[test]
+  fn import_regional_data()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_mapping_import.csv");
+    let mapping_path = dir.join("test_mapping_import.toml");
+    let db_path = dir.join("test_mapping_import.db");
+    let _ = std::fs::remove_file(&db_path);
+    // Two regions per day for Germany, one row for Austria and one row for an
+    // unknown country.
+    let csv = "Region;Land;Datum;Faelle;Tote
+Bayern;DEU;02.01.2021;10;1
+Berlin;DEU;02.01.2021;5;0
+Bayern;DEU;01.01.2021;3;0
+Wien;at;01.01.2021;7;1
+Nirgendwo;;01.01.2021;1;0
+";
+    assert!(std::fs::write(&csv_path, csv).is_ok());
+    let mapping = r#"
+name = "Agency"
+delimiter = ";"
+
+[date]
+column = "Datum"
+format = "%d.%m.%Y"
+
+[country]
+code_column = "Land"
+code = "ZZ"
+
+[cases]
+column = "Faelle"
+
+[deaths]
+column = "Tote"
+"#;
+    assert!(std::fs::write(&mapping_path, mapping).is_ok());
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: Some(mapping_path.to_str().unwrap().to_string()),
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let importer = MappingImporter::load(config.mapping.as_ref().unwrap()).unwrap();
+    assert!(importer.detect(csv.lines().next().unwrap()));
+    assert!(!importer.detect("Region,Land,Datum,Faelle,Tote"));
+
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      assert_eq!(db.meta("source_format").unwrap(), Some(String::from("Agency")));
+      let countries = db.countries().unwrap();
+      assert_eq!(countries.len(), 3);
+      let germany = countries.iter().find(|c| c.geo_id == "DE").unwrap();
+      assert_eq!(germany.name, "Germany");
+      assert!(germany.population > 0);
+      let numbers = db.numbers(&germany.country_id, &DateRange::default()).unwrap();
+      assert_eq!(numbers.len(), 2);
+      assert_eq!(numbers[0].date, "2021-01-01");
+      assert_eq!(numbers[0].cases, 3);
+      assert_eq!(numbers[1].cases, 15);
+      assert_eq!(numbers[1].deaths, 1);
+      assert!(countries.iter().any(|c| c.geo_id == "AT"));
+      assert!(countries.iter().any(|c| c.geo_id == "ZZ"));
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(mapping_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn import_unknown_name_without_code()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_mapping_unknown_name.csv");
+    let mapping_path = dir.join("test_mapping_unknown_name.toml");
+    let db_path = dir.join("test_mapping_unknown_name.db");
+    let _ = std::fs::remove_file(&db_path);
+    assert!(std::fs::write(&csv_path, "date,country,cases,deaths\n\
+                                       2021-01-01,Germany,1,0\n\
+                                       2021-01-01,Atlantis,2,0\n\
+                                       2021-01-02,Atlantis,3,0\n").is_ok());
+    assert!(std::fs::write(&mapping_path, "[date]\ncolumn = \"date\"\n[country]\nname_column = \"country\"\n\
+                                           [cases]\ncolumn = \"cases\"\n[deaths]\ncolumn = \"deaths\"\n").is_ok());
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: Some(mapping_path.to_str().unwrap().to_string()),
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let (_, report) = Db::new(&config).unwrap().create_db_detected().unwrap();
+    // The rows of the unknown country are skipped, because it has no code
+    // that could be used as geo id.
+    assert!(report.unknown_countries.contains("Atlantis"));
+    let atlantis = report.countries.get("Atlantis").unwrap();
+    assert_eq!(atlantis.rows_read, 2);
+    assert_eq!(atlantis.rows_kept, 0);
+    assert_eq!(atlantis.rows_skipped.get(&SkipReason::MissingCode), Some(&2));
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      let countries = db.countries().unwrap();
+      assert_eq!(countries.len(), 1);
+      assert_eq!(countries[0].geo_id, "DE");
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(mapping_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn import_invalid_date()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_mapping_invalid_date.csv");
+    let db_path = dir.join("test_mapping_invalid_date.db");
+    let _ = std::fs::remove_file(&db_path);
+    assert!(std::fs::write(&csv_path, "date,cases,deaths\n2021-01-01,1,0\n01/02/2021,2,0\n").is_ok());
+    let mapping = Mapping::parse("[date]\ncolumn = \"date\"\n[country]\ncode = \"FR\"\n\
+                                  [cases]\ncolumn = \"cases\"\n[deaths]\ncolumn = \"deaths\"\n").unwrap();
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let result = Db::new(&config).unwrap().create_db_with(&MappingImporter::new(mapping));
+    match result
+    {
+      Err(Error::CsvParse { line, message }) =>
+      {
+        assert_eq!(line, 3);
+        assert!(message.contains("01/02/2021"));
+      },
+      _ => panic!("Expected parse error!")
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+}
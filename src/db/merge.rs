@@ -0,0 +1,505 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::configuration::{MergeConfiguration, Precedence};
+use crate::data::{Country, DateRange, Numbers, Provenance};
+use crate::database::Database;
+use crate::db::{save, Db, STDIN};
+use crate::error::Error;
+use crate::world::World;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// numbers of one country, collected from all inputs of a merge
+struct MergedCountry
+{
+  /// country data that is written to the merged database
+  country: Country,
+  /// daily numbers, indexed by date
+  numbers: BTreeMap<String, Numbers>
+}
+
+pub struct DbMerge
+{
+  config: MergeConfiguration
+}
+
+impl DbMerge
+{
+  /**
+   * Creates a new instance.
+   *
+   * @config   application configuration
+   * @return   Returns a Result containing the DbMerge object, if successful.
+   *           Returns an Error, if the configuration seems to be invalid.
+   */
+  pub fn new(config: &MergeConfiguration) -> Result<DbMerge, Error>
+  {
+    if config.db_path.is_empty()
+    {
+      return Err(Error::Configuration("Path for SQLite database must not be an empty string!".to_string()));
+    }
+    if config.inputs.iter().any(|input| input.is_empty())
+    {
+      return Err(Error::Configuration("Paths of input files must not be empty strings!".to_string()));
+    }
+
+    Ok(DbMerge
+    {
+      config: MergeConfiguration
+      {
+        inputs: config.inputs.clone(),
+        db_path: config.db_path.clone(),
+        precedence: config.precedence,
+        quiet: config.quiet,
+        gaps: config.gaps
+      }
+    })
+  }
+
+  /**
+   * Combines all inputs into a new SQLite database.
+   *
+   * Inputs that are CSV files are imported into temporary databases first.
+   * Countries of different inputs are matched by their ISO codes, and incidence
+   * values as well as accumulated numbers are recalculated from the merged
+   * daily numbers.
+   *
+   * @return Returns an Error, if the merge failed.
+   */
+  pub fn merge_db(&self) -> Result<(), Error>
+  {
+    let db_path = Path::new(&self.config.db_path);
+    if db_path.exists()
+    {
+      return Err(Error::AlreadyExists(db_path.to_path_buf()));
+    }
+
+    let mut temporary_files: Vec<PathBuf> = Vec::new();
+    let mut provenance: Vec<Provenance> = Vec::new();
+    let result = self.collect(&mut temporary_files, &mut provenance);
+    // Temporary databases are not needed anymore, whatever the outcome was.
+    for path in temporary_files.iter()
+    {
+      let _ = std::fs::remove_file(path);
+    }
+    let (countries, formats) = result?;
+
+    let db = Database::create(&self.config.db_path)?;
+    let country_count = countries.len();
+    let result = DbMerge::write(&db, countries, &formats, &provenance);
+    if result.is_err()
+    {
+      // Do not leave a half-written database behind.
+      drop(db);
+      let _ = std::fs::remove_file(db_path);
+    }
+    let days = result?;
+    if !self.config.quiet
+    {
+      println!(
+        "Merged {} inputs into {} countries with {} days of data in total.",
+        self.config.inputs.len(),
+        country_count,
+        days
+      );
+    }
+    Ok(())
+  }
+
+  /**
+   * Writes the merged countries and the history of all inputs into the new
+   * database. All changes are performed in a single transaction, so the
+   * database is left empty, if one of them fails.
+   *
+   * @param db           the newly created database
+   * @param countries    the merged countries, indexed by their geo id
+   * @param formats      source formats of the inputs
+   * @param provenance   recorded imports of all inputs
+   * @return Returns the number of days written in case of success.
+   */
+  fn write(db: &Database, countries: BTreeMap<String, MergedCountry>, formats: &[String], provenance: &[Provenance]) -> Result<usize, Error>
+  {
+    db.batch("BEGIN TRANSACTION;")?;
+    match DbMerge::write_countries(db, countries, formats, provenance)
+    {
+      Ok(days) =>
+      {
+        db.batch("COMMIT;")?;
+        Ok(days)
+      },
+      Err(e) =>
+      {
+        let _ = db.batch("ROLLBACK;");
+        Err(e)
+      }
+    }
+  }
+
+  /**
+   * Writes the merged countries and the history of all inputs into the new
+   * database. The caller is responsible for the transaction.
+   *
+   * @param db           the newly created database
+   * @param countries    the merged countries, indexed by their geo id
+   * @param formats      source formats of the inputs
+   * @param provenance   recorded imports of all inputs
+   * @return Returns the number of days written in case of success.
+   */
+  fn write_countries(db: &Database, countries: BTreeMap<String, MergedCountry>, formats: &[String], provenance: &[Provenance]) -> Result<usize, Error>
+  {
+    let mut days = 0;
+    for merged in countries.into_values()
+    {
+      let c = &merged.country;
+      let country_id = db.get_country_id_or_insert(
+        &c.geo_id, &c.name, &i64::from(c.population), &c.country_code, &c.continent
+      )?;
+      let mut numbers: Vec<Numbers> = merged.numbers.into_values().collect();
+      days += numbers.len();
+      save::numbers_into_db(db, &country_id, &c.population, &mut numbers)?;
+    }
+    db.set_meta("source_format", &format!("merged ({})", formats.join(", ")))?;
+    // The merged database keeps the history of all its inputs.
+    for entry in provenance.iter()
+    {
+      db.insert_provenance(entry)?;
+    }
+    Ok(days)
+  }
+
+  /**
+   * Reads the numbers of all inputs and merges them in memory.
+   *
+   * @param temporary_files  receives the paths of temporary databases that
+   *                         have to be deleted by the caller
+   * @param provenance       receives the recorded imports of all inputs
+   * @return Returns the merged countries, indexed by their geo id, and the
+   *         source formats of the inputs in case of success.
+   */
+  fn collect(&self, temporary_files: &mut Vec<PathBuf>, provenance: &mut Vec<Provenance>) -> Result<(BTreeMap<String, MergedCountry>, Vec<String>), Error>
+  {
+    let world = World::new();
+    let mut countries: BTreeMap<String, MergedCountry> = BTreeMap::new();
+    let mut formats: Vec<String> = Vec::new();
+    let all = DateRange::default();
+    for input in self.config.inputs.iter()
+    {
+      let (source, format) = if DbMerge::is_sqlite_file(input)?
+      {
+        let source = Database::new(input)?;
+        let format = source.meta("source_format")?.unwrap_or_else(|| String::from("unknown"));
+        (source, format)
+      }
+      else
+      {
+        let (path, csv_type, _) = Db::import_into_temporary_db(input, None, self.config.gaps, "merge")?;
+        temporary_files.push(path.clone());
+        (Database::new(&path.to_string_lossy())?, csv_type.name().to_string())
+      };
+      if !formats.contains(&format)
+      {
+        formats.push(format);
+      }
+      provenance.extend(source.provenance()?);
+
+      for entry in source.country_statistics()?.iter()
+      {
+        let country = &entry.country;
+        let canonical = DbMerge::canonical_country(&world, country);
+        let merged = countries
+          .entry(canonical.geo_id.clone())
+          .or_insert_with(|| MergedCountry { country: canonical, numbers: BTreeMap::new() });
+        // Population numbers of the inputs are usually more recent than the
+        // built-in ones, so they follow the same precedence as the numbers.
+        if country.population > 0
+          && (merged.country.population <= 0 || self.config.precedence == Precedence::Last)
+        {
+          merged.country.population = country.population;
+        }
+        for n in source.numbers(&country.country_id, &all)?.into_iter()
+        {
+          match self.config.precedence
+          {
+            Precedence::First =>
+            {
+              merged.numbers.entry(n.date.clone()).or_insert(n);
+            },
+            Precedence::Last =>
+            {
+              merged.numbers.insert(n.date.clone(), n);
+            }
+          }
+        }
+      }
+    }
+    // Use the built-in population, if none of the inputs knows it.
+    for merged in countries.values_mut().filter(|m| m.country.population <= 0)
+    {
+      if let Some(known) = world.find_by_geo_id(&merged.country.geo_id)
+      {
+        merged.country.population = known.population;
+      }
+    }
+    Ok((countries, formats))
+  }
+
+  /**
+   * Gets the data of a country as it shall be written to the merged database.
+   * Known countries get the data of the built-in list of countries, so that
+   * inputs with slightly different names or codes end up as one country.
+   *
+   * @param world    list of known countries
+   * @param country  country as it appears in one of the inputs
+   * @return Returns the country data to use for the merged database.
+   */
+  fn canonical_country(world: &World, country: &Country) -> Country
+  {
+    let known = match country.country_code.is_empty()
+    {
+      true => None,
+      false => world.find_by_country_code(&country.country_code)
+    };
+    let known = known.or_else(|| world.find_by_geo_id(&country.geo_id));
+    match known
+    {
+      Some(known) => Country {
+        country_id: -1,
+        name: known.name.clone(),
+        population: -1,
+        geo_id: known.geo_id.clone(),
+        country_code: known.country_code.clone(),
+        continent: known.continent.clone()
+      },
+      None => Country {
+        country_id: -1,
+        population: -1,
+        ..country.clone()
+      }
+    }
+  }
+
+  /**
+   * Checks whether a file is a SQLite database by looking at its header.
+   *
+   * @param path   path of the file, or "-" for the standard input
+   * @return Returns true, if the file is a SQLite database. The standard input
+   *         is never a database, it can only provide CSV data.
+   *         Returns an Error, if the file could not be read.
+   */
+  fn is_sqlite_file(path: &str) -> Result<bool, Error>
+  {
+    use std::io::Read;
+
+    // Reading the header would consume the data of the standard input.
+    if path == STDIN
+    {
+      return Ok(false);
+    }
+    let path = Path::new(path);
+    let mut file = std::fs::File::open(path).map_err(|e| Error::io(path, e))?;
+    let mut header = [0u8; 16];
+    match file.read_exact(&mut header)
+    {
+      Ok(()) => Ok(&header == b"SQLite format 3\0"),
+      // Files shorter than the header cannot be databases.
+      Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+      Err(e) => Err(Error::io(path, e))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::configuration::GapHandling;
+  use crate::db::tests::write_who_csv;
+
+  /**
+   * Gets the path to the corona.db file in data directory.
+   *
+   * @return Returns path of the SQLite database.
+   */
+  fn get_sqlite_db_path() -> String
+  {
+    let db_path = Path::new(file!()) // current file: src/db/merge.rs
+      .parent()
+      .unwrap() // parent: src/db/
+      .join("..") // up one directory
+      .join("..") // up another directory
+      .join("data") // into directory data/
+      .join("corona-ecdc-2020-12-14.db"); // and to the corona-ecdc-2020-12-14.db file;
+    db_path.to_str().unwrap().to_string()
+  }
+
+  #[test]
+  fn canonical_country()
+  {
+    let world = World::new();
+    let germany = Country {
+      country_id: 12,
+      name: String::from("Deutschland"),
+      population: 83_000_000,
+      geo_id: String::from("XY"),
+      country_code: String::from("DEU"),
+      continent: String::from("EURO")
+    };
+    let canonical = DbMerge::canonical_country(&world, &germany);
+    assert_eq!(canonical.geo_id, "DE");
+    assert_eq!(canonical.name, "Germany");
+    assert_eq!(canonical.continent, "Europe");
+
+    let unknown = Country {
+      country_id: 12,
+      name: String::from("Wonderland"),
+      population: 42,
+      geo_id: String::from("ZZ"),
+      country_code: String::new(),
+      continent: String::from("Utopia")
+    };
+    let canonical = DbMerge::canonical_country(&world, &unknown);
+    assert_eq!(canonical.geo_id, "ZZ");
+    assert_eq!(canonical.name, "Wonderland");
+  }
+
+  #[test]
+  fn is_sqlite_file()
+  {
+    assert!(DbMerge::is_sqlite_file(&get_sqlite_db_path()).unwrap());
+    assert!(!DbMerge::is_sqlite_file(file!()).unwrap());
+    assert!(DbMerge::is_sqlite_file("/does/not/exist.db").is_err());
+    assert!(!DbMerge::is_sqlite_file(STDIN).unwrap());
+  }
+
+  #[test]
+  fn merge_database_and_csv()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_merge_db.csv");
+    let db_path = dir.join("test_merge_db.db");
+    let _ = std::fs::remove_file(&db_path);
+    // Germany overlaps with the ECDC database on 2020-12-14.
+    write_who_csv(&csv_path, "2020-12-14,DE,Germany,EURO,1,1,0,0\n\
+                              2020-12-15,DE,Germany,EURO,2,3,0,0\n\
+                              2020-12-14,ZZ,Wonderland,EURO,5,5,1,1\n");
+    let mut config = MergeConfiguration {
+      inputs: vec![get_sqlite_db_path(), csv_path.to_str().unwrap().to_string()],
+      db_path: db_path.to_str().unwrap().to_string(),
+      precedence: Precedence::First,
+      quiet: true,
+      gaps: GapHandling::Truncate
+    };
+    let source = Database::new(&get_sqlite_db_path()).unwrap();
+    let source_germany = source.countries().unwrap().into_iter().find(|c| c.geo_id == "DE").unwrap();
+    let source_numbers = source.numbers(&source_germany.country_id, &DateRange::default()).unwrap();
+    let last = source_numbers.last().unwrap();
+    assert_eq!(last.date, "2020-12-14");
+
+    for precedence in [Precedence::First, Precedence::Last]
+    {
+      config.precedence = precedence;
+      assert!(DbMerge::new(&config).unwrap().merge_db().is_ok());
+      // scope for database
+      {
+        let db = Database::new(&config.db_path).unwrap();
+        let countries = db.countries().unwrap();
+        assert_eq!(countries.iter().filter(|c| c.geo_id == "DE").count(), 1);
+        assert!(countries.iter().any(|c| c.name == "Wonderland"));
+        let germany = countries.iter().find(|c| c.geo_id == "DE").unwrap();
+        let numbers = db.numbers(&germany.country_id, &DateRange::default()).unwrap();
+        assert_eq!(numbers.len(), source_numbers.len() + 1);
+        let overlap = numbers.iter().find(|n| n.date == "2020-12-14").unwrap();
+        match precedence
+        {
+          Precedence::First => assert_eq!(overlap.cases, last.cases),
+          Precedence::Last => assert_eq!(overlap.cases, 1)
+        }
+        // Accumulated numbers are recalculated over the merged data.
+        let accumulated = db.accumulated_numbers(&germany.country_id, &DateRange::default()).unwrap();
+        let expected: i32 = numbers.iter().map(|n| n.cases).sum();
+        assert_eq!(accumulated.last().unwrap().cases, expected);
+        assert_eq!(db.meta("source_format").unwrap(), Some(String::from("merged (unknown, WHO)")));
+        // Only the CSV file has a recorded import, the old database has none.
+        let provenance = db.provenance().unwrap();
+        assert_eq!(provenance.len(), 1);
+        assert_eq!(provenance[0].format, "WHO");
+      }
+      assert!(std::fs::remove_file(&db_path).is_ok());
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+  }
+
+  #[test]
+  fn failed_write_is_rolled_back()
+  {
+    let db_path = std::env::temp_dir().join("test_merge_rollback.db");
+    let _ = std::fs::remove_file(&db_path);
+    let db = Database::create(db_path.to_str().unwrap()).unwrap();
+    let country = Country {
+      country_id: -1,
+      name: String::from("Wonderland"),
+      population: 42,
+      geo_id: String::from("ZZ"),
+      country_code: String::new(),
+      continent: String::from("Utopia")
+    };
+    let numbers = BTreeMap::from([
+      (String::from("2020-12-14"), Numbers { date: String::from("2020-12-14"), cases: 5, deaths: 1 })
+    ]);
+    let countries = BTreeMap::from([(String::from("ZZ"), MergedCountry { country, numbers })]);
+    let provenance = vec![Provenance {
+      format: String::from("WHO"),
+      attribution: String::from("World Health Organization (WHO)"),
+      file: String::from("who.csv"),
+      sha256: None,
+      imported_at: String::from("2026-10-18T12:34:56Z"),
+      version: String::from("0.20.1"),
+      rows_read: 1,
+      rows_kept: 1,
+      rows_skipped: 0
+    }];
+    // Recording the provenance is the last step, so all numbers have already
+    // been written when it fails.
+    assert!(db.batch("DROP TABLE provenance;").is_ok());
+    assert!(DbMerge::write(&db, countries, &[String::from("WHO")], &provenance).is_err());
+    assert!(db.countries().unwrap().is_empty());
+    assert!(db.meta("source_format").unwrap().is_none());
+    // No transaction is left open.
+    assert!(db.batch("BEGIN TRANSACTION; COMMIT;").is_ok());
+
+    // clean up
+    drop(db);
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn merge_into_existing_database()
+  {
+    let config = MergeConfiguration {
+      inputs: vec![get_sqlite_db_path(), get_sqlite_db_path()],
+      db_path: get_sqlite_db_path(),
+      precedence: Precedence::First,
+      quiet: true,
+      gaps: GapHandling::Truncate
+    };
+    let result = DbMerge::new(&config).unwrap().merge_db();
+    assert!(matches!(result, Err(Error::AlreadyExists(_))));
+  }
+}
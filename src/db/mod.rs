@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2023, 2024, 2025  Dirk Stolle
+    Copyright (C) 2023, 2024, 2025, 2026  Dirk Stolle
 
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
@@ -16,30 +16,53 @@
  -------------------------------------------------------------------------------
 */
 
-use super::configuration::DbConfiguration;
+use super::configuration::{DbConfiguration, GapHandling};
+mod columns;
+mod disease_sh;
 mod ecdc;
-mod save;
+mod ecdc_weekly;
+mod importer;
+mod input;
+mod jhu;
+mod mapping;
+mod merge;
+mod report;
+pub mod save;
 mod owid;
 mod owid_etl_compact;
+mod update;
 mod who;
-use crate::db::ecdc::DbEcdc;
-use crate::db::owid::DbOwid;
-use crate::db::owid_etl_compact::DbOwidEtlCompact;
-use crate::db::who::DbWho;
+pub use crate::db::columns::Columns;
+pub use crate::db::importer::{Importer, ImporterRegistry};
+pub use crate::db::input::{Input, InputDigest, STDIN};
+pub use crate::db::mapping::{CountryColumns, DateColumn, Mapping, MappingImporter, NumberColumn};
+pub(crate) use crate::db::merge::DbMerge;
+pub use crate::db::report::{ImportReport, InputFile, RowStatistics, SkipReason};
+use crate::db::update::DbUpdate;
+use crate::data::Provenance;
+use crate::database::Database;
+use crate::error::Error;
+use std::path::{Path, PathBuf};
 
+/// Creates and updates SQLite databases from CSV files.
 pub struct Db
 {
-  config: DbConfiguration
+  config: DbConfiguration,
+  importers: ImporterRegistry
 }
 
 /// enumeration for supported CSV formats
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum CsvType
 {
   /// ECDC's CSV format as used at <https://data.europa.eu/euodp/data/dataset/covid-19-coronavirus-data>
   /// before 14th December 2020
   Ecdc,
 
+  /// ECDC's weekly CSV format as used at <https://www.ecdc.europa.eu/en/publications-data/data-national-14-day-notification-rate-covid-19>
+  /// after 14th December 2020
+  EcdcWeekly,
+
   /// Our World In Data's CSV format as used at <https://covid.ourworldindata.org/data/owid-covid-data.csv>
   Owid,
 
@@ -47,28 +70,98 @@ pub enum CsvType
   OwidEtlCompact,
 
   /// WHO's CSV format as used at <https://covid19.who.int/data>
-  Who
+  Who,
+
+  /// time series CSV format of the Johns Hopkins University as used at
+  /// <https://github.com/CSSEGISandData/COVID-19> until 10th March 2023
+  Jhu,
+
+  /// JSON format of the historical data of the disease.sh API as used at
+  /// <https://disease.sh/v3/covid-19/historical>
+  DiseaseSh,
+
+  /// format of an importer that was registered by a user of the library,
+  /// identified by its name
+  Other(String)
+}
+
+impl CsvType
+{
+  /**
+   * Gets a short, human-readable name of the CSV format.
+   *
+   * @return Returns the name of the format, e. g. "OWID".
+   */
+  pub fn name(&self) -> &str
+  {
+    match self
+    {
+      CsvType::Ecdc => "ECDC",
+      CsvType::EcdcWeekly => "ECDC weekly",
+      CsvType::Owid => "OWID",
+      CsvType::OwidEtlCompact => "OWID ETL compact",
+      CsvType::Who => "WHO",
+      CsvType::Jhu => "JHU",
+      CsvType::DiseaseSh => "disease.sh JSON",
+      CsvType::Other(name) => name
+    }
+  }
+
+  /**
+   * Gets the attribution of the data source, i. e. the organisation that
+   * publishes data in that format.
+   *
+   * @return Returns the attribution, e. g. "Our World in Data". Formats of
+   *         other importers are attributed with their name.
+   */
+  pub fn attribution(&self) -> &str
+  {
+    match self
+    {
+      CsvType::Ecdc | CsvType::EcdcWeekly => "European Centre for Disease Prevention and Control (ECDC)",
+      CsvType::Owid | CsvType::OwidEtlCompact => "Our World in Data",
+      CsvType::Who => "World Health Organization (WHO)",
+      CsvType::Jhu => "Center for Systems Science and Engineering (CSSE) at Johns Hopkins University",
+      CsvType::DiseaseSh => "disease.sh",
+      CsvType::Other(name) => name
+    }
+  }
 }
 
 impl Db
 {
   /**
-   * Creates a new instance.
+   * Creates a new instance that knows the built-in source formats.
    *
    * @config   application configuration
    * @return   Returns a Result containing the Db object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
+   *           Returns an Error, if the configuration seems to be invalid.
    */
-  pub fn new(config: &DbConfiguration) -> Result<Db, String>
+  pub fn new(config: &DbConfiguration) -> Result<Db, Error>
+  {
+    Db::with_importers(config, ImporterRegistry::with_gap_handling(config.gaps))
+  }
+
+  /**
+   * Creates a new instance that uses the given importers to detect and read
+   * the CSV file. The handling of missing days in the configuration is not
+   * applied to the importers, it is up to the caller to create them
+   * accordingly.
+   *
+   * @config      application configuration
+   * @importers   importers for all source formats that shall be supported
+   * @return   Returns a Result containing the Db object, if successful.
+   *           Returns an Error, if the configuration seems to be invalid.
+   */
+  pub fn with_importers(config: &DbConfiguration, importers: ImporterRegistry) -> Result<Db, Error>
   {
     if config.db_path.is_empty()
     {
-      return Err("Path for SQLite database must not be an empty string!".to_string());
+      return Err(Error::Configuration("Path for SQLite database must not be an empty string!".to_string()));
     }
     if config.csv_input_file.is_empty()
     {
-      return Err("Path of CSV file must be set to a non-empty string!".to_string());
+      return Err(Error::Configuration("Path of CSV file must be set to a non-empty string!".to_string()));
     }
 
     Ok(Db
@@ -76,99 +169,196 @@ impl Db
       config: DbConfiguration
       {
         csv_input_file: config.csv_input_file.clone(),
-        db_path: config.db_path.clone()
-      }
+        db_path: config.db_path.clone(),
+        quiet: config.quiet,
+        mapping: config.mapping.clone(),
+        report: config.report.clone(),
+        gaps: config.gaps
+      },
+      importers
     })
   }
 
   /**
-   * Creates the SQLite database from a CSV file.
+   * Creates the SQLite database from a CSV file. If the configuration names a
+   * mapping file, the mapping describes the format of the CSV file. Otherwise
+   * the format is detected. The file may be compressed with gzip or be a ZIP
+   * archive, and "-" reads the data from standard input. Afterwards the
+   * import report is shown and, if requested, written to a JSON file.
    *
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the database could not be created.
    */
-  pub fn create_db(&self) -> bool
+  pub fn create_db(&self) -> Result<(), Error>
   {
-    if !std::path::Path::new(&self.config.csv_input_file).is_file()
-    {
-      eprintln!("Error: {} does not exist or is not a file.",
-                self.config.csv_input_file);
-      return false;
-    }
-    match Db::get_csv_type(&self.config.csv_input_file)
+    let (_, report) = self.create_db_detected()?;
+    report.output(&self.config)
+  }
+
+  /**
+   * Creates the SQLite database from a CSV file like create_db() does, but
+   * without showing the import report.
+   *
+   * @return Returns the format of the CSV file and the import report in case
+   *         of success. Returns an Error, if the database could not be created.
+   */
+  fn create_db_detected(&self) -> Result<(CsvType, ImportReport), Error>
+  {
+    let input = Input::open(&self.config.csv_input_file)?;
+    match &self.config.mapping
     {
-      Some(CsvType::Ecdc) => {
-        let db = DbEcdc::new(&self.config).unwrap();
-        db.create_db()
-      },
-      Some(CsvType::Owid) => {
-        let db = DbOwid::new(&self.config).unwrap();
-        db.create_db()
-      },
-      Some(CsvType::OwidEtlCompact) => {
-        let db = DbOwidEtlCompact::new(&self.config).unwrap();
-        db.create_db()
-      },
-      Some(CsvType::Who) => {
-        let db = DbWho::new(&self.config).unwrap();
-        db.create_db()
-      },
-      None => {
-        eprintln!("File {} does not seem to contain a known CSV format!",
-                  self.config.csv_input_file);
-        eprintln!("Only CSV format as used by the ECDC, Our World In Data or the WHO can be detected.");
-        false
-      }
+      Some(mapping) => self.import(&MappingImporter::load(mapping)?, input),
+      None => self.import(self.importers.detect(&input)?, input)
     }
   }
 
   /**
-   * Gets the CSV type of the file by checking the CSV headers.
+   * Creates the SQLite database from a CSV file with a given importer, i. e.
+   * without detection of the format.
    *
-   * @param reader    an opened CSV reader
-   * @return Returns the detected CSV type, if a match was found.
-   *         Returns None otherwise.
+   * @param importer   the importer for the format of the CSV file
+   * @return Returns an Error, if the database could not be created.
    */
-  pub fn get_csv_type(file_path: &str) -> Option<CsvType>
+  pub fn create_db_with(&self, importer: &dyn Importer) -> Result<(), Error>
   {
-    use std::io::{BufRead, BufReader};
-
-    let file = match std::fs::File::open(file_path)
-    {
-      Ok(f) => f,
-      Err(e) => {
-        eprintln!("Failed to open file {file_path}: {e}");
-        return None;
-      }
-    };
-    let reader = BufReader::new(file);
-    let line_one = match reader.lines().next()
-    {
-      None => return None,
-      Some(Ok(line)) => line,
-      Some(Err(_)) => return None
-    };
+    let input = Input::open(&self.config.csv_input_file)?;
+    let (_, report) = self.import(importer, input)?;
+    report.output(&self.config)
+  }
 
-    if line_one.contains("dateRep,day,month,year,cases,deaths")
+  /**
+   * Creates the SQLite database and imports the data with the given importer.
+   *
+   * @param importer   the importer for the format of the input
+   * @param input      the opened input
+   * @return Returns the format of the input and the import report in case of
+   *         success. Returns an Error, if the database could not be created.
+   */
+  fn import(&self, importer: &dyn Importer, input: Input) -> Result<(CsvType, ImportReport), Error>
+  {
+    match crate::checks::sqlite_check()
     {
-      return Some(CsvType::Ecdc);
+      crate::checks::Status::Error(msg) => return Err(Error::UnsupportedSqlite(msg)),
+      crate::checks::Status::Warn(msg) if !self.config.quiet => println!("Warning: {msg}"),
+      _ => ()
     }
-    if line_one.contains("iso_code,continent,location,date,")
-      && line_one.contains(",new_cases,") && line_one.contains(",new_deaths,")
-    {
-      return Some(CsvType::Owid);
+    let db = Database::create(&self.config.db_path)?;
+    let csv_type = importer.csv_type();
+    let mut report = ImportReport::new();
+    report.format = csv_type.name().to_string();
+    let name = input.name().to_string();
+    let digest = input.digest();
+    importer.import(input, &db, &mut report)?;
+    report.inputs.insert(0, InputFile { name, sha256: digest.finish()? });
+    // Remember where the data came from.
+    db.set_meta("source_format", csv_type.name())?;
+    db.insert_provenance(&Db::provenance(&csv_type, &report))?;
+    Ok((csv_type, report))
+  }
+
+  /**
+   * Gathers the information about an import that is recorded in the database.
+   *
+   * @param csv_type   format of the input
+   * @param report     report of the import, including the files that were read
+   * @return Returns the information about the import.
+   */
+  fn provenance(csv_type: &CsvType, report: &ImportReport) -> Provenance
+  {
+    let total = report.total();
+    // Only the name of the file is kept, because the directory is usually
+    // meaningless on other machines.
+    let files: Vec<String> = report
+      .inputs
+      .iter()
+      .map(|input| Path::new(&input.name)
+        .file_name()
+        .map_or_else(|| input.name.clone(), |name| name.to_string_lossy().into_owned()))
+      .collect();
+    let hashes: Vec<&str> = report.inputs.iter().map(|input| input.sha256.as_str()).collect();
+    Provenance {
+      format: csv_type.name().to_string(),
+      attribution: csv_type.attribution().to_string(),
+      file: files.join(", "),
+      sha256: Some(hashes.join(", ")),
+      imported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+      version: env!("CARGO_PKG_VERSION").to_string(),
+      rows_read: total.rows_read as i64,
+      rows_kept: total.rows_kept as i64,
+      rows_skipped: total.skipped() as i64
     }
-    if line_one.contains("country,date,total_cases,new_cases,")
-      && line_one.contains(",new_deaths,") && line_one.contains(",code,continent,")
+  }
+
+  /**
+   * Updates an existing SQLite database with the data from a newer CSV file.
+   * Only countries with new or revised numbers are rewritten.
+   *
+   * @return Returns an Error, if the database could not be updated.
+   */
+  pub fn update_db(&self) -> Result<(), Error>
+  {
+    DbUpdate::new(&self.config)?.update_db()?;
+    Ok(())
+  }
+
+  /**
+   * Imports a CSV file into a new database in the temporary directory. The
+   * caller is responsible for deleting the database file afterwards.
+   *
+   * @param csv_input_file   path of the CSV file to import
+   * @param mapping          path of a mapping file describing the CSV file,
+   *                         or None to detect the format
+   * @param gaps             handling of missing days
+   * @param name             distinct name for the database file, e. g. "update"
+   * @return Returns the path of the created database, the format of the CSV
+   *         file and the import report in case of success.
+   *         Returns an Error, if the import failed. No file is left behind in
+   *         that case.
+   */
+  pub(crate) fn import_into_temporary_db(csv_input_file: &str, mapping: Option<&str>, gaps: GapHandling, name: &str) -> Result<(PathBuf, CsvType, ImportReport), Error>
+  {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Several imports may happen within the same process, e. g. during a merge.
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let number = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp_path = std::env::temp_dir().join(format!("corona-{name}-{}-{number}.db", std::process::id()));
+    if temp_path.exists()
     {
-      return Some(CsvType::OwidEtlCompact);
+      std::fs::remove_file(&temp_path).map_err(|e| Error::io(&temp_path, e))?;
     }
-    if line_one.contains("Date_reported,Country_code,Country,WHO_region")
+    let temp_config = DbConfiguration {
+      csv_input_file: csv_input_file.to_string(),
+      db_path: temp_path.to_string_lossy().into_owned(),
+      quiet: true,
+      mapping: mapping.map(String::from),
+      report: None,
+      gaps
+    };
+    match Db::new(&temp_config).and_then(|db| db.create_db_detected())
     {
-      return Some(CsvType::Who);
+      Ok((csv_type, report)) => Ok((temp_path, csv_type, report)),
+      Err(e) =>
+      {
+        let _ = std::fs::remove_file(&temp_path);
+        Err(e)
+      }
     }
+  }
 
-    // Unknown CSV type.
-    None
+  /**
+   * Gets the CSV type of the file by checking the CSV headers with the
+   * built-in importers. Compressed files are decompressed for that.
+   *
+   * @param file_path   path of the CSV file
+   * @return Returns the detected CSV type, if a match was found.
+   *         Returns Error::UnknownFormat, if the format is not known.
+   */
+  pub fn get_csv_type(file_path: &str) -> Result<CsvType, Error>
+  {
+    let input = Input::open(file_path)?;
+    ImporterRegistry::new()
+      .detect(&input)
+      .map(|importer| importer.csv_type())
   }
 }
 
@@ -177,6 +367,64 @@ mod tests
 {
   use super::*;
 
+  /**
+   * Writes a WHO CSV file with the given lines after the header. Tests of the
+   * submodules use it, too.
+   *
+   * @param path    path of the file to write
+   * @param lines   data rows, each one terminated by a line break
+   */
+  pub(crate) fn write_who_csv(path: &Path, lines: &str)
+  {
+    let content = String::from("Date_reported,Country_code,Country,WHO_region,New_cases,Cumulative_cases,New_deaths,Cumulative_deaths\n")
+      + lines;
+    assert!(std::fs::write(path, content).is_ok());
+  }
+
+  /**
+   * Creates a ZIP archive with a single file. Tests of the submodules use it,
+   * too.
+   *
+   * @param method   compression method: 0 for stored, 8 for deflate
+   * @param data     content of the file
+   */
+  pub(crate) fn zip_archive(method: u16, data: &[u8]) -> Vec<u8>
+  {
+    use flate2::write::DeflateEncoder;
+    use std::io::Write;
+
+    let compressed = if method == 8
+    {
+      let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+      assert!(encoder.write_all(data).is_ok());
+      encoder.finish().unwrap()
+    }
+    else
+    {
+      data.to_vec()
+    };
+    let file_name = b"data.csv";
+    let mut archive = Vec::new();
+    // A directory entry comes first, it has to be skipped.
+    for (name, method, content) in [(&b"dir/"[..], 0, &[][..]), (&file_name[..], method, &compressed[..])]
+    {
+      archive.extend_from_slice(&[b'P', b'K', 0x03, 0x04]);
+      archive.extend_from_slice(&20u16.to_le_bytes()); // version needed
+      archive.extend_from_slice(&0u16.to_le_bytes()); // flags
+      archive.extend_from_slice(&method.to_le_bytes());
+      archive.extend_from_slice(&[0; 8]); // time, date, CRC-32 (not checked)
+      archive.extend_from_slice(&(content.len() as u32).to_le_bytes());
+      archive.extend_from_slice(&(data.len() as u32).to_le_bytes());
+      archive.extend_from_slice(&(name.len() as u16).to_le_bytes());
+      archive.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+      archive.extend_from_slice(name);
+      archive.extend_from_slice(content);
+    }
+    // start of the central directory
+    archive.extend_from_slice(&[b'P', b'K', 0x01, 0x02]);
+    archive
+  }
+
   /**
    * Gets path to the ECDC corona_daily.csv file in data directory.
    *
@@ -199,7 +447,7 @@ mod tests
   #[test]
   fn get_csv_type_ecdc()
   {
-    let detected = Db::get_csv_type(&get_ecdc_csv_path());
+    let detected = Db::get_csv_type(&get_ecdc_csv_path()).ok();
     assert_eq!(detected, Some(CsvType::Ecdc));
   }
 
@@ -211,7 +459,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"dateRep,day,month,year,cases,deaths,countriesAndTerritories,geoId,countryterritoryCode,popData2019,continentExp,Cumulative_number_for_14_days_of_COVID-19_cases_per_100000,Cumulative_number_for_7_days_of_COVID-19_cases_per_100000\n
                                    2021-11-23,23,11,2021,104,-60,Afghanistan,AF,AFG,38041757,Asia,1.6245306440499054,0.9621006726897499").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Ecdc));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -224,7 +472,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"dateRep,day,month,year,cases,deaths,countriesAndTerritories,geoId,countryterritoryCode,popData2019,continentExp,Cumulative_number_for_14_days_of_COVID-19_cases_per_100000\n
                                    14/12/2020,14,12,2020,746,6,Afghanistan,AF,AFG,38041757,Asia,9.01377925").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Ecdc));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -237,7 +485,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"\xEF\xBB\xBFdateRep,day,month,year,cases,deaths,countriesAndTerritories,geoId,countryterritoryCode,popData2019,continentExp,Cumulative_number_for_14_days_of_COVID-19_cases_per_100000,Cumulative_number_for_7_days_of_COVID-19_cases_per_100000\n
                                    2021-11-23,23,11,2021,104,-60,Afghanistan,AF,AFG,38041757,Asia,1.6245306440499054,0.9621006726897499").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Ecdc));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -250,7 +498,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"\xEF\xBB\xBFdateRep,day,month,year,cases,deaths,countriesAndTerritories,geoId,countryterritoryCode,popData2019,continentExp,Cumulative_number_for_14_days_of_COVID-19_cases_per_100000\n
                                    14/12/2020,14,12,2020,746,6,Afghanistan,AF,AFG,38041757,Asia,9.01377925").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Ecdc));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -263,7 +511,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"iso_code,continent,location,date,total_cases,new_cases,new_cases_smoothed,total_deaths,new_deaths,new_deaths_smoothed,total_cases_per_million,new_cases_per_million,new_cases_smoothed_per_million,total_deaths_per_million,new_deaths_per_million,new_deaths_smoothed_per_million,reproduction_rate,icu_patients,icu_patients_per_million,hosp_patients,hosp_patients_per_million,weekly_icu_admissions,weekly_icu_admissions_per_million,weekly_hosp_admissions,weekly_hosp_admissions_per_million,total_tests,new_tests,total_tests_per_thousand,new_tests_per_thousand,new_tests_smoothed,new_tests_smoothed_per_thousand,positive_rate,tests_per_case,tests_units,total_vaccinations,people_vaccinated,people_fully_vaccinated,total_boosters,new_vaccinations,new_vaccinations_smoothed,total_vaccinations_per_hundred,people_vaccinated_per_hundred,people_fully_vaccinated_per_hundred,total_boosters_per_hundred,new_vaccinations_smoothed_per_million,new_people_vaccinated_smoothed,new_people_vaccinated_smoothed_per_hundred,stringency_index,population_density,median_age,aged_65_older,aged_70_older,gdp_per_capita,extreme_poverty,cardiovasc_death_rate,diabetes_prevalence,female_smokers,male_smokers,handwashing_facilities,hospital_beds_per_thousand,life_expectancy,human_development_index,population,excess_mortality_cumulative_absolute,excess_mortality_cumulative,excess_mortality,excess_mortality_cumulative_per_million
                                    AFG,Asia,Afghanistan,2020-01-05,,0.0,,,0.0,,,0.0,,,0.0,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,0.0,54.422,18.6,2.581,1.337,1803.987,,597.029,9.59,,,37.746,0.5,64.83,0.511,41128772.0,,,,").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Owid));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -276,7 +524,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"country,date,total_cases,new_cases,new_cases_smoothed,total_cases_per_million,new_cases_per_million,new_cases_smoothed_per_million,total_deaths,new_deaths,new_deaths_smoothed,total_deaths_per_million,new_deaths_per_million,new_deaths_smoothed_per_million,excess_mortality,excess_mortality_cumulative,excess_mortality_cumulative_absolute,excess_mortality_cumulative_per_million,hosp_patients,hosp_patients_per_million,weekly_hosp_admissions,weekly_hosp_admissions_per_million,icu_patients,icu_patients_per_million,weekly_icu_admissions,weekly_icu_admissions_per_million,stringency_index,reproduction_rate,total_tests,new_tests,total_tests_per_thousand,new_tests_per_thousand,new_tests_smoothed,new_tests_smoothed_per_thousand,positive_rate,tests_per_case,total_vaccinations,people_vaccinated,people_fully_vaccinated,total_boosters,new_vaccinations,new_vaccinations_smoothed,total_vaccinations_per_hundred,people_vaccinated_per_hundred,people_fully_vaccinated_per_hundred,total_boosters_per_hundred,new_vaccinations_smoothed_per_million,new_people_vaccinated_smoothed,new_people_vaccinated_smoothed_per_hundred,code,continent,population,population_density,median_age,life_expectancy,gdp_per_capita,extreme_poverty,diabetes_prevalence,handwashing_facilities,hospital_beds_per_thousand,human_development_index
                                    Afghanistan,2020-01-01,,,,,,,,,,,,,,,,,,,,,,,,,0.0,,,,,,,,,,,,,,,,,,,,,,,AFG,Asia,40578846,62.215546,16.752,,1516.2733,,10.9,48.214695,0.39,0.462").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::OwidEtlCompact));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -289,7 +537,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"Date_reported,Country_code,Country,WHO_region,New_cases,Cumulative_cases,New_deaths,Cumulative_deaths
                                    2020-01-03,AF,Afghanistan,EMRO,0,0,0,0").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Who));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -302,7 +550,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"\xEF\xBB\xBFDate_reported,Country_code,Country,WHO_region,New_cases,Cumulative_cases,New_deaths,Cumulative_deaths
                                    2020-01-03,AF,Afghanistan,EMRO,0,0,0,0").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, Some(CsvType::Who));
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -315,7 +563,7 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"C,S,V
                                    2020-01-03,123,45").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, None);
     assert!(std::fs::remove_file(path).is_ok());
   }
@@ -328,8 +576,136 @@ mod tests
     let path = path.to_str().unwrap();
     assert!(std::fs::write(path, b"\xEF\xBB\xBFC,S,V
                                    2020-01-03,123,45").is_ok());
-    let detected = Db::get_csv_type(&path);
+    let detected = Db::get_csv_type(&path).ok();
     assert_eq!(detected, None);
     assert!(std::fs::remove_file(path).is_ok());
   }
+
+  #[test]
+  fn get_csv_type_unknown()
+  {
+    let path = std::env::temp_dir().join("get_csv_type_unknown.csv");
+    assert!(std::fs::write(&path, b"foo,bar,baz\n1,2,3\n").is_ok());
+    let detected = Db::get_csv_type(path.to_str().unwrap());
+    assert!(matches!(detected, Err(Error::UnknownFormat(_))));
+    assert!(std::fs::remove_file(path).is_ok());
+
+    let detected = Db::get_csv_type("/this/file/does/not/exist.csv");
+    assert!(matches!(detected, Err(Error::Io { .. })));
+  }
+
+  #[test]
+  fn create_db_records_source_format()
+  {
+    let csv_path = std::env::temp_dir().join("create_db_source_format.csv");
+    let db_path = std::env::temp_dir().join("create_db_source_format.db");
+    write_who_csv(&csv_path, "2020-01-03,AF,Afghanistan,EMRO,0,0,0,0\n\
+                              2020-01-04,AF,Afghanistan,EMRO,2,2,1,1\n");
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let db = Db::new(&config).unwrap();
+    assert!(db.create_db().is_ok());
+    // scope for database
+    {
+      let database = Database::new(&config.db_path).unwrap();
+      assert_eq!(database.meta("source_format").unwrap(), Some(String::from("WHO")));
+      assert!(database.meta("no_such_entry").unwrap().is_none());
+      // Every import is recorded.
+      let provenance = database.provenance().unwrap();
+      assert_eq!(provenance.len(), 1);
+      assert_eq!(provenance[0].format, "WHO");
+      assert_eq!(provenance[0].attribution, "World Health Organization (WHO)");
+      assert_eq!(provenance[0].file, "create_db_source_format.csv");
+      // SHA-256 hash of the CSV data above
+      assert_eq!(provenance[0].sha256.as_deref(), Some("9a7d65b844b2fb471d0f1ca62a0d7ba2a5ff482ffba8da6c3f1d03291d539186"));
+      assert_eq!(provenance[0].version, env!("CARGO_PKG_VERSION"));
+      assert_eq!(provenance[0].rows_read, 2);
+      assert_eq!(provenance[0].rows_kept, 2);
+      assert_eq!(provenance[0].rows_skipped, 0);
+    }
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn create_db_from_gzip_file()
+  {
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    let csv_path = std::env::temp_dir().join("create_db_gzip.csv.gz");
+    let db_path = std::env::temp_dir().join("create_db_gzip.db");
+    let _ = std::fs::remove_file(&db_path);
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    assert!(encoder.write_all(b"Date_reported,Country_code,Country,WHO_region,New_cases,Cumulative_cases,New_deaths,Cumulative_deaths
+2020-01-03,AF,Afghanistan,EMRO,0,0,0,0
+2020-01-04,AF,Afghanistan,EMRO,2,2,1,1
+").is_ok());
+    assert!(std::fs::write(&csv_path, encoder.finish().unwrap()).is_ok());
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert_eq!(Db::get_csv_type(&config.csv_input_file).ok(), Some(CsvType::Who));
+    let db = Db::new(&config).unwrap();
+    assert!(db.create_db().is_ok());
+    // scope for database
+    {
+      let database = Database::new(&config.db_path).unwrap();
+      assert_eq!(database.meta("source_format").unwrap(), Some(String::from("WHO")));
+      assert_eq!(database.countries().unwrap().len(), 1);
+    }
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn create_db_from_zip_file()
+  {
+    use sha2::{Digest, Sha256};
+
+    let zip_path = std::env::temp_dir().join("create_db_zip.zip");
+    let db_path = std::env::temp_dir().join("create_db_zip.db");
+    let _ = std::fs::remove_file(&db_path);
+    let archive = zip_archive(0, b"Date_reported,Country_code,Country,WHO_region,New_cases,Cumulative_cases,New_deaths,Cumulative_deaths
+2020-01-03,AF,Afghanistan,EMRO,0,0,0,0
+2020-01-04,AF,Afghanistan,EMRO,2,2,1,1
+");
+    assert!(std::fs::write(&zip_path, &archive).is_ok());
+    let config = DbConfiguration {
+      csv_input_file: zip_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let db = Db::new(&config).unwrap();
+    assert!(db.create_db().is_ok());
+    // scope for database
+    {
+      let database = Database::new(&config.db_path).unwrap();
+      assert_eq!(database.countries().unwrap().len(), 1);
+      // The hash covers the whole archive, not only the part that was read.
+      let expected = Sha256::digest(&archive)[..].iter().map(|x| format!("{x:02x}")).collect::<String>();
+      let provenance = database.provenance().unwrap();
+      assert_eq!(provenance.len(), 1);
+      assert_eq!(provenance[0].sha256.as_deref(), Some(expected.as_str()));
+    }
+    // clean up
+    assert!(std::fs::remove_file(zip_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
 }
@@ -16,222 +16,89 @@
  -------------------------------------------------------------------------------
 */
 
-use crate::configuration::DbConfiguration;
 use crate::data::{Country, Numbers};
 use crate::database::Database;
-use crate::db::save;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input, SkipReason};
+use crate::error::Error;
 use crate::world::World;
 use csv::Reader;
 
-pub struct DbOwid
-{
-  config: DbConfiguration
-}
+/// importer for Our World In Data's CSV format
+pub struct DbOwid;
 
-impl DbOwid
+impl Importer for DbOwid
 {
-  /**
-   * Creates a new instance.
-   *
-   * @config   application configuration
-   * @return   Returns a Result containing the Db object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
-   */
-  pub fn new(config: &DbConfiguration) -> Result<DbOwid, String>
-  {
-    if config.db_path.is_empty()
-    {
-      return Err("Path for SQLite database must not be an empty string!".to_string());
-    }
-    if config.csv_input_file.is_empty()
-    {
-      return Err("Path of CSV file must be set to a non-empty string!".to_string());
-    }
-
-    Ok(DbOwid
-    {
-      config: DbConfiguration
-      {
-        csv_input_file: config.csv_input_file.clone(),
-        db_path: config.db_path.clone()
-      }
-    })
-  }
-
-  /**
-   * Creates the SQLite database from a CSV file.
-   *
-   * @return Returns whether the operation was successful.
-   */
-  pub fn create_db(&self) -> bool
+  fn csv_type(&self) -> CsvType
   {
-    match crate::checks::sqlite_check()
-    {
-      crate::checks::Status::Error(msg) =>
-      {
-        eprintln!("{msg}");
-        return false;
-      },
-      crate::checks::Status::Warn(msg) => println!("Warning: {msg}"),
-      _ => ()
-    }
-    let db = match Database::create(&self.config.db_path)
-    {
-      Err(e) =>
-      {
-        eprintln!("Error while creating database: {e}");
-        return false;
-      }
-      Ok(base) => base
-    };
-    if !db.calculate_total_numbers(&false)
-    {
-      eprintln!("Error: Could not add columns for accumulated numbers to database table!");
-      return false;
-    }
-    self.read_csv(&db)
+    CsvType::Owid
   }
 
-  /**
-   * Fills the SQLite database with values from the CSV file.
-   *
-   * @param db    an open SQLite database with existing tables
-   * @param
-   */
-  fn read_csv(&self, db: &Database) -> bool
+  fn detect(&self, first_line: &str) -> bool
   {
-    let mut reader = match Reader::from_path(&self.config.csv_input_file)
-    {
-      Ok(rdr) => rdr,
-      Err(e) => {
-        eprintln!("Error: Could not open CSV file {}: {}",
-                  self.config.csv_input_file, e);
-        return false;
-      }
-    };
-    // Check headers.
-    if !self.check_headers(&mut reader)
-    {
-      return false;
-    }
-    // parse CSV values
-    DbOwid::parse_csv_into_db(db, &mut reader)
+    Columns::from_line(first_line).contains_all(&["iso_code", "continent", "location", "date", "new_cases", "new_deaths"])
   }
 
-  /**
-   * Checks whether the CSV headers match the expected headers.
-   *
-   * @param reader    an opened CSV reader
-   * @return Returns true, if the headers are correct. Returns false otherwise.
-   */
-  fn check_headers(&self, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
   {
-    let headers = match reader.headers()
-    {
-      Ok(head) => head,
-      Err(e) =>
-      {
-        eprintln!(
-          "Error: Could not read header of CSV file {}: {}",
-          self.config.csv_input_file, e
-        );
-        return false;
-      }
-    };
-    let expected_headers = vec![
-      "iso_code",
-      "continent",
-      "location",
-      "date",
-      "total_cases",
-      "new_cases",
-      "new_cases_smoothed",
-      "total_deaths",
-      "new_deaths",
-    ];
-    let mut header_part = headers.iter().collect::<Vec<_>>();
-    header_part.truncate(expected_headers.len());
-    if header_part != expected_headers
-    {
-      eprintln!("Error: CSV headers do not match the expected headers. \
-                 Found the following headers: {headers:?}");
-      return false;
-    }
-    // Headers match. :)
-    true
+    let mut reader = Reader::from_reader(input);
+    DbOwid::parse_csv_into_db(db, &mut reader, report)
   }
+}
 
+impl DbOwid
+{
   /**
    * Parses the CSV data and writes it into the database.
    *
    * @param db        an open SQLite database with existing tables
    * @param reader    an opened CSV reader
-   * @return Returns true, if all data was parsed and written to the database
-   *         successfully. Returns false otherwise.
+   * @param report    report that collects statistics about the rows
+   * @return Returns an Error, if the data could not be parsed or written to
+   *         the database.
    */
-  fn parse_csv_into_db(db: &Database, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn parse_csv_into_db<R: std::io::Read>(db: &Database, reader: &mut csv::Reader<R>, report: &mut ImportReport) -> Result<(), Error>
   {
+    let [idx_iso3, idx_continent, idx_name, idx_date, idx_cases, idx_deaths] = Columns::new(reader.headers()?)
+      .require(["iso_code", "continent", "location", "date", "new_cases", "new_deaths"])?;
     let mut last_iso3_id = String::new();
     let mut country_id: i64 = -1;
     let mut population: i32 = -1;
     let mut record = csv::StringRecord::new();
     let world = World::new();
     let mut parsed_data = Vec::<Numbers>::new();
-    loop
+    while reader.read_record(&mut record)?
     {
-      match reader.read_record(&mut record)
-      {
-        Ok(success) =>
-        {
-          if !success
-          {
-            // No more records to read.
-            break;
-          }
-        },
-        Err(e) => {
-          // Failed to read.
-          eprintln!("Failed to read CSV record! {e}");
-          return false;
-        }
-      }
-      let r_len = record.len();
-      if r_len < 9
-      {
-        eprintln!("Error: A line of CSV data does not have enough data elements, \
-                   but only {} elements!\nThe line position is '{}'. \
-                   It will be skipped.",
-                   r_len, record.position().unwrap().line());
-        return false;
-      }
-      let current_iso3_id = record.get(0).unwrap();
+      let current_iso3_id = &record[idx_iso3];
       // Skip "OWID_..." rows.
       if current_iso3_id.starts_with("OWID_")
       {
+        report.skipped(&record[idx_name], SkipReason::Aggregate);
         continue;
       }
       if current_iso3_id != last_iso3_id
       {
         // Insert data of previous country.
         if country_id != -1
-          && !save::numbers_into_db(db, &country_id, &population, &mut parsed_data)
         {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
+          save::numbers_into_db(db, &country_id, &population, &mut parsed_data)?;
         }
         parsed_data.clear();
         // new country
-        let name = record.get(2).unwrap();
+        let name = &record[idx_name];
         let new_country = Country {
           country_id: -1,
           name: name.to_string(),
           population: -1,
           geo_id: String::new(),
-          country_code: record.get(0).unwrap().to_string(),
-          continent: record.get(1).unwrap().to_string()
+          country_code: current_iso3_id.to_string(),
+          continent: record[idx_continent].to_string()
         };
-        let world_data = world.find_by_country_code(current_iso3_id).unwrap_or(&new_country);
+        let known = world.find_by_country_code(current_iso3_id);
+        if known.is_none()
+        {
+          report.not_found(name);
+        }
+        let world_data = known.unwrap_or(&new_country);
         population = world_data.population;
         // Get country id or insert country.
         country_id = db.get_country_id_or_insert(
@@ -240,46 +107,36 @@ impl DbOwid
           &i64::from(world_data.population),
           current_iso3_id,
           &new_country.continent
-        );
-        if country_id == -1
-        {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
-        }
+        )?;
         last_iso3_id = current_iso3_id.to_string();
       }
       // Add current record.
-      let date = record.get(3).unwrap();
-      let cases: f64 = match record.get(5).unwrap().is_empty()
+      let date = &record[idx_date];
+      let cases: f64 = match record[idx_cases].is_empty()
       {
-        false => record.get(5).unwrap().parse().unwrap_or(f64::MIN),
+        false => record[idx_cases].parse().unwrap_or(f64::MIN),
         true => 0.0
       };
-      let deaths: f64 = match record.get(8).unwrap().is_empty()
+      let deaths: f64 = match record[idx_deaths].is_empty()
       {
-        false => record.get(8).unwrap().parse().unwrap_or(f64::MIN),
+        false => record[idx_deaths].parse().unwrap_or(f64::MIN),
         true => 0.0
       };
       if cases == f64::MIN || deaths == f64::MIN
       {
-        eprintln!(
-          "Error: Got invalid case numbers on line {}.",
-          record.position().unwrap().line()
-        );
-        return false;
+        return Err(Error::csv_parse(&record, "Got invalid case numbers."));
       }
       parsed_data.push(Numbers { date: String::from(date), cases: cases as i32, deaths: deaths as i32});
+      report.kept(&record[idx_name], 1);
     }
     // Execute remaining batch inserts, if any are left.
     if !parsed_data.is_empty()
-      && !crate::db::save::numbers_into_db(db, &country_id, &population, &mut parsed_data)
     {
-      eprintln!("Error: Could not insert country data into database!");
-      return false;
+      crate::db::save::numbers_into_db(db, &country_id, &population, &mut parsed_data)?;
     }
 
     // Done.
-    true
+    Ok(())
   }
 }
 
@@ -287,7 +144,9 @@ impl DbOwid
 mod tests
 {
   use super::*;
-  use crate::data::Country;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::db::Db;
+  use crate::data::{Country, DateRange};
 
   /**
    * Gets path to the temporary corona.csv file suitable for testing.
@@ -390,18 +249,22 @@ CHE,Europe,Switzerland,2020-03-31,16170.0,0.0,1065.714,335.0,0.0,32.286,1850.015
     let db_file_name = std::env::temp_dir().join("test_db_corona_owid.db");
     let config = DbConfiguration {
       db_path: db_file_name.to_str().unwrap().to_string(),
-      csv_input_file: get_csv_path()
+      csv_input_file: get_csv_path(),
+      quiet: false,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
     };
     // scope for db
     {
-      let db = DbOwid::new(&config).unwrap();
-      assert!(db.create_db());
+      let db = Db::new(&config).unwrap();
+      assert!(db.create_db_with(&DbOwid).is_ok());
       // Check that DB file exists.
       assert!(db_file_name.exists());
       // Check some content.
       let db = Database::new(&config.db_path).unwrap();
       // Check a country.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       {
         let de = Country
         {
@@ -422,7 +285,7 @@ CHE,Europe,Switzerland,2020-03-31,16170.0,0.0,1065.714,335.0,0.0,32.286,1850.015
         assert_eq!(de.country_code, found.country_code);
         assert_eq!(de.continent, found.continent);
         // Check some numbers.
-        let numbers = db.numbers_with_incidence(&de.country_id);
+        let numbers = db.numbers_with_incidence(&de.country_id, &DateRange::default()).unwrap();
         // 1|2020-02-09|9|0|0.0216817280597444|0.0108408640298722|20|9
         let found = numbers.iter().find(|&n| n.date == "2020-02-09");
         assert!(found.is_some());
@@ -458,7 +321,7 @@ CHE,Europe,Switzerland,2020-03-31,16170.0,0.0,1065.714,335.0,0.0,32.286,1850.015
       assert_eq!(ch.country_code, found.country_code);
       assert_eq!(ch.continent, found.continent);
       // Check some numbers.
-      let numbers = db.numbers_with_incidence(&ch.country_id);
+      let numbers = db.numbers_with_incidence(&ch.country_id, &DateRange::default()).unwrap();
       // 2|2020-03-29|7460|226|161.471781878622|87.3073489029878|16170|335
       let found = numbers.iter().find(|&n| n.date == "2020-03-29");
       assert!(found.is_some());
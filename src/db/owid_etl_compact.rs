@@ -16,156 +16,69 @@
  -------------------------------------------------------------------------------
 */
 
-use crate::configuration::DbConfiguration;
-use crate::data::{Country, Numbers};
+use crate::configuration::GapHandling;
+use crate::data::{Country, Metric, MetricValue, Numbers};
 use crate::database::Database;
-use crate::db::save;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input, SkipReason};
+use crate::error::Error;
 use crate::world::World;
 use csv::Reader;
 
+/// importer for Our World In Data's compact CSV from the ETL
+#[derive(Default)]
 pub struct DbOwidEtlCompact
 {
-  config: DbConfiguration
+  /// handling of missing days, because the file has gaps in recent data
+  gaps: GapHandling
 }
 
-impl DbOwidEtlCompact
+impl Importer for DbOwidEtlCompact
 {
-  /**
-   * Creates a new instance.
-   *
-   * @config   application configuration
-   * @return   Returns a Result containing the Db object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
-   */
-  pub fn new(config: &DbConfiguration) -> Result<DbOwidEtlCompact, String>
+  fn csv_type(&self) -> CsvType
   {
-    if config.db_path.is_empty()
-    {
-      return Err("Path for SQLite database must not be an empty string!".to_string());
-    }
-    if config.csv_input_file.is_empty()
-    {
-      return Err("Path of CSV file must be set to a non-empty string!".to_string());
-    }
+    CsvType::OwidEtlCompact
+  }
 
-    Ok(DbOwidEtlCompact
-    {
-      config: DbConfiguration
-      {
-        csv_input_file: config.csv_input_file.clone(),
-        db_path: config.db_path.clone()
-      }
-    })
+  fn detect(&self, first_line: &str) -> bool
+  {
+    Columns::from_line(first_line).contains_all(&["country", "date", "new_cases", "new_deaths", "code", "continent"])
   }
 
-  /**
-   * Creates the SQLite database from a CSV file.
-   *
-   * @return Returns whether the operation was successful.
-   */
-  pub fn create_db(&self) -> bool
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
   {
-    match crate::checks::sqlite_check()
-    {
-      crate::checks::Status::Error(msg) =>
-      {
-        eprintln!("{msg}");
-        return false;
-      },
-      crate::checks::Status::Warn(msg) => println!("Warning: {msg}"),
-      _ => ()
-    }
-    let db = match Database::create(&self.config.db_path)
-    {
-      Err(e) =>
-      {
-        eprintln!("Error while creating database: {e}");
-        return false;
-      }
-      Ok(base) => base
-    };
-    if !db.calculate_total_numbers(&false)
-    {
-      eprintln!("Error: Could not add columns for accumulated numbers to database table!");
-      return false;
-    }
-    self.read_csv(&db)
+    let mut reader = Reader::from_reader(input);
+    self.parse_csv_into_db(db, &mut reader, report)
   }
+}
 
+impl DbOwidEtlCompact
+{
   /**
-   * Fills the SQLite database with values from the CSV file.
+   * Creates a new importer.
    *
-   * @param db    an open SQLite database with existing tables
-   * @param
+   * @param gaps   handling of missing days
    */
-  fn read_csv(&self, db: &Database) -> bool
+  pub fn new(gaps: GapHandling) -> DbOwidEtlCompact
   {
-    let mut reader = match Reader::from_path(&self.config.csv_input_file)
-    {
-      Ok(rdr) => rdr,
-      Err(e) => {
-        eprintln!("Error: Could not open CSV file {}: {}",
-                  self.config.csv_input_file, e);
-        return false;
-      }
-    };
-    // Check headers.
-    if !self.check_headers(&mut reader)
-    {
-      return false;
-    }
-    // parse CSV values
-    DbOwidEtlCompact::parse_csv_into_db(db, &mut reader)
+    DbOwidEtlCompact { gaps }
   }
 
   /**
-   * Checks whether the CSV headers match the expected headers.
+   * Makes the dates of a country contiguous as configured, so that the
+   * incidence values can be calculated.
    *
-   * @param reader    an opened CSV reader
-   * @return Returns true, if the headers are correct. Returns false otherwise.
+   * @param numbers   numbers of the country, will be modified in place
+   * @param country   name of the country, for the report
+   * @param report    report where removed or added dates are recorded
    */
-  fn check_headers(&self, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn handle_gaps(&self, numbers: &mut Vec<Numbers>, country: &str, report: &mut ImportReport)
   {
-    let headers = match reader.headers()
-    {
-      Ok(head) => head,
-      Err(e) =>
-      {
-        eprintln!(
-          "Error: Could not read header of CSV file {}: {}",
-          self.config.csv_input_file, e
-        );
-        return false;
-      }
-    };
-    let expected_headers = vec![
-      "country",
-      "date",
-      "total_cases",
-      "new_cases",
-      "new_cases_smoothed",
-      "total_cases_per_million",
-      "new_cases_per_million",
-      "new_cases_smoothed_per_million",
-      "total_deaths",
-      "new_deaths",
-    ];
-    let header_strings = headers.iter().collect::<Vec<_>>();
-    if !header_strings.starts_with(&expected_headers)
-    {
-      eprintln!("Error: CSV headers do not match the expected headers. \
-                 Found the following headers: {headers:?}");
-      return false;
-    }
-    if !header_strings.contains(&"code") || !header_strings.contains(&"continent")
+    match self.gaps
     {
-      eprintln!("Error: CSV headers for country code and continent are missing. \
-                 Found the following headers: {headers:?}");
-      return false;
+      GapHandling::Truncate => report.cut_off(country, crate::data::cutoff_non_contiguous_dates(numbers)),
+      GapHandling::Zero => report.filled(country, crate::data::fill_gaps(numbers, false)),
+      GapHandling::Interpolate => report.filled(country, crate::data::fill_gaps(numbers, true))
     }
-    // Headers match. :)
-    true
   }
 
   /**
@@ -173,65 +86,36 @@ impl DbOwidEtlCompact
    *
    * @param db        an open SQLite database with existing tables
    * @param reader    an opened CSV reader
-   * @return Returns true, if all data was parsed and written to the database
-   *         successfully. Returns false otherwise.
+   * @param report    report that collects statistics about the rows
+   * @return Returns an Error, if the data could not be parsed or written to
+   *         the database.
    */
-  fn parse_csv_into_db(db: &Database, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn parse_csv_into_db<R: std::io::Read>(&self, db: &Database, reader: &mut csv::Reader<R>, report: &mut ImportReport) -> Result<(), Error>
   {
-    /// zero-based index of the column that contains the country's name
-    const IDX_NAME: usize = 0;
-
-    /// zero-based index of the column that contains the date
-    const IDX_DATE: usize = 1;
-
-    /// zero-based index of the column containing the number of new cases
-    const IDX_CASES: usize = 3;
-
-    /// zero-based index of the column containing the number of new deaths
-    const IDX_DEATHS: usize = 9;
-
-    /// zero-based index of the column containing the ISO-3166 ALPHA-3 country code
-    const IDX_ISO3: usize = 49;
-
-    /// zero-based index of the column that contains the continent name
-    const IDX_CONTINENT: usize = 50;
+    // The file has more than 60 columns, and their order has changed before,
+    // so the columns are found by their names.
+    let columns = Columns::new(reader.headers()?);
+    let [idx_name, idx_date, idx_cases, idx_deaths, idx_iso3, idx_continent] = columns
+      .require(["country", "date", "new_cases", "new_deaths", "code", "continent"])?;
+    // Further metrics are optional, only those with a column are imported.
+    let idx_metrics: Vec<(Metric, usize)> = Metric::ALL
+      .into_iter()
+      .filter_map(|metric| columns.position(metric.name()).map(|idx| (metric, idx)))
+      .collect();
 
     let now: String = chrono::Utc::now().format("%Y-%m-%d").to_string();
 
     let mut last_iso3_id = String::new();
+    let mut last_name = String::new();
     let mut country_id: i64 = -1;
     let mut population: i32 = -1;
     let mut record = csv::StringRecord::new();
     let world = World::new();
     let mut parsed_data = Vec::<Numbers>::new();
-    loop
+    let mut parsed_metrics = Vec::<MetricValue>::new();
+    while reader.read_record(&mut record)?
     {
-      match reader.read_record(&mut record)
-      {
-        Ok(success) =>
-        {
-          if !success
-          {
-            // No more records to read.
-            break;
-          }
-        },
-        Err(e) => {
-          // Failed to read.
-          eprintln!("Failed to read CSV record! {e}");
-          return false;
-        }
-      }
-      let r_len = record.len();
-      if r_len < 51
-      {
-        eprintln!("Error: A line of CSV data does not have enough data elements, \
-                   but only {} elements!\nThe line position is '{}'. \
-                   It will be skipped.",
-                   r_len, record.position().unwrap().line());
-        return false;
-      }
-      let current_iso3_id = record.get(IDX_ISO3).unwrap();
+      let current_iso3_id = &record[idx_iso3];
       let current_iso3_id = if current_iso3_id == "OWID_KOS"
       {
         // "XKX" is the temporary code for Kosovo as long as it has no official
@@ -243,37 +127,49 @@ impl DbOwidEtlCompact
         current_iso3_id
       };
       // Skip "OWID_..." and empty rows.
-      if current_iso3_id.starts_with("OWID_") || current_iso3_id.is_empty()
+      if current_iso3_id.starts_with("OWID_")
       {
+        report.skipped(&record[idx_name], SkipReason::Aggregate);
         continue;
       }
-      let date = record.get(IDX_DATE).unwrap();
-      if date > &now
+      if current_iso3_id.is_empty()
       {
+        report.skipped(&record[idx_name], SkipReason::MissingCode);
+        continue;
+      }
+      let date = &record[idx_date];
+      if date > now.as_str()
+      {
+        report.skipped(&record[idx_name], SkipReason::FutureDate);
         continue;
       }
       if current_iso3_id != last_iso3_id
       {
         // Insert data of previous country.
-        crate::data::cutoff_non_contiguous_dates(&mut parsed_data);
+        self.handle_gaps(&mut parsed_data, &last_name, report);
         if country_id != -1
-          && !save::numbers_into_db(db, &country_id, &population, &mut parsed_data)
         {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
+          save::numbers_into_db(db, &country_id, &population, &mut parsed_data)?;
+          save::metrics_into_db(db, &country_id, &parsed_data, &mut parsed_metrics)?;
         }
         parsed_data.clear();
+        parsed_metrics.clear();
         // new country
-        let name = record.get(IDX_NAME).unwrap();
+        let name = &record[idx_name];
         let new_country = Country {
           country_id: -1,
           name: name.to_string(),
           population: -1,
           geo_id: String::new(),
-          country_code: record.get(IDX_ISO3).unwrap().to_string(),
-          continent: record.get(IDX_CONTINENT).unwrap().to_string()
+          country_code: record[idx_iso3].to_string(),
+          continent: record[idx_continent].to_string()
         };
-        let world_data = world.find_by_country_code(current_iso3_id).unwrap_or(&new_country);
+        let known = world.find_by_country_code(current_iso3_id);
+        if known.is_none()
+        {
+          report.not_found(name);
+        }
+        let world_data = known.unwrap_or(&new_country);
         population = world_data.population;
         // Get country id or insert country.
         country_id = db.get_country_id_or_insert(
@@ -282,46 +178,50 @@ impl DbOwidEtlCompact
           &i64::from(world_data.population),
           current_iso3_id,
           &new_country.continent
-        );
-        if country_id == -1
-        {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
-        }
+        )?;
         last_iso3_id = current_iso3_id.to_string();
+        last_name = name.to_string();
       }
       // Add current record.
-      let cases: f64 = match record.get(IDX_CASES).unwrap().is_empty()
+      let cases: f64 = match record[idx_cases].is_empty()
       {
-        false => record.get(IDX_CASES).unwrap().parse().unwrap_or(f64::MIN),
+        false => record[idx_cases].parse().unwrap_or(f64::MIN),
         true => 0.0
       };
-      let deaths: f64 = match record.get(IDX_DEATHS).unwrap().is_empty()
+      let deaths: f64 = match record[idx_deaths].is_empty()
       {
-        false => record.get(IDX_DEATHS).unwrap().parse().unwrap_or(f64::MIN),
+        false => record[idx_deaths].parse().unwrap_or(f64::MIN),
         true => 0.0
       };
       if cases == f64::MIN || deaths == f64::MIN
       {
-        eprintln!(
-          "Error: Got invalid case numbers on line {}.",
-          record.position().unwrap().line()
-        );
-        return false;
+        return Err(Error::csv_parse(&record, "Got invalid case numbers."));
       }
       parsed_data.push(Numbers { date: String::from(date), cases: cases as i32, deaths: deaths as i32});
+      for (metric, idx) in idx_metrics.iter()
+      {
+        if record[*idx].is_empty()
+        {
+          continue;
+        }
+        match record[*idx].parse::<f64>()
+        {
+          Ok(value) if value.is_finite() => parsed_metrics.push(MetricValue { date: String::from(date), metric: *metric, value }),
+          _ => return Err(Error::csv_parse(&record, &format!("Got invalid value for {}.", metric.name())))
+        }
+      }
+      report.kept(&last_name, 1);
     }
     // Execute remaining batch inserts, if any are left.
-    crate::data::cutoff_non_contiguous_dates(&mut parsed_data);
+    self.handle_gaps(&mut parsed_data, &last_name, report);
     if !parsed_data.is_empty()
-      && !crate::db::save::numbers_into_db(db, &country_id, &population, &mut parsed_data)
     {
-      eprintln!("Error: Could not insert country data into database!");
-      return false;
+      crate::db::save::numbers_into_db(db, &country_id, &population, &mut parsed_data)?;
+      crate::db::save::metrics_into_db(db, &country_id, &parsed_data, &mut parsed_metrics)?;
     }
 
     // Done.
-    true
+    Ok(())
   }
 }
 
@@ -329,7 +229,9 @@ impl DbOwidEtlCompact
 mod tests
 {
   use super::*;
-  use crate::data::Country;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::db::Db;
+  use crate::data::{Country, DateRange};
 
   /**
    * Gets path to the temporary corona.csv file suitable for testing.
@@ -436,18 +338,22 @@ Switzerland,2020-03-31,17909,1307,1027.1428,2036.9237,148.65483,116.82459,438,58
     let db_file_name = std::env::temp_dir().join("test_db_corona_owid_etl_compact.db");
     let config = DbConfiguration {
       db_path: db_file_name.to_str().unwrap().to_string(),
-      csv_input_file: get_csv_path()
+      csv_input_file: get_csv_path(),
+      quiet: false,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
     };
     // scope for db
     {
-      let db = DbOwidEtlCompact::new(&config).unwrap();
-      assert!(db.create_db());
+      let db = Db::new(&config).unwrap();
+      assert!(db.create_db_with(&DbOwidEtlCompact::default()).is_ok());
       // Check that DB file exists.
       assert!(db_file_name.exists());
       // Check some content.
       let db = Database::new(&config.db_path).unwrap();
       // Check a country.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       {
         let de = Country
         {
@@ -468,7 +374,7 @@ Switzerland,2020-03-31,17909,1307,1027.1428,2036.9237,148.65483,116.82459,438,58
         assert_eq!(de.country_code, found.country_code);
         assert_eq!(de.continent, found.continent);
         // Check some numbers.
-        let numbers = db.numbers_with_incidence(&de.country_id);
+        let numbers = db.numbers_with_incidence(&de.country_id, &DateRange::default()).unwrap();
         // 1|2020-02-09|9|0|0.0216817280597444|0.0108408640298722|20|9
         let found = numbers.iter().find(|&n| n.date == "2020-02-09");
         assert!(found.is_some());
@@ -504,7 +410,7 @@ Switzerland,2020-03-31,17909,1307,1027.1428,2036.9237,148.65483,116.82459,438,58
       assert_eq!(ch.country_code, found.country_code);
       assert_eq!(ch.continent, found.continent);
       // Check some numbers.
-      let numbers = db.numbers_with_incidence(&ch.country_id);
+      let numbers = db.numbers_with_incidence(&ch.country_id, &DateRange::default()).unwrap();
       // 2|2020-03-29|722|57|161.471781878622|87.3073489029878|16170|335
       let found = numbers.iter().find(|&n| n.date == "2020-03-29");
       assert!(found.is_some());
@@ -518,6 +424,18 @@ Switzerland,2020-03-31,17909,1307,1027.1428,2036.9237,148.65483,116.82459,438,58
       assert!(found.incidence_7d.is_some());
       assert!(found.incidence_7d.unwrap() > 87.307348);
       assert!(found.incidence_7d.unwrap() < 87.307349);
+      // Check further metrics.
+      assert_eq!(db.available_metrics(&ch.country_id).unwrap(),
+                 vec![Metric::HospPatients, Metric::IcuPatients, Metric::WeeklyHospAdmissions, Metric::ExcessMortality]);
+      let icu = db.metric(&ch.country_id, Metric::IcuPatients, &DateRange::default()).unwrap();
+      assert_eq!(icu.len(), 2);
+      assert_eq!(icu[1], MetricValue { date: String::from("2020-03-31"), metric: Metric::IcuPatients, value: 429.0 });
+      let metrics = db.metrics(&ch.country_id, &DateRange::new(Some(String::from("2020-03-29")), Some(String::from("2020-03-29"))).unwrap()).unwrap();
+      assert_eq!(metrics.len(), 2);
+      assert_eq!(metrics[0].metric, Metric::WeeklyHospAdmissions);
+      assert_eq!(metrics[0].value, 1174.0);
+      assert_eq!(metrics[1].metric, Metric::ExcessMortality);
+      assert!((metrics[1].value - 25.713408).abs() < 1e-9);
     }
     // clean up
     assert!(std::fs::remove_file(db_file_name).is_ok());
@@ -585,18 +503,22 @@ Albania,2020-12-31,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,ALB,Europe,282
     let db_file_name = std::env::temp_dir().join("test_db_corona_owid_etl_compact_overhead.db");
     let config = DbConfiguration {
       db_path: db_file_name.to_str().unwrap().to_string(),
-      csv_input_file: get_csv_overhead_lines_path()
+      csv_input_file: get_csv_overhead_lines_path(),
+      quiet: false,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
     };
     // scope for db
     {
-      let db = DbOwidEtlCompact::new(&config).unwrap();
-      assert!(db.create_db());
+      let db = Db::new(&config).unwrap();
+      assert!(db.create_db_with(&DbOwidEtlCompact::default()).is_ok());
       // Check that DB file exists.
       assert!(db_file_name.exists());
       // Check some content.
       let db = Database::new(&config.db_path).unwrap();
       // Check a country.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       {
         let albania = Country
         {
@@ -617,7 +539,7 @@ Albania,2020-12-31,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,ALB,Europe,282
         assert_eq!(albania.country_code, found.country_code);
         assert_eq!(albania.continent, found.continent);
         // Check some numbers.
-        let numbers = db.numbers_with_incidence(&albania.country_id);
+        let numbers = db.numbers_with_incidence(&albania.country_id, &DateRange::default()).unwrap();
         // 1|2020-03-23|6|0|2.65508954464166|1.32754477232083|76|2
         let found = numbers.iter().find(|&n| n.date == "2020-03-23");
         assert!(found.is_some());
@@ -657,4 +579,90 @@ Albania,2020-12-31,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,ALB,Europe,282
     assert!(std::fs::remove_file(db_file_name).is_ok());
     assert!(std::fs::remove_file(config.csv_input_file).is_ok());
   }
+
+  #[test]
+  fn import_report()
+  {
+    let csv_path = std::env::temp_dir().join("test_owid_etl_compact_report.csv");
+    let db_path = std::env::temp_dir().join("test_owid_etl_compact_report.db");
+    let json_path = std::env::temp_dir().join("test_owid_etl_compact_report.json");
+    let _ = std::fs::remove_file(&db_path);
+    let csv = "country,date,new_cases,new_deaths,code,continent
+Atlantis,2021-01-01,1,0,ATL,Europe
+Germany,2021-01-01,5,0,DEU,Europe
+Germany,2021-01-02,7,1,DEU,Europe
+Germany,2021-01-03,6,0,DEU,Europe
+Germany,2021-01-10,2,0,DEU,Europe
+Germany,2999-01-01,1,0,DEU,Europe
+Nowhere,2021-01-01,1,0,,
+World,2021-01-01,13,1,OWID_WRL,
+";
+    assert!(std::fs::write(&csv_path, csv).is_ok());
+    let config = DbConfiguration {
+      db_path: db_path.to_str().unwrap().to_string(),
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: Some(json_path.to_str().unwrap().to_string()),
+      gaps: GapHandling::Truncate
+    };
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+
+    let json = std::fs::read_to_string(&json_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["format"], "OWID ETL compact");
+    let germany = &json["countries"]["Germany"];
+    assert_eq!(germany["rows_read"], 5);
+    assert_eq!(germany["rows_kept"], 3);
+    assert_eq!(germany["rows_skipped"]["future_date"], 1);
+    assert_eq!(germany["dates_cut_off"], serde_json::json!(["2021-01-10"]));
+    assert_eq!(json["countries"]["World"]["rows_skipped"]["aggregate"], 1);
+    assert_eq!(json["countries"]["Nowhere"]["rows_skipped"]["missing_code"], 1);
+    assert_eq!(json["total"]["rows_read"], 8);
+    assert_eq!(json["total"]["rows_kept"], 4);
+    assert_eq!(json["unknown_countries"], serde_json::json!(["Atlantis"]));
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+    assert!(std::fs::remove_file(json_path).is_ok());
+  }
+
+  #[test]
+  fn gap_interpolation()
+  {
+    let csv_path = std::env::temp_dir().join("test_owid_etl_compact_gaps.csv");
+    let db_path = std::env::temp_dir().join("test_owid_etl_compact_gaps.db");
+    let _ = std::fs::remove_file(&db_path);
+    let csv = "country,date,new_cases,new_deaths,code,continent
+Germany,2021-01-01,5,0,DEU,Europe
+Germany,2021-01-02,7,1,DEU,Europe
+Germany,2021-01-05,9,3,DEU,Europe
+";
+    assert!(std::fs::write(&csv_path, csv).is_ok());
+    let config = DbConfiguration {
+      db_path: db_path.to_str().unwrap().to_string(),
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Interpolate
+    };
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      let germany = db.countries().unwrap().into_iter().find(|c| c.geo_id == "DE").unwrap();
+      let mut numbers = db.numbers(&germany.country_id, &DateRange::default()).unwrap();
+      numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+      let cases: Vec<i32> = numbers.iter().map(|n| n.cases).collect();
+      let deaths: Vec<i32> = numbers.iter().map(|n| n.deaths).collect();
+      assert_eq!(cases, vec![5, 7, 3, 3, 3]);
+      assert_eq!(deaths, vec![0, 1, 1, 1, 1]);
+    }
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
 }
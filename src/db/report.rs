@@ -0,0 +1,407 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::configuration::DbConfiguration;
+use crate::error::Error;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// reasons for skipping a row of the input
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason
+{
+  /// row contains numbers of an aggregate like a continent or the whole world,
+  /// e. g. "OWID_WRL"
+  Aggregate,
+  /// row is dated after the current day
+  FutureDate,
+  /// row has no country code
+  MissingCode
+}
+
+impl SkipReason
+{
+  /**
+   * Gets a short, human-readable description of the reason.
+   *
+   * @return Returns the description, e. g. "future date".
+   */
+  pub fn description(&self) -> &'static str
+  {
+    match self
+    {
+      SkipReason::Aggregate => "aggregate",
+      SkipReason::FutureDate => "future date",
+      SkipReason::MissingCode => "missing country code"
+    }
+  }
+}
+
+/// statistics about the rows of one country, or of all countries
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct RowStatistics
+{
+  /// number of rows read from the input
+  pub rows_read: u64,
+  /// number of rows that were written to the database
+  pub rows_kept: u64,
+  /// number of skipped rows per reason
+  pub rows_skipped: BTreeMap<SkipReason, u64>,
+  /// dates that were removed, because they were not contiguous with the
+  /// previous dates
+  pub dates_cut_off: Vec<String>,
+  /// missing dates that were added to get contiguous dates
+  pub dates_filled: Vec<String>
+}
+
+impl RowStatistics
+{
+  /**
+   * Gets the total number of skipped rows.
+   *
+   * @return Returns the number of skipped rows, regardless of the reason.
+   */
+  pub fn skipped(&self) -> u64
+  {
+    self.rows_skipped.values().sum()
+  }
+
+  /**
+   * Gets the skipped rows as text, e. g. "3 skipped (future date: 3)".
+   *
+   * @return Returns the text.
+   */
+  fn skipped_text(&self) -> String
+  {
+    let reasons: Vec<String> = self
+      .rows_skipped
+      .iter()
+      .map(|(reason, count)| format!("{}: {count}", reason.description()))
+      .collect();
+    format!("{} skipped ({})", self.skipped(), reasons.join(", "))
+  }
+}
+
+/// file that has been read during an import
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InputFile
+{
+  /// path of the file, or "-" for the standard input
+  pub name: String,
+  /// SHA-256 hash of the data that has been read from the file as hexadecimal
+  /// string
+  pub sha256: String
+}
+
+/// report about what happened to the data of an input file during the import
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ImportReport
+{
+  /// name of the source format
+  pub format: String,
+  /// files that have been read, starting with the file given by the user;
+  /// importers only have to add further files they open themselves
+  pub inputs: Vec<InputFile>,
+  /// statistics per country, indexed by the name or code used in the input
+  pub countries: BTreeMap<String, RowStatistics>,
+  /// countries of the input that are not in the list of known countries, so
+  /// their population and continent may be unknown
+  pub unknown_countries: BTreeSet<String>
+}
+
+impl ImportReport
+{
+  /**
+   * Creates an empty report.
+   */
+  pub fn new() -> ImportReport
+  {
+    ImportReport::default()
+  }
+
+  /**
+   * Records rows of a country that are written to the database.
+   *
+   * @param country   name or code of the country as used in the input
+   * @param rows      number of rows
+   */
+  pub fn kept(&mut self, country: &str, rows: u64)
+  {
+    let stats = self.countries.entry(country.to_string()).or_default();
+    stats.rows_read += rows;
+    stats.rows_kept += rows;
+  }
+
+  /**
+   * Records a row that is not written to the database.
+   *
+   * @param country   name or code of the country as used in the input
+   * @param reason    the reason for skipping the row
+   */
+  pub fn skipped(&mut self, country: &str, reason: SkipReason)
+  {
+    let stats = self.countries.entry(country.to_string()).or_default();
+    stats.rows_read += 1;
+    *stats.rows_skipped.entry(reason).or_insert(0) += 1;
+  }
+
+  /**
+   * Records dates that were removed after they had been kept.
+   *
+   * @param country   name or code of the country as used in the input
+   * @param dates     the removed dates
+   */
+  pub fn cut_off(&mut self, country: &str, dates: Vec<String>)
+  {
+    if dates.is_empty()
+    {
+      return;
+    }
+    let stats = self.countries.entry(country.to_string()).or_default();
+    stats.rows_kept = stats.rows_kept.saturating_sub(dates.len() as u64);
+    stats.dates_cut_off.extend(dates);
+    stats.dates_cut_off.sort_unstable();
+  }
+
+  /**
+   * Records missing dates that were added.
+   *
+   * @param country   name or code of the country as used in the input
+   * @param dates     the added dates
+   */
+  pub fn filled(&mut self, country: &str, dates: Vec<String>)
+  {
+    if dates.is_empty()
+    {
+      return;
+    }
+    let stats = self.countries.entry(country.to_string()).or_default();
+    stats.dates_filled.extend(dates);
+    stats.dates_filled.sort_unstable();
+  }
+
+  /**
+   * Records a country that is not in the list of known countries.
+   *
+   * @param country   name or code of the country as used in the input
+   */
+  pub fn not_found(&mut self, country: &str)
+  {
+    self.unknown_countries.insert(country.to_string());
+  }
+
+  /**
+   * Gets the statistics over all countries.
+   *
+   * @return Returns the sums of all countries. The dates that were cut off or
+   *         filled are prefixed with the country, e. g. "Germany: 2024-08-04".
+   */
+  pub fn total(&self) -> RowStatistics
+  {
+    let mut total = RowStatistics::default();
+    for (country, stats) in self.countries.iter()
+    {
+      total.rows_read += stats.rows_read;
+      total.rows_kept += stats.rows_kept;
+      for (reason, count) in stats.rows_skipped.iter()
+      {
+        *total.rows_skipped.entry(*reason).or_insert(0) += count;
+      }
+      total.dates_cut_off.extend(stats.dates_cut_off.iter().map(|date| format!("{country}: {date}")));
+      total.dates_filled.extend(stats.dates_filled.iter().map(|date| format!("{country}: {date}")));
+    }
+    total
+  }
+
+  /**
+   * Formats the report as JSON.
+   *
+   * @return Returns the JSON-encoded report, including the totals.
+   */
+  pub fn to_json(&self) -> String
+  {
+    #[derive(Serialize)]
+    struct Json<'a>
+    {
+      format: &'a str,
+      total: RowStatistics,
+      countries: &'a BTreeMap<String, RowStatistics>,
+      unknown_countries: &'a BTreeSet<String>
+    }
+
+    let json = Json {
+      format: &self.format,
+      total: self.total(),
+      countries: &self.countries,
+      unknown_countries: &self.unknown_countries
+    };
+    // Serialization of maps with string keys and of plain numbers cannot fail.
+    serde_json::to_string_pretty(&json).expect("report contains only serializable values")
+  }
+
+  /**
+   * Writes the report as JSON into a file. An existing file is overwritten.
+   *
+   * @param path   path of the file
+   * @return Returns an Error, if the file could not be written.
+   */
+  pub fn write_json(&self, path: &str) -> Result<(), Error>
+  {
+    let path = std::path::Path::new(path);
+    std::fs::write(path, self.to_json() + "\n").map_err(|e| Error::io(path, e))
+  }
+
+  /**
+   * Shows the report, unless the configuration asks for quiet operation, and
+   * writes it to the JSON file given in the configuration, if any.
+   *
+   * @param config   configuration of the db or update subcommand
+   * @return Returns an Error, if the JSON file could not be written.
+   */
+  pub(crate) fn output(&self, config: &DbConfiguration) -> Result<(), Error>
+  {
+    if !config.quiet
+    {
+      print!("{self}");
+    }
+    match &config.report
+    {
+      Some(path) => self.write_json(path),
+      None => Ok(())
+    }
+  }
+}
+
+impl fmt::Display for ImportReport
+{
+  /// Shows the totals and only those countries whose rows were changed, because
+  /// a line for every country would hide them.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    let total = self.total();
+    writeln!(f, "Import report ({}):", self.format)?;
+    write!(f, "  {} rows read, {} rows kept", total.rows_read, total.rows_kept)?;
+    if total.skipped() > 0
+    {
+      write!(f, ", {}", total.skipped_text())?;
+    }
+    writeln!(f)?;
+    if !total.dates_cut_off.is_empty()
+    {
+      writeln!(f, "  {} date(s) removed, because they were not contiguous", total.dates_cut_off.len())?;
+    }
+    if !total.dates_filled.is_empty()
+    {
+      writeln!(f, "  {} missing date(s) added", total.dates_filled.len())?;
+    }
+    if !self.unknown_countries.is_empty()
+    {
+      let names: Vec<&str> = self.unknown_countries.iter().map(|name| name.as_str()).collect();
+      writeln!(f, "  countries not in the list of known countries: {}", names.join(", "))?;
+    }
+    for (country, stats) in self.countries.iter()
+    {
+      let skipped = stats.skipped();
+      if skipped == 0 && stats.dates_cut_off.is_empty() && stats.dates_filled.is_empty()
+      {
+        continue;
+      }
+      write!(f, "  {country}: {} rows read, {} rows kept", stats.rows_read, stats.rows_kept)?;
+      if skipped > 0
+      {
+        write!(f, ", {}", stats.skipped_text())?;
+      }
+      if !stats.dates_cut_off.is_empty()
+      {
+        write!(f, ", removed dates: {}", stats.dates_cut_off.join(", "))?;
+      }
+      if !stats.dates_filled.is_empty()
+      {
+        write!(f, ", added dates: {}", stats.dates_filled.join(", "))?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  #[test]
+  fn totals()
+  {
+    let mut report = ImportReport::new();
+    report.kept("Germany", 3);
+    report.skipped("Germany", SkipReason::FutureDate);
+    report.cut_off("Germany", vec![String::from("2024-08-05"), String::from("2024-08-04")]);
+    report.skipped("World", SkipReason::Aggregate);
+    report.skipped("World", SkipReason::Aggregate);
+    report.kept("Atlantis", 2);
+    report.not_found("Atlantis");
+
+    let germany = &report.countries["Germany"];
+    assert_eq!(germany.rows_read, 4);
+    assert_eq!(germany.rows_kept, 1);
+    assert_eq!(germany.skipped(), 1);
+    assert_eq!(germany.dates_cut_off, vec!["2024-08-04", "2024-08-05"]);
+
+    let total = report.total();
+    assert_eq!(total.rows_read, 8);
+    assert_eq!(total.rows_kept, 3);
+    assert_eq!(total.rows_skipped.get(&SkipReason::Aggregate), Some(&2));
+    assert_eq!(total.rows_skipped.get(&SkipReason::FutureDate), Some(&1));
+    assert_eq!(total.dates_cut_off, vec!["Germany: 2024-08-04", "Germany: 2024-08-05"]);
+  }
+
+  #[test]
+  fn display()
+  {
+    let mut report = ImportReport::new();
+    report.format = String::from("OWID");
+    report.kept("France", 10);
+    report.kept("Germany", 10);
+    report.skipped("Germany", SkipReason::FutureDate);
+    report.not_found("Atlantis");
+    let text = report.to_string();
+    assert!(text.starts_with("Import report (OWID):\n"));
+    assert!(text.contains("21 rows read, 20 rows kept, 1 skipped (future date: 1)"));
+    assert!(text.contains("countries not in the list of known countries: Atlantis"));
+    assert!(text.contains("Germany: 11 rows read"));
+    // Countries without skipped rows are not listed.
+    assert!(!text.contains("France"));
+  }
+
+  #[test]
+  fn json()
+  {
+    let mut report = ImportReport::new();
+    report.format = String::from("OWID");
+    report.kept("Germany", 2);
+    report.skipped("World", SkipReason::Aggregate);
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["format"], "OWID");
+    assert_eq!(json["total"]["rows_read"], 3);
+    assert_eq!(json["total"]["rows_skipped"]["aggregate"], 1);
+    assert_eq!(json["countries"]["Germany"]["rows_kept"], 2);
+    assert!(json["unknown_countries"].as_array().unwrap().is_empty());
+  }
+}
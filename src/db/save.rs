@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2023  Dirk Stolle
+    Copyright (C) 2023, 2026  Dirk Stolle
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
     the Free Software Foundation, either version 3 of the License, or
@@ -16,47 +16,125 @@
 */
 
 use crate::data;
-use crate::data::{Numbers, NumbersAndIncidence};
+use crate::data::{Granularity, MetricValue, Numbers, NumbersAndIncidence};
 use crate::database::Database;
+use crate::error::Error;
+use std::collections::BTreeMap;
+
+/// sums of cases and deaths of one country, indexed by date
+pub(crate) type DailySums = BTreeMap<String, (i64, i64)>;
 
 /**
- * Writes case numbers of one country it into the database.
+ * Writes case numbers of one country it into the database. Whether the
+ * country reports its numbers daily or weekly is detected and recorded, and
+ * the incidence values are calculated accordingly.
  *
  * @param db          an open SQLite database with existing tables
  * @param country_id  id of the country in the database
  * @param population  population of the country; or -1 if unknown
  * @param numbers     case numbers for that country
- * @return Returns true, if all data was written to the database successfully.
- *         Returns false otherwise.
+ * @return Returns an Error, if the data could not be written to the database.
  */
-pub fn numbers_into_db(db: &Database, country_id: &i64, population: &i32, numbers: &mut [Numbers]) -> bool
+pub fn numbers_into_db(db: &Database, country_id: &i64, population: &i32, numbers: &mut [Numbers]) -> Result<(), Error>
 {
   if numbers.is_empty()
   {
-    return true;
+    return Ok(());
   }
   numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
-  let enriched_data = data::calculate_incidence(numbers, population);
+  let granularity = data::detect_granularity(numbers.iter().map(|n| (n.date.as_str(), n.cases)));
+  let enriched_data = match granularity
+  {
+    Granularity::Daily => data::calculate_incidence(numbers, population),
+    Granularity::Weekly => data::calculate_incidence_weekly(numbers, population)
+  };
   let enriched_data = data::calculate_totals(&enriched_data);
-  db.insert_data(country_id, &enriched_data)
+  db.insert_data(country_id, &enriched_data)?;
+  db.set_granularity(country_id, granularity)
 }
 
 /**
- * Writes case numbers of one country it into the database.
+ * Writes case numbers of one country it into the database. Whether the
+ * country reports its numbers daily or weekly is detected and recorded.
  *
  * @param db          an open SQLite database with existing tables
  * @param country_id  id of the country in the database
  * @param numbers     case numbers for that country
- * @return Returns true, if all data was written to the database successfully.
- *         Returns false otherwise.
+ * @return Returns an Error, if the data could not be written to the database.
  */
-pub fn numbers_and_incidence_into_db(db: &Database, country_id: &i64, numbers: &mut [NumbersAndIncidence]) -> bool
+pub fn numbers_and_incidence_into_db(db: &Database, country_id: &i64, numbers: &mut [NumbersAndIncidence]) -> Result<(), Error>
 {
   if numbers.is_empty()
   {
-    return true;
+    return Ok(());
   }
   numbers.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+  let granularity = data::detect_granularity(numbers.iter().map(|n| (n.date.as_str(), n.cases)));
   let enriched_data = data::calculate_totals(numbers);
-  db.insert_data(country_id, &enriched_data)
+  db.insert_data(country_id, &enriched_data)?;
+  db.set_granularity(country_id, granularity)
+}
+
+/**
+ * Writes values of further metrics of one country into the database. Values
+ * after the last day with case numbers are dropped, because those days were
+ * removed from the case numbers, too.
+ *
+ * @param db          an open SQLite database with existing tables
+ * @param country_id  id of the country in the database
+ * @param numbers     case numbers of that country as written to the database
+ * @param metrics     values of the metrics of that country
+ * @return Returns an Error, if the data could not be written to the database.
+ */
+pub fn metrics_into_db(db: &Database, country_id: &i64, numbers: &[Numbers], metrics: &mut Vec<MetricValue>) -> Result<(), Error>
+{
+  match numbers.iter().map(|n| n.date.as_str()).max()
+  {
+    Some(last) => metrics.retain(|m| m.date.as_str() <= last),
+    None => metrics.clear()
+  }
+  db.insert_metrics(country_id, metrics)
+}
+
+/**
+ * Converts the summed up numbers of a country into daily numbers.
+ *
+ * @param days                sums of cases and deaths per date
+ * @param cumulative_cases    whether cases are accumulated numbers
+ * @param cumulative_deaths   whether deaths are accumulated numbers
+ * @return Returns the daily numbers, sorted by date.
+ */
+pub(crate) fn daily_numbers(days: DailySums, cumulative_cases: bool, cumulative_deaths: bool) -> Vec<Numbers>
+{
+  let mut previous = (0, 0);
+  let mut numbers = Vec::with_capacity(days.len());
+  for (date, (cases, deaths)) in days.into_iter()
+  {
+    let daily_cases = if cumulative_cases { cases - previous.0 } else { cases };
+    let daily_deaths = if cumulative_deaths { deaths - previous.1 } else { deaths };
+    previous = (cases, deaths);
+    numbers.push(Numbers { date, cases: daily_cases as i32, deaths: daily_deaths as i32 });
+  }
+  numbers
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  #[test]
+  fn daily_numbers_from_sums()
+  {
+    let mut days = BTreeMap::new();
+    days.insert(String::from("2021-01-02"), (15, 1));
+    days.insert(String::from("2021-01-01"), (10, 1));
+    days.insert(String::from("2021-01-03"), (12, 2));
+    let numbers = daily_numbers(days, true, false);
+    let cases: Vec<i32> = numbers.iter().map(|n| n.cases).collect();
+    let deaths: Vec<i32> = numbers.iter().map(|n| n.deaths).collect();
+    assert_eq!(cases, vec![10, 5, -3]);
+    assert_eq!(deaths, vec![1, 1, 2]);
+    assert_eq!(numbers[0].date, "2021-01-01");
+  }
 }
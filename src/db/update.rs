@@ -0,0 +1,439 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::configuration::DbConfiguration;
+use crate::data::{DateRange, Numbers, NumbersAndIncidence};
+use crate::database::Database;
+use crate::db::{save, CsvType, Db};
+use crate::error::Error;
+use std::collections::{BTreeMap, HashMap};
+
+/// statistics about an update of a database
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UpdateStatistics
+{
+  /// number of countries whose data was changed
+  pub changed_countries: usize,
+  /// number of countries whose data was already up to date
+  pub unchanged_countries: usize,
+  /// number of days that were not in the database before
+  pub new_days: usize,
+  /// number of days whose numbers were revised
+  pub revised_days: usize
+}
+
+pub struct DbUpdate
+{
+  config: DbConfiguration
+}
+
+impl DbUpdate
+{
+  /**
+   * Creates a new instance.
+   *
+   * @config   application configuration
+   * @return   Returns a Result containing the DbUpdate object, if successful.
+   *           Returns an Error, if the configuration seems to be invalid.
+   */
+  pub fn new(config: &DbConfiguration) -> Result<DbUpdate, Error>
+  {
+    if config.db_path.is_empty()
+    {
+      return Err(Error::Configuration("Path for SQLite database must not be an empty string!".to_string()));
+    }
+    if config.csv_input_file.is_empty()
+    {
+      return Err(Error::Configuration("Path of CSV file must be set to a non-empty string!".to_string()));
+    }
+
+    Ok(DbUpdate
+    {
+      config: DbConfiguration
+      {
+        csv_input_file: config.csv_input_file.clone(),
+        db_path: config.db_path.clone(),
+        quiet: config.quiet,
+        mapping: config.mapping.clone(),
+        report: config.report.clone(),
+        gaps: config.gaps
+      }
+    })
+  }
+
+  /**
+   * Updates the existing SQLite database with the data from the CSV file.
+   *
+   * The CSV file is first imported into a temporary database. Afterwards the
+   * numbers of every country are compared, and only countries with new or
+   * revised days are rewritten in the existing database.
+   *
+   * @return Returns statistics about the changes in case of success.
+   *         Returns an Error, if the update failed.
+   */
+  pub fn update_db(&self) -> Result<UpdateStatistics, Error>
+  {
+    let target = Database::new(&self.config.db_path)?;
+    target.require_current_schema()?;
+    let mapping = self.config.mapping.as_deref();
+    // The input is only read once, because it may come from standard input.
+    let (temp_path, csv_type, report) = Db::import_into_temporary_db(&self.config.csv_input_file, mapping, self.config.gaps, "update")?;
+    let result = Database::new(&temp_path.to_string_lossy())
+      .and_then(|source| self.apply(&source, &target, &csv_type));
+    // The temporary database is not needed anymore, whatever the outcome was.
+    let _ = std::fs::remove_file(&temp_path);
+    let stats = result?;
+    report.output(&self.config)?;
+    if !self.config.quiet
+    {
+      println!(
+        "Updated {} of {} countries: {} new day(s), {} revised day(s).",
+        stats.changed_countries,
+        stats.changed_countries + stats.unchanged_countries,
+        stats.new_days,
+        stats.revised_days
+      );
+    }
+    Ok(stats)
+  }
+
+  /**
+   * Transfers new and revised numbers from one database into another. All
+   * changes are performed in a single transaction, so the target is left
+   * unchanged, if one of them fails.
+   *
+   * @param source     database with the newer data
+   * @param target     database that shall be updated
+   * @param csv_type   format of the CSV file the newer data came from
+   * @return Returns statistics about the changes in case of success.
+   */
+  fn apply(&self, source: &Database, target: &Database, csv_type: &CsvType) -> Result<UpdateStatistics, Error>
+  {
+    target.batch("BEGIN TRANSACTION;")?;
+    match self.apply_changes(source, target, csv_type)
+    {
+      Ok(stats) =>
+      {
+        target.batch("COMMIT;")?;
+        Ok(stats)
+      },
+      Err(e) =>
+      {
+        let _ = target.batch("ROLLBACK;");
+        Err(e)
+      }
+    }
+  }
+
+  /**
+   * Writes new and revised numbers as well as the provenance of the newer data
+   * into the target database. The caller is responsible for the transaction.
+   *
+   * @param source     database with the newer data
+   * @param target     database that shall be updated
+   * @param csv_type   format of the CSV file the newer data came from
+   * @return Returns statistics about the changes in case of success.
+   */
+  fn apply_changes(&self, source: &Database, target: &Database, csv_type: &CsvType) -> Result<UpdateStatistics, Error>
+  {
+    let mut stats = UpdateStatistics::default();
+    let populations: HashMap<i64, i64> = target
+      .country_statistics()?
+      .iter()
+      .map(|c| (i64::from(c.country.country_id), i64::from(c.country.population)))
+      .collect();
+    for entry in source.country_statistics()?.iter()
+    {
+      let country = &entry.country;
+      let population = i64::from(country.population);
+      let target_id = target.get_country_id_or_insert(
+        &country.geo_id, &country.name, &population, &country.country_code, &country.continent
+      )?;
+      // Countries that were just inserted already have the new population.
+      let population_changed = match populations.get(&target_id)
+      {
+        Some(old_population) => *old_population != population,
+        None => false
+      };
+      if population_changed
+      {
+        target.set_population(&target_id, &population)?;
+      }
+
+      let all = DateRange::default();
+      let old = target.numbers_with_incidence(&(target_id as i32), &all)?;
+      let new = source.numbers_with_incidence(&country.country_id, &all)?;
+      let (mut merged, new_days, revised_days) = DbUpdate::merge(old, new);
+      // Newer values of further metrics replace older values of the same day.
+      target.insert_metrics(&target_id, &source.metrics(&country.country_id, &all)?)?;
+      // Incidence values depend on the population, so they have to be
+      // recalculated when it changes, even if the numbers stay the same.
+      if new_days == 0 && revised_days == 0 && !population_changed
+      {
+        stats.unchanged_countries += 1;
+        continue;
+      }
+      stats.changed_countries += 1;
+      stats.new_days += new_days;
+      stats.revised_days += revised_days;
+
+      target.delete_numbers(&target_id)?;
+      match csv_type
+      {
+        // ECDC data contains the incidence values, so they are kept as is,
+        // just like during the initial import. Weekly numbers must not go
+        // through the calculation of the daily incidence, either.
+        CsvType::Ecdc | CsvType::EcdcWeekly => save::numbers_and_incidence_into_db(target, &target_id, &mut merged)?,
+        _ =>
+        {
+          let mut numbers: Vec<Numbers> = merged
+            .into_iter()
+            .map(|n| Numbers { date: n.date, cases: n.cases, deaths: n.deaths })
+            .collect();
+          save::numbers_into_db(target, &target_id, &country.population, &mut numbers)?
+        }
+      }
+    }
+    if target.meta("source_format")?.is_none()
+    {
+      target.set_meta("source_format", csv_type.name())?;
+    }
+    // The import of the newer data is part of the history of the target.
+    for provenance in source.provenance()?.iter()
+    {
+      target.insert_provenance(provenance)?;
+    }
+    Ok(stats)
+  }
+
+  /**
+   * Merges newer numbers into existing numbers of a country.
+   *
+   * @param old   numbers that are in the database so far
+   * @param new   numbers from the newer data source
+   * @return Returns a tuple of the merged numbers (sorted by date), the number
+   *         of new days and the number of revised days.
+   */
+  fn merge(old: Vec<NumbersAndIncidence>, new: Vec<NumbersAndIncidence>) -> (Vec<NumbersAndIncidence>, usize, usize)
+  {
+    let mut merged: BTreeMap<String, NumbersAndIncidence> = old
+      .into_iter()
+      .map(|n| (n.date.clone(), n))
+      .collect();
+    let mut new_days = 0;
+    let mut revised_days = 0;
+    for n in new.into_iter()
+    {
+      match merged.get(&n.date)
+      {
+        None => new_days += 1,
+        Some(existing) if existing.cases != n.cases || existing.deaths != n.deaths => revised_days += 1,
+        Some(_) => continue
+      }
+      merged.insert(n.date.clone(), n);
+    }
+    (merged.into_values().collect(), new_days, revised_days)
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::configuration::GapHandling;
+  use crate::db::tests::write_who_csv;
+
+  /**
+   * Creates numbers without incidence values.
+   */
+  fn num(date: &str, cases: i32) -> NumbersAndIncidence
+  {
+    NumbersAndIncidence {
+      date: date.to_string(),
+      cases,
+      deaths: 0,
+      incidence_14d: None,
+      incidence_7d: None
+    }
+  }
+
+  #[test]
+  fn merge()
+  {
+    let old = vec![num("2021-01-01", 1), num("2021-01-02", 2), num("2021-01-03", 3)];
+    let new = vec![num("2021-01-02", 2), num("2021-01-03", 4), num("2021-01-04", 5)];
+    let (merged, new_days, revised_days) = DbUpdate::merge(old, new);
+    assert_eq!(new_days, 1);
+    assert_eq!(revised_days, 1);
+    let cases: Vec<i32> = merged.iter().map(|n| n.cases).collect();
+    assert_eq!(cases, vec![1, 2, 4, 5]);
+    assert_eq!(merged[0].date, "2021-01-01");
+    assert_eq!(merged[3].date, "2021-01-04");
+  }
+
+  #[test]
+  fn update_existing_database()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_update_db.csv");
+    let db_path = dir.join("test_update_db.db");
+    let _ = std::fs::remove_file(&db_path);
+    write_who_csv(&csv_path, "2020-01-03,AF,Afghanistan,EMRO,1,1,0,0\n\
+                              2020-01-04,AF,Afghanistan,EMRO,2,3,0,0\n\
+                              2020-01-03,DE,Germany,EURO,5,5,1,1\n\
+                              2020-01-04,DE,Germany,EURO,5,10,0,1\n");
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+
+    // Newer file: one new day for Afghanistan, revised numbers for Germany,
+    // and a new country.
+    write_who_csv(&csv_path, "2020-01-03,AF,Afghanistan,EMRO,1,1,0,0\n\
+                              2020-01-04,AF,Afghanistan,EMRO,2,3,0,0\n\
+                              2020-01-05,AF,Afghanistan,EMRO,4,7,1,1\n\
+                              2020-01-03,DE,Germany,EURO,5,5,1,1\n\
+                              2020-01-04,DE,Germany,EURO,7,12,0,1\n\
+                              2020-01-03,FR,France,EURO,3,3,0,0\n");
+    let stats = DbUpdate::new(&config).unwrap().update_db();
+    assert!(stats.is_ok());
+    let stats = stats.unwrap();
+    assert_eq!(stats.changed_countries, 3);
+    assert_eq!(stats.new_days, 2);
+    assert_eq!(stats.revised_days, 1);
+    // scope for database
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      let countries = db.countries().unwrap();
+      assert_eq!(countries.len(), 3);
+      let germany = countries.iter().find(|c| c.geo_id == "DE").unwrap();
+      let numbers = db.accumulated_numbers(&germany.country_id, &DateRange::default()).unwrap();
+      assert_eq!(numbers.len(), 2);
+      assert_eq!(numbers[1].cases, 12);
+      let afghanistan = countries.iter().find(|c| c.geo_id == "AF").unwrap();
+      let numbers = db.numbers(&afghanistan.country_id, &DateRange::default()).unwrap();
+      assert_eq!(numbers.len(), 3);
+      assert_eq!(numbers[2].date, "2020-01-05");
+      // The update is recorded after the initial import.
+      let provenance = db.provenance().unwrap();
+      assert_eq!(provenance.len(), 2);
+      assert_eq!(provenance[0].rows_kept, 4);
+      assert_eq!(provenance[1].rows_kept, 6);
+      assert_eq!(provenance[1].file, "test_update_db.csv");
+    }
+
+    // Running the same update again changes nothing.
+    let stats = DbUpdate::new(&config).unwrap().update_db().unwrap();
+    assert_eq!(stats.changed_countries, 0);
+    assert_eq!(stats.unchanged_countries, 3);
+
+    // clean up
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn update_missing_database()
+  {
+    let config = DbConfiguration {
+      csv_input_file: String::from("/does/not/exist.csv"),
+      db_path: String::from("/does/not/exist.db"),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let result = DbUpdate::new(&config).unwrap().update_db();
+    assert!(matches!(result, Err(Error::Io { .. })));
+  }
+
+  #[test]
+  fn update_outdated_database()
+  {
+    let db_path = std::env::temp_dir().join("test_update_outdated.db");
+    let _ = std::fs::remove_file(&db_path);
+    // The ECDC database was created before the schema version was recorded.
+    let original = std::path::Path::new(file!())
+      .parent()
+      .unwrap()
+      .join("..")
+      .join("..")
+      .join("data")
+      .join("corona-ecdc-2020-12-14.db");
+    assert!(std::fs::copy(original, &db_path).is_ok());
+    let config = DbConfiguration {
+      csv_input_file: String::from("/does/not/exist.csv"),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    // The database has to be migrated explicitly before it can be updated.
+    let result = DbUpdate::new(&config).unwrap().update_db();
+    assert!(matches!(result, Err(Error::UnsupportedSchema { version: 0, .. })));
+    // clean up
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn failed_update_is_rolled_back()
+  {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join("test_update_rollback.csv");
+    let db_path = dir.join("test_update_rollback.db");
+    let _ = std::fs::remove_file(&db_path);
+    write_who_csv(&csv_path, "2020-01-03,DE,Germany,EURO,5,5,1,1\n");
+    let config = DbConfiguration {
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      db_path: db_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert!(Db::new(&config).unwrap().create_db().is_ok());
+
+    write_who_csv(&csv_path, "2020-01-03,DE,Germany,EURO,5,5,1,1\n\
+                              2020-01-04,DE,Germany,EURO,7,12,0,1\n");
+    let (temp_path, csv_type, _) = Db::import_into_temporary_db(
+      &config.csv_input_file, None, GapHandling::Truncate, "update-rollback-test").unwrap();
+    let source = Database::new(&temp_path.to_string_lossy()).unwrap();
+    let target = Database::new(&config.db_path).unwrap();
+    // Recording the provenance is the last step, so all numbers have already
+    // been written when it fails.
+    assert!(target.batch("DROP TABLE provenance;").is_ok());
+    let update = DbUpdate::new(&config).unwrap();
+    assert!(update.apply(&source, &target, &csv_type).is_err());
+    let germany = target.countries().unwrap().remove(0);
+    let numbers = target.numbers(&germany.country_id, &DateRange::default()).unwrap();
+    assert_eq!(numbers.len(), 1);
+    // No transaction is left open.
+    assert!(target.batch("BEGIN TRANSACTION; COMMIT;").is_ok());
+
+    // clean up
+    drop(source);
+    assert!(std::fs::remove_file(temp_path).is_ok());
+    assert!(std::fs::remove_file(csv_path).is_ok());
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+}
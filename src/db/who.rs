@@ -15,205 +15,69 @@
  -------------------------------------------------------------------------------
 */
 
-use crate::configuration::DbConfiguration;
 use crate::data::{Country, Numbers};
 use crate::database::Database;
-use crate::db::save;
+use crate::db::{save, Columns, CsvType, ImportReport, Importer, Input};
+use crate::error::Error;
 use crate::world::World;
 use csv::Reader;
 
-pub struct DbWho
-{
-  config: DbConfiguration
-}
+/// importer for WHO's CSV format
+pub struct DbWho;
 
-impl DbWho
+impl Importer for DbWho
 {
-  /**
-   * Creates a new instance.
-   *
-   * @config   application configuration
-   * @return   Returns a Result containing the Db object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
-   */
-  pub fn new(config: &DbConfiguration) -> Result<DbWho, String>
-  {
-    if config.db_path.is_empty()
-    {
-      return Err("Path for SQLite database must not be an empty string!".to_string());
-    }
-    if config.csv_input_file.is_empty()
-    {
-      return Err("Path of CSV file must be set to a non-empty string!".to_string());
-    }
-
-    Ok(DbWho
-    {
-      config: DbConfiguration
-      {
-        csv_input_file: config.csv_input_file.clone(),
-        db_path: config.db_path.clone()
-      }
-    })
-  }
-
-  /**
-   * Creates the SQLite database from a CSV file.
-   *
-   * @return Returns whether the operation was successful.
-   */
-  pub fn create_db(&self) -> bool
+  fn csv_type(&self) -> CsvType
   {
-    match crate::checks::sqlite_check()
-    {
-      crate::checks::Status::Error(msg) =>
-      {
-        eprintln!("{msg}");
-        return false;
-      },
-      crate::checks::Status::Warn(msg) => println!("Warning: {msg}"),
-      _ => ()
-    }
-    let db = match Database::create(&self.config.db_path)
-    {
-      Err(e) =>
-      {
-        eprintln!("Error while creating database: {e}");
-        return false;
-      }
-      Ok(base) => base
-    };
-    if !db.calculate_total_numbers(&false)
-    {
-      eprintln!("Error: Could not add columns for accumulated numbers to database table!");
-      return false;
-    }
-    self.read_csv(&db)
+    CsvType::Who
   }
 
-  /**
-   * Fills the SQLite database with values from the CSV file.
-   *
-   * @param db    an open SQLite database with existing tables
-   * @param
-   */
-  fn read_csv(&self, db: &Database) -> bool
+  fn detect(&self, first_line: &str) -> bool
   {
-    let mut reader = match Reader::from_path(&self.config.csv_input_file)
-    {
-      Ok(rdr) => rdr,
-      Err(e) => {
-        eprintln!("Error: Could not open CSV file {}: {}",
-                  self.config.csv_input_file, e);
-        return false;
-      }
-    };
-    // Check headers.
-    if !self.check_headers(&mut reader)
-    {
-      return false;
-    }
-    // parse CSV values
-    DbWho::parse_csv_into_db(db, &mut reader)
+    Columns::from_line(first_line).contains_all(&["Date_reported", "Country_code", "Country", "WHO_region"])
   }
 
-  /**
-   * Checks whether the CSV headers match the expected headers.
-   *
-   * @param reader    an opened CSV reader
-   * @return Returns true, if the headers are correct. Returns false otherwise.
-   */
-  fn check_headers(&self, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn import(&self, input: Input, db: &Database, report: &mut ImportReport) -> Result<(), Error>
   {
-    let headers = match reader.headers()
-    {
-      Ok(head) => head,
-      Err(e) =>
-      {
-        eprintln!(
-          "Error: Could not read header of CSV file {}: {}",
-          self.config.csv_input_file, e
-        );
-        return false;
-      }
-    };
-    let expected_headers = vec![
-      "Date_reported",
-      "Country_code",
-      "Country",
-      "WHO_region",
-      "New_cases",
-      "Cumulative_cases",
-      "New_deaths",
-      "Cumulative_deaths",
-    ];
-    if headers != expected_headers
-    {
-      eprintln!("Error: CSV headers do not match the expected headers. \
-                 Found the following headers: {headers:?}");
-      return false;
-    }
-    // Headers match. :)
-    true
+    let mut reader = Reader::from_reader(input);
+    DbWho::parse_csv_into_db(db, &mut reader, report)
   }
+}
 
+impl DbWho
+{
   /**
    * Parses the CSV data and writes it into the database.
    *
    * @param db        an open SQLite database with existing tables
    * @param reader    an opened CSV reader
-   * @return Returns true, if all data was parsed and written to the database
-   *         successfully. Returns false otherwise.
+   * @param report    report that collects statistics about the rows
+   * @return Returns an Error, if the data could not be parsed or written to
+   *         the database.
    */
-  fn parse_csv_into_db(db: &Database, reader: &mut csv::Reader<std::fs::File>) -> bool
+  fn parse_csv_into_db<R: std::io::Read>(db: &Database, reader: &mut csv::Reader<R>, report: &mut ImportReport) -> Result<(), Error>
   {
+    let [idx_date, idx_geo_id, idx_name, idx_region, idx_cases, idx_deaths] = Columns::new(reader.headers()?)
+      .require(["Date_reported", "Country_code", "Country", "WHO_region", "New_cases", "New_deaths"])?;
     let mut last_geo_id = String::new();
     let mut country_id: i64 = -1;
     let mut population: i32 = -1;
     let mut record = csv::StringRecord::new();
     let world = World::new();
     let mut parsed_data = Vec::<Numbers>::new();
-    loop
+    while reader.read_record(&mut record)?
     {
-      match reader.read_record(&mut record)
-      {
-        Ok(success) =>
-        {
-          if !success
-          {
-            // No more records to read.
-            break;
-          }
-        },
-        Err(e) => {
-          // Failed to read.
-          eprintln!("Failed to read CSV record! {e}");
-          return false;
-        }
-      }
-      let r_len = record.len();
-      if r_len != 8
-      {
-        eprintln!("Error: A line of CSV data does not have eight data elements, \
-                   but {} elements instead!\nThe line position is '{}'. \
-                   It will be skipped.",
-                   r_len, record.position().unwrap().line());
-        return false;
-      }
-      let current_geo_id = record.get(1).unwrap();
+      let current_geo_id = &record[idx_geo_id];
       if current_geo_id != last_geo_id
       {
         // Insert data of previous country.
         if country_id != -1
-          && !save::numbers_into_db(db, &country_id, &population, &mut parsed_data)
         {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
+          save::numbers_into_db(db, &country_id, &population, &mut parsed_data)?;
         }
         parsed_data.clear();
         // new country
-        let name = record.get(2).unwrap();
+        let name = &record[idx_name];
         let name = name.trim_end_matches("[1]");
         let no_country = Country {
           country_id: -1,
@@ -221,9 +85,14 @@ impl DbWho
           population: -1,
           geo_id: current_geo_id.to_string(),
           country_code: String::new(),
-          continent: record.get(3).unwrap().to_string()
+          continent: record[idx_region].to_string()
         };
-        let world_data = world.find_by_geo_id(current_geo_id).unwrap_or(&no_country);
+        let known = world.find_by_geo_id(current_geo_id);
+        if known.is_none()
+        {
+          report.not_found(name);
+        }
+        let world_data = known.unwrap_or(&no_country);
         population = world_data.population;
         // Get country id or insert country.
         country_id = db.get_country_id_or_insert(
@@ -232,46 +101,36 @@ impl DbWho
           &i64::from(world_data.population),
           &world_data.country_code,
           &world_data.continent
-        );
-        if country_id == -1
-        {
-          eprintln!("Error: Could not insert country data into database!");
-          return false;
-        }
+        )?;
         last_geo_id = current_geo_id.to_string();
       }
       // Add current record.
-      let date = record.get(0).unwrap();
-      let cases: i32 = match record.get(4).unwrap().is_empty()
+      let date = &record[idx_date];
+      let cases: i32 = match record[idx_cases].is_empty()
       {
-        false => record.get(4).unwrap().parse().unwrap_or(i32::MIN),
+        false => record[idx_cases].parse().unwrap_or(i32::MIN),
         true => 0
       };
-      let deaths: i32 = match record.get(6).unwrap().is_empty()
+      let deaths: i32 = match record[idx_deaths].is_empty()
       {
-        false => record.get(6).unwrap().parse().unwrap_or(i32::MIN),
+        false => record[idx_deaths].parse().unwrap_or(i32::MIN),
         true => 0
       };
       if cases == i32::MIN || deaths == i32::MIN
       {
-        eprintln!(
-          "Error: Got invalid case numbers on line {}.",
-          record.position().unwrap().line()
-        );
-        return false;
+        return Err(Error::csv_parse(&record, "Got invalid case numbers."));
       }
       parsed_data.push(Numbers { date: String::from(date), cases, deaths});
+      report.kept(&record[idx_name], 1);
     }
     // Execute remaining batch inserts, if any are left.
     if !parsed_data.is_empty()
-      && !crate::db::save::numbers_into_db(db, &country_id, &population, &mut parsed_data)
     {
-      eprintln!("Error: Could not insert country data into database!");
-      return false;
+      crate::db::save::numbers_into_db(db, &country_id, &population, &mut parsed_data)?;
     }
 
     // Done.
-    true
+    Ok(())
   }
 }
 
@@ -279,7 +138,9 @@ impl DbWho
 mod tests
 {
   use super::*;
-  use crate::data::Country;
+  use crate::configuration::{DbConfiguration, GapHandling};
+  use crate::db::Db;
+  use crate::data::{Country, DateRange};
 
   /**
    * Gets path to the corona_daily.csv file in data directory.
@@ -384,18 +245,22 @@ mod tests
     let db_file_name = std::env::temp_dir().join("test_db_corona_who.db");
     let config = DbConfiguration {
       db_path: db_file_name.to_str().unwrap().to_string(),
-      csv_input_file: get_csv_path()
+      csv_input_file: get_csv_path(),
+      quiet: false,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
     };
     // scope for db
     {
-      let db = DbWho::new(&config).unwrap();
-      assert!(db.create_db());
+      let db = Db::new(&config).unwrap();
+      assert!(db.create_db_with(&DbWho).is_ok());
       // Check that DB file exists.
       assert!(db_file_name.exists());
       // Check some content.
       let db = Database::new(&config.db_path).unwrap();
       // Check a country.
-      let countries = db.countries();
+      let countries = db.countries().unwrap();
       {
         let de = Country
         {
@@ -416,7 +281,7 @@ mod tests
         assert_eq!(de.country_code, found.country_code);
         assert_eq!(de.continent, found.continent);
         // Check some numbers.
-        let numbers = db.numbers_with_incidence(&de.country_id);
+        let numbers = db.numbers_with_incidence(&de.country_id, &DateRange::default()).unwrap();
         // 1|2020-02-12|2|0|0.022886268507508|0.00843178313434506|23|0
         let found = numbers.iter().find(|&n| n.date == "2020-02-12");
         assert!(found.is_some());
@@ -452,7 +317,7 @@ mod tests
       assert_eq!(ch.country_code, found.country_code);
       assert_eq!(ch.continent, found.continent);
       // Check some numbers.
-      let numbers = db.numbers_with_incidence(&ch.country_id);
+      let numbers = db.numbers_with_incidence(&ch.country_id, &DateRange::default()).unwrap();
       // 2|2020-03-28|1305|32|157.90224549586|86.9328401677471|15448|278
       let found = numbers.iter().find(|&n| n.date == "2020-03-28");
       assert!(found.is_some());
@@ -471,4 +336,71 @@ mod tests
     assert!(std::fs::remove_file(db_file_name).is_ok());
     assert!(std::fs::remove_file(config.csv_input_file).is_ok());
   }
+
+  #[test]
+  fn reordered_and_extended_columns()
+  {
+    let csv_path = std::env::temp_dir().join("test_db_corona_who_reordered.csv");
+    let db_path = std::env::temp_dir().join("test_db_corona_who_reordered.db");
+    let _ = std::fs::remove_file(&db_path);
+    let csv = "Country,Country_code,WHO_region,Date_reported,New_deaths,New_cases,Cumulative_cases,Cumulative_deaths,Remark
+Germany,DE,EURO,2020-01-04,0,1,1,0,
+Germany,DE,EURO,2020-01-05,2,3,4,2,revised
+";
+    assert!(std::fs::write(&csv_path, csv).is_ok());
+    let config = DbConfiguration {
+      db_path: db_path.to_str().unwrap().to_string(),
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    assert!(DbWho.detect(csv.lines().next().unwrap()));
+    assert!(Db::new(&config).unwrap().create_db_with(&DbWho).is_ok());
+    // scope for db
+    {
+      let db = Database::new(&config.db_path).unwrap();
+      let countries = db.countries().unwrap();
+      assert_eq!(countries.len(), 1);
+      assert_eq!(countries[0].geo_id, "DE");
+      let numbers = db.numbers(&countries[0].country_id, &DateRange::default()).unwrap();
+      assert_eq!(numbers.len(), 2);
+      assert_eq!(numbers[1].date, "2020-01-05");
+      assert_eq!(numbers[1].cases, 3);
+      assert_eq!(numbers[1].deaths, 2);
+    }
+    // clean up
+    assert!(std::fs::remove_file(db_path).is_ok());
+    assert!(std::fs::remove_file(csv_path).is_ok());
+  }
+
+  #[test]
+  fn missing_column()
+  {
+    let csv_path = std::env::temp_dir().join("test_db_corona_who_missing.csv");
+    let db_path = std::env::temp_dir().join("test_db_corona_who_missing.db");
+    let _ = std::fs::remove_file(&db_path);
+    let csv = "Date_reported,Country_code,Country,WHO_region,Cumulative_cases,Cumulative_deaths
+2020-01-04,DE,Germany,EURO,1,0
+";
+    assert!(std::fs::write(&csv_path, csv).is_ok());
+    let config = DbConfiguration {
+      db_path: db_path.to_str().unwrap().to_string(),
+      csv_input_file: csv_path.to_str().unwrap().to_string(),
+      quiet: true,
+      mapping: None,
+      report: None,
+      gaps: GapHandling::Truncate
+    };
+    let result = Db::new(&config).unwrap().create_db_with(&DbWho);
+    match result
+    {
+      Err(Error::MissingColumns(names)) => assert_eq!(names, vec!["New_cases", "New_deaths"]),
+      _ => panic!("Expected error about missing columns!")
+    }
+    // clean up
+    assert!(std::fs::remove_file(db_path).is_ok());
+    assert!(std::fs::remove_file(csv_path).is_ok());
+  }
 }
@@ -0,0 +1,213 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// errors that can occur in this crate
+#[derive(Debug)]
+pub enum Error
+{
+  /// reading or writing a file or directory failed
+  Io { path: PathBuf, error: std::io::Error },
+
+  /// an SQLite operation failed
+  Sqlite(rusqlite::Error),
+
+  /// reading or writing a CSV file failed
+  Csv(csv::Error),
+
+  /// a CSV record contains invalid data
+  CsvParse { line: u64, message: String },
+
+  /// the CSV file has an unknown format
+  UnknownFormat(String),
+
+  /// the CSV file lacks columns that are required to read it
+  MissingColumns(Vec<String>),
+
+  /// the template contains no usable sections
+  Template(String),
+
+  /// a required section is missing in the template
+  TemplateSectionMissing(String),
+
+  /// the configuration or the command line arguments are invalid
+  Configuration(String),
+
+  /// the database does not contain the requested data
+  NoData(String),
+
+  /// an output file or database already exists and will not be overwritten
+  AlreadyExists(PathBuf),
+
+  /// the SQLite version is too old
+  UnsupportedSqlite(String),
+
+  /// the database contains inconsistent data
+  InvalidData(String),
+
+  /// the database schema has a version the operation cannot handle
+  UnsupportedSchema { version: i64, supported: i64 }
+}
+
+impl Error
+{
+  /**
+   * Creates an I/O error for a given path.
+   *
+   * @param path    the file or directory that caused the error
+   * @param error   the underlying I/O error
+   * @return Returns the corresponding Error.
+   */
+  pub fn io(path: &Path, error: std::io::Error) -> Error
+  {
+    Error::Io { path: path.to_path_buf(), error }
+  }
+
+  /**
+   * Creates a CSV parse error for a record.
+   *
+   * @param record    the CSV record containing invalid data
+   * @param message   description of the problem
+   * @return Returns the corresponding Error.
+   */
+  pub fn csv_parse(record: &csv::StringRecord, message: &str) -> Error
+  {
+    Error::CsvParse {
+      line: record.position().map_or(0, |p| p.line()),
+      message: message.to_string()
+    }
+  }
+}
+
+impl fmt::Display for Error
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    match self
+    {
+      Error::Io { path, error } => write!(f, "{}: {error}", path.display()),
+      Error::Sqlite(e) => write!(f, "SQLite error: {e}"),
+      Error::Csv(e) => write!(f, "CSV error: {e}"),
+      Error::CsvParse { line, message } => write!(f, "Invalid data in line {line} of CSV file: {message}"),
+      Error::UnknownFormat(msg) => write!(f, "Unknown format: {msg}"),
+      Error::MissingColumns(names) => write!(
+        f, "The CSV file lacks the required column(s) '{}'.", names.join("', '")
+      ),
+      Error::Template(msg) => write!(f, "Template error: {msg}"),
+      Error::TemplateSectionMissing(name) => write!(f, "There is no section '{name}' in the template."),
+      Error::Configuration(msg) => write!(f, "{msg}"),
+      Error::NoData(msg) => write!(f, "{msg}"),
+      Error::AlreadyExists(path) => write!(f, "A file or directory named {} already exists!", path.display()),
+      Error::UnsupportedSqlite(msg) => write!(f, "{msg}"),
+      Error::InvalidData(msg) => write!(f, "{msg}"),
+      Error::UnsupportedSchema { version, supported } if version > supported => write!(
+        f, "The database has the schema version {version}, but this version of the \
+            application only supports schema versions up to {supported}. Use a newer \
+            version of the application."
+      ),
+      Error::UnsupportedSchema { version, supported } => write!(
+        f, "The database has the outdated schema version {version}, but schema version \
+            {supported} is required. Run the migrate subcommand to upgrade it."
+      )
+    }
+  }
+}
+
+impl std::error::Error for Error
+{
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+  {
+    match self
+    {
+      Error::Io { error, .. } => Some(error),
+      Error::Sqlite(e) => Some(e),
+      Error::Csv(e) => Some(e),
+      _ => None
+    }
+  }
+}
+
+impl From<rusqlite::Error> for Error
+{
+  fn from(error: rusqlite::Error) -> Self
+  {
+    Error::Sqlite(error)
+  }
+}
+
+impl From<csv::Error> for Error
+{
+  fn from(error: csv::Error) -> Self
+  {
+    Error::Csv(error)
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  #[test]
+  fn display()
+  {
+    let e = Error::CsvParse { line: 42, message: String::from("invalid date") };
+    assert_eq!(e.to_string(), "Invalid data in line 42 of CSV file: invalid date");
+    let e = Error::TemplateSectionMissing(String::from("graph"));
+    assert_eq!(e.to_string(), "There is no section 'graph' in the template.");
+    let e = Error::MissingColumns(vec![String::from("date"), String::from("code")]);
+    assert_eq!(e.to_string(), "The CSV file lacks the required column(s) 'date', 'code'.");
+    let e = Error::io(Path::new("/tmp/foo"),
+                      std::io::Error::new(std::io::ErrorKind::NotFound, "not found"));
+    assert_eq!(e.to_string(), "/tmp/foo: not found");
+    let e = Error::UnsupportedSchema { version: 5, supported: 4 };
+    assert!(e.to_string().contains("Use a newer version of the application."));
+    let e = Error::UnsupportedSchema { version: 0, supported: 4 };
+    assert!(e.to_string().contains("Run the migrate subcommand"));
+  }
+
+  #[test]
+  fn source()
+  {
+    use std::error::Error as _;
+
+    let e = Error::io(Path::new("/tmp/foo"),
+                      std::io::Error::new(std::io::ErrorKind::NotFound, "not found"));
+    assert!(e.source().is_some());
+    let e = Error::NoData(String::from("no data"));
+    assert!(e.source().is_none());
+  }
+
+  #[test]
+  fn csv_parse_line_number()
+  {
+    let data = "a,b\n1,2\n3,4\n";
+    let mut reader = csv::Reader::from_reader(data.as_bytes());
+    let record = reader.records().nth(1).unwrap().unwrap();
+    match Error::csv_parse(&record, "bad")
+    {
+      Error::CsvParse { line, message } =>
+      {
+        assert_eq!(line, 3);
+        assert_eq!(message, "bad");
+      },
+      _ => panic!("Expected CsvParse!")
+    }
+  }
+}
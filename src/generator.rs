@@ -15,9 +15,10 @@
  -------------------------------------------------------------------------------
 */
 
-use super::configuration::HtmlConfiguration;
-use crate::data::Country;
+use super::configuration::{Graph, HtmlConfiguration};
+use crate::data::{self, Country, DateRange, Granularity, Metric};
 use crate::database::Database;
+use crate::error::Error;
 use crate::template::Template;
 
 use std::fs; // for create_dir_all() and copy()
@@ -54,27 +55,26 @@ impl Generator
    *
    * @config   application configuration
    * @return   Returns a Result containing the Generator object, if successful.
-   *           Returns a string with an error message, if the configuration
-   *           seems to be invalid.
+   *           Returns an Error, if the configuration seems to be invalid.
    */
-  pub fn new(config: &HtmlConfiguration) -> Result<Generator, String>
+  pub fn new(config: &HtmlConfiguration) -> Result<Generator, Error>
   {
     if config.db_path.is_empty()
     {
-      return Err("Path to SQLite database must not be an empty string!".to_string());
+      return Err(Error::Configuration("Path to SQLite database must not be an empty string!".to_string()));
     }
     if config.output_directory.is_empty()
     {
-      return Err("Path of output directory must be set to a non-empty string!".to_string());
+      return Err(Error::Configuration("Path of output directory must be set to a non-empty string!".to_string()));
     }
     if let Some(path) = &config.template_path
     {
       if !path.exists()
       {
-        return Err(format!(
-          "Template file '{}' does not exist!",
-          path.display()
-        ));
+        return Err(Error::io(path, std::io::Error::new(
+          std::io::ErrorKind::NotFound,
+          "template file does not exist"
+        )));
       }
     }
 
@@ -84,7 +84,11 @@ impl Generator
       {
         db_path: config.db_path.clone(),
         output_directory: config.output_directory.clone(),
-        template_path: config.template_path.clone()
+        template_path: config.template_path.clone(),
+        graphs: config.graphs.clone(),
+        selection: config.selection.clone(),
+        date_range: config.date_range.clone(),
+        quiet: config.quiet
       }
     })
   }
@@ -92,90 +96,102 @@ impl Generator
   /**
    * Generates the HTML files.
    *
-   * @return Returns whether the generation was successful.
+   * @return Returns an Error, if the generation failed.
    */
-  pub fn generate(&self) -> bool
+  pub fn generate(&self) -> Result<(), Error>
   {
     match crate::checks::sqlite_check()
     {
-      crate::checks::Status::Error(msg) =>
-      {
-        eprintln!("{msg}");
-        return false;
-      },
-      crate::checks::Status::Warn(msg) => println!("Warning: {msg}"),
+      crate::checks::Status::Error(msg) => return Err(Error::UnsupportedSqlite(msg)),
+      crate::checks::Status::Warn(msg) if !self.config.quiet => println!("Warning: {msg}"),
       _ => ()
     }
 
-    let db = Database::new(&self.config.db_path);
-    let db = match db
-    {
-      Ok(db) => db,
-      Err(_) =>
-      {
-        eprintln!(
-          "Error: Database file {} does not exist or is not readable!",
-          self.config.db_path
-        );
-        return false;
-      }
-    };
-
-    let success = fs::create_dir_all(&self.config.output_directory);
-    if let Err(error) = success
-    {
-      eprintln!("Error: Could not create directory {}: {error}",
-                self.config.output_directory);
-      return false;
-    }
-    // Perform calculations for total numbers in database, if necessary.
-    if !db.calculate_total_numbers(&true)
-    {
-      eprintln!("Error: Database update failed. \
-                 Calculations for accumulated numbers could not be performed!");
-      return false;
+    let db = Database::new(&self.config.db_path)?;
+    fs::create_dir_all(&self.config.output_directory)
+      .map_err(|e| Error::io(Path::new(&self.config.output_directory), e))?;
+    // Databases of the oldest schema lack the accumulated numbers, which have to
+    // be added by an explicit migration.
+    if !db.has_total_numbers()?
+    {
+      return Err(Error::UnsupportedSchema { version: db.schema_version()?, supported: Database::SCHEMA_VERSION });
     }
     // Handle each country.
-    let countries = db.countries();
+    let countries = db.countries()?;
     if countries.is_empty()
     {
       // Something is wrong here, there is no data.
-      eprintln!(
-        "Error: Could not find any countries in the database {}!",
+      return Err(Error::NoData(format!(
+        "Could not find any countries in the database {}!",
         self.config.db_path
-      );
-      return false;
+      )));
     }
-    for country in countries.iter()
+    let countries: Vec<Country> = countries
+      .into_iter()
+      .filter(|c| self.config.selection.includes(c))
+      .collect();
+    if countries.is_empty()
     {
-      if !self.generate_country(&db, country)
-      {
-        eprintln!(
-          "Error while generating file for {} ({})!",
-          country.name, country.geo_id
-        );
-        return false;
-      }
+      return Err(Error::NoData("None of the countries in the database matches the selection!".to_string()));
     }
-    // Handle accumulated numbers worldwide.
-    if !self.generate_world(&db)
+    // Only continents with at least one selected country get a page.
+    let continents: Vec<String> = db
+      .continents()?
+      .into_iter()
+      .filter(|continent| countries.iter().any(|c| &c.continent == continent))
+      .collect();
+    // Every page shows where the data came from.
+    let footer = self.generate_footer(&db)?;
+    for country in countries.iter()
     {
-      eprintln!("Error while generating file for worldwide numbers!");
-      return false;
+      self.generate_country(&db, country, &footer)?;
     }
+    // Handle accumulated numbers worldwide.
+    self.generate_world(&db, &footer)?;
     // Generate graphs per continent (incidence only).
-    if !self.generate_continents(&db)
+    self.generate_continents(&db, &continents, &footer)?;
+    // Copy assets.
+    self.create_assets()?;
+    // Site index comes last.
+    self.create_index(&countries, &continents, &footer)
+  }
+
+  /**
+   * Generates the HTML snippet with the attribution of the data source and
+   * the date of the latest data, which is shown at the bottom of every page.
+   *
+   * @param db       reference to the Database instance
+   * @return Returns a string containing the HTML snippet, if the generation was successful.
+   *         Returns an empty string, if the template has no footer section.
+   *         Returns an Error, if an error occurred.
+   */
+  fn generate_footer(&self, db: &Database) -> Result<String, Error>
+  {
+    let mut tpl = Template::new();
+    self.load_template(&mut tpl)?;
+    // Custom templates of older versions may not have the section.
+    if !tpl.has_section("footer")
     {
-      eprintln!("Error while generating files for continents!");
-      return false;
+      return Ok(String::new());
     }
-    // Copy assets.
-    if !self.create_assets()
+    let mut attributions: Vec<String> = Vec::new();
+    for provenance in db.provenance()?.into_iter()
     {
-      return false;
+      if !attributions.contains(&provenance.attribution)
+      {
+        attributions.push(provenance.attribution);
+      }
     }
-    // Site index comes last.
-    self.create_index(&countries, &db.continents())
+    let attribution = match attributions.is_empty()
+    {
+      // Databases created by older versions do not record their source.
+      true => String::from("unknown"),
+      false => attributions.join(", ")
+    };
+    tpl.load_section("footer")?;
+    tpl.tag("attribution", &attribution);
+    tpl.tag("asOf", &db.last_date()?.unwrap_or_else(|| String::from("unknown")));
+    tpl.generate()
   }
 
   /**
@@ -183,34 +199,28 @@ impl Generator
    * The decision is made depending on the configuration setting.
    *
    * @param tpl   the template instance into which the template shall be loaded
-   * @return Returns whether the template was loaded successfully.
+   * @return Returns an Error, if the template could not be loaded.
    */
-  fn load_template(&self, tpl: &mut Template) -> bool
+  fn load_template(&self, tpl: &mut Template) -> Result<(), Error>
   {
     match &self.config.template_path
     {
-      None =>
-      {
-        if !tpl.load_from_str(MAIN_TEMPLATE)
-        {
-          eprintln!("Error: Could not load main template!");
-          return false;
-        }
-      },
-      Some(path) =>
-      {
-        if !tpl.load_from_file(path)
-        {
-          eprintln!(
-            "Error: Could not load main template file '{}'!",
-            path.display()
-          );
-          return false;
-        }
-      }
+      None => tpl.load_from_str(MAIN_TEMPLATE),
+      Some(path) => tpl.load_from_file(path)
     }
+  }
 
-    true
+  /**
+   * Writes generated HTML content to a file in the output directory.
+   *
+   * @param name      file name, relative to the output directory
+   * @param content   the HTML content
+   * @return Returns an Error, if the file could not be written.
+   */
+  fn write_file(&self, name: &str, content: &str) -> Result<(), Error>
+  {
+    let file = Path::new(&self.config.output_directory).join(name);
+    fs::write(&file, content.as_bytes()).map_err(|e| Error::io(&file, e))
   }
 
   /**
@@ -218,156 +228,82 @@ impl Generator
    *
    * @param db       reference to the Database instance
    * @param country  country data (id, name, etc.)
-   * @return Returns whether the generation was successful.
+   * @param footer   HTML snippet that is shown at the bottom of the page
+   * @return Returns an Error, if the generation failed.
    */
-  fn generate_country(&self, db: &Database, country: &Country) -> bool
+  fn generate_country(&self, db: &Database, country: &Country, footer: &str) -> Result<(), Error>
   {
     let mut tpl = Template::new();
-    if !self.load_template(&mut tpl)
-    {
-      return false;
-    }
+    self.load_template(&mut tpl)?;
     // scripts
-    if !tpl.load_section("script")
-    {
-      return false;
-    }
+    tpl.load_section("script")?;
     tpl.tag("path", Plotly::ASSET_PATH);
-    let scripts = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    let scripts = tpl.generate()?;
     // header
-    if !tpl.load_section("header")
-    {
-      return false;
-    }
+    tpl.load_section("header")?;
     tpl.integrate("scripts", &scripts);
     tpl.tag("title",
             &("Corona cases in ".to_owned() + &country.name
           + " (" + &country.geo_id + ")"));
-    let header = match tpl.generate()
+    let header = tpl.generate()?;
+    // graphs
+    let mut parts: Vec<String> = Vec::new();
+    for graph in Graph::ALL.iter().filter(|g| self.config.graphs.contains(g))
     {
-      Some(generated) => generated,
-      None => return false
-    };
-    // graph
-    let graph = self.generate_graph(db, country, &mut tpl);
-    let graph = match graph
-    {
-      Some(stringy) => stringy,
-      None => return false
-    };
-    let graph_accu = self.generate_accumulated_graph(db, country, &mut tpl);
-    let graph_accu = match graph_accu
-    {
-      Some(stringy) => stringy,
-      None => return false
-    };
-    let mut graph = graph + "\n<br />\n" + &graph_accu;
-    let graph_years = self.generate_graph_incidence_by_year(db, country, &mut tpl);
-    let graph_years = match graph_years
-    {
-      Some(stringy) => stringy,
-      None => return false
-    };
-    if !graph_years.is_empty()
-    {
-      graph = graph_years + "\n<br />\n" + &graph;
-    }
-    let graph_incidence = self.generate_incidence_graph(db, country, &mut tpl);
-    let graph_incidence = match graph_incidence
-    {
-      Some(stringy) => stringy,
-      None => return false
-    };
-    if !graph_incidence.is_empty()
-    {
-      graph = graph_incidence + "\n<br />\n" + &graph;
+      let part = match graph
+      {
+        Graph::Incidence => self.generate_incidence_graph(db, country, &mut tpl)?,
+        Graph::IncidenceByYear => self.generate_graph_incidence_by_year(db, country, &mut tpl)?,
+        Graph::Cases => self.generate_graph(db, country, &mut tpl)?,
+        Graph::Accumulated => self.generate_accumulated_graph(db, country, &mut tpl)?,
+        Graph::Vaccination => self.generate_vaccination_graph(db, country, &mut tpl)?
+      };
+      if !part.is_empty()
+      {
+        parts.push(part);
+      }
     }
+    let graph = parts.join("\n<br />\n");
     // full
-    if !tpl.load_section("full")
-    {
-      return false;
-    }
+    tpl.load_section("full")?;
     tpl.integrate("header", &header);
-    tpl.integrate("content", &graph);
-    let full = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    tpl.integrate("content", &(graph + footer));
+    let full = tpl.generate()?;
     // write it to a file
-    let file = format!("{}/{}.html", self.config.output_directory, country.geo_id.to_lowercase());
-    let written = fs::write(file, full.as_bytes());
-    written.is_ok()
+    self.write_file(&(country.geo_id.to_lowercase() + ".html"), &full)
   }
 
   /**
    * Generates the HTML file for worldwide numbers.
    *
    * @param db       reference to the Database instance
-   * @return Returns whether the generation was successful.
+   * @param footer   HTML snippet that is shown at the bottom of the page
+   * @return Returns an Error, if the generation failed.
    */
-  fn generate_world(&self, db: &Database) -> bool
+  fn generate_world(&self, db: &Database, footer: &str) -> Result<(), Error>
   {
     let mut tpl = Template::new();
-    if !self.load_template(&mut tpl)
-    {
-      return false;
-    }
+    self.load_template(&mut tpl)?;
     // scripts
-    if !tpl.load_section("script")
-    {
-      return false;
-    }
+    tpl.load_section("script")?;
     tpl.tag("path", Plotly::ASSET_PATH);
-    let scripts = match tpl.generate()
-    {
-      Some(stringy) => stringy,
-      None => return false
-    };
+    let scripts = tpl.generate()?;
     // header
-    if !tpl.load_section("header")
-    {
-      return false;
-    }
+    tpl.load_section("header")?;
     tpl.integrate("scripts", &scripts);
     tpl.tag("title", "Coronavirus cases worldwide");
-    let header = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    let header = tpl.generate()?;
     // graph
-    let graph = match self.generate_graph_world(db, &mut tpl)
-    {
-      Some(generated) => generated,
-      None => return false
-    };
-    let graph_accu = match self.generate_accumulated_graph_world(db, &mut tpl)
-    {
-      Some(generated) => generated,
-      None => return false
-    };
-    let graph = graph + "\n<br />\n" + &graph_accu;
+    let graph = self.generate_graph_world(db, &mut tpl)?;
+    let graph_accu = self.generate_accumulated_graph_world(db, &mut tpl)?;
+    let graph = graph + "\n<br />\n" + &graph_accu + footer;
     // full
-    if !tpl.load_section("full")
-    {
-      return false;
-    }
+    tpl.load_section("full")?;
     tpl.integrate("header", &header);
     tpl.integrate("content", &graph);
-    let full = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    let full = tpl.generate()?;
     // write it to a file
-    let file = format!("{}/world.html", self.config.output_directory);
-    let written = fs::write(file, full.as_bytes());
-    written.is_ok()
+    self.write_file("world.html", &full)
   }
 
   /**
@@ -385,72 +321,39 @@ impl Generator
   /**
    * Generates the HTML files for different continents.
    *
-   * @param db       reference to the Database instance
-   * @return Returns whether the generation was successful.
+   * @param db         reference to the Database instance
+   * @param continents names of the continents to generate pages for
+   * @param footer     HTML snippet that is shown at the bottom of every page
+   * @return Returns an Error, if the generation failed.
    */
-  fn generate_continents(&self, db: &Database) -> bool
+  fn generate_continents(&self, db: &Database, continents: &[String], footer: &str) -> Result<(), Error>
   {
     let mut tpl = Template::new();
-    if !self.load_template(&mut tpl)
-    {
-      return false;
-    }
+    self.load_template(&mut tpl)?;
 
-    let continents = db.continents();
     for continent in continents.iter()
     {
       // template: scripts
-      if !tpl.load_section("script")
-      {
-        return false;
-      }
+      tpl.load_section("script")?;
       tpl.tag("path", Plotly::ASSET_PATH);
-      let scripts = match tpl.generate()
-      {
-        Some(generated) => generated,
-        None => return false
-      };
+      let scripts = tpl.generate()?;
       // template: header
-      if !tpl.load_section("header")
-      {
-        return false;
-      }
+      tpl.load_section("header")?;
       tpl.integrate("scripts", &scripts);
       tpl.tag("title", &("Coronavirus incidence in ".to_owned() + continent));
-      let header = match tpl.generate()
-      {
-        Some(generated) => generated,
-        None => return false
-      };
+      let header = tpl.generate()?;
       // template: graph
-      let graph = self.generate_graph_continent(db, continent, &mut tpl);
-      let graph = match graph
-      {
-        Some(g) => g,
-        None => return false
-      };
+      let graph = self.generate_graph_continent(db, continent, &mut tpl)? + footer;
       // template: full
-      if !tpl.load_section("full")
-      {
-        return false;
-      }
+      tpl.load_section("full")?;
       tpl.integrate("header", &header);
       tpl.integrate("content", &graph);
-      let full = match tpl.generate()
-      {
-        Some(stuff) => stuff,
-        None => return false
-      };
+      let full = tpl.generate()?;
       // write it to a file
-      let file = format!("{}/continent_{}.html", self.config.output_directory, Self::sanitize_continent_name(continent));
-      let written = fs::write(file, full.as_bytes());
-      if written.is_err()
-      {
-        return false;
-      }
+      self.write_file(&("continent_".to_owned() + &Self::sanitize_continent_name(continent) + ".html"), &full)?;
     }
     // All is done here.
-    true
+    Ok(())
   }
 
   /**
@@ -460,20 +363,47 @@ impl Generator
    * @param country  country data (id, name, etc.)
    * @param tpl      loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Option<String>
+  fn generate_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Result<String, Error>
   {
     // load graph section
-    if !tpl.load_section("graph")
-    {
-      return None;
-    }
+    tpl.load_section("graph")?;
     tpl.tag("title", &("Coronavirus cases in ".to_owned() + &country.name
                    + " (" + &country.geo_id + ")"));
     tpl.tag("plotId", &("graph_".to_owned() + &country.geo_id.to_lowercase()));
     // prepare numbers
-    let data = db.numbers(&country.country_id);
+    let data = db.numbers(&country.country_id, &self.config.date_range)?;
+    // Weekly numbers are shown as sums per week, because single days with the
+    // numbers of a whole week would be drawn as spikes.
+    let data = match db.granularity(&country.country_id)?
+    {
+      Granularity::Daily =>
+      {
+        tpl.tag("y_axis_title", "Cases per day");
+        data
+      },
+      Granularity::Weekly =>
+      {
+        // Many countries reported daily before they switched to weekly
+        // reports, so the switch is searched in the whole history. If it
+        // cannot be found, all numbers are treated as weekly.
+        let history = db.numbers(&country.country_id, &DateRange::default())?;
+        let since = data::weekly_since(history.iter().map(|n| (n.date.as_str(), n.cases)))
+          .map(|date| date.format("%Y-%m-%d").to_string());
+        let split = since.as_ref().map_or(0, |since| data.partition_point(|n| &n.date < since));
+        let title = match since.filter(|_| split > 0)
+        {
+          None => String::from("Cases per week"),
+          Some(_) if split == data.len() => String::from("Cases per day"),
+          Some(since) => format!("Cases per day, per week since {since}")
+        };
+        tpl.tag("y_axis_title", &title);
+        let mut daily = data[..split].to_vec();
+        daily.extend(data::weekly_sums(&data[split..]));
+        daily
+      }
+    };
     let capacity = data.len();
     let mut dates: Vec<String> = Vec::with_capacity(capacity);
     let mut infections: Vec<String> = Vec::with_capacity(capacity);
@@ -518,19 +448,17 @@ impl Generator
    * @param db       reference to the Database instance
    * @param tpl      loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_graph_world(&self, db: &Database, tpl: &mut Template) -> Option<String>
+  fn generate_graph_world(&self, db: &Database, tpl: &mut Template) -> Result<String, Error>
   {
     // load graph section
-    if !tpl.load_section("graph")
-    {
-      return None;
-    }
+    tpl.load_section("graph")?;
     tpl.tag("title", "Coronavirus cases worldwide");
     tpl.tag("plotId", "graph_world");
+    tpl.tag("y_axis_title", "Cases per day");
     // prepare numbers
-    let data = db.numbers_world();
+    let data = db.numbers_world(&self.config.date_range)?;
     let capacity = data.len();
     let mut dates: Vec<String> = Vec::with_capacity(capacity);
     let mut infections: Vec<String> = Vec::with_capacity(capacity);
@@ -573,20 +501,17 @@ impl Generator
    * @param country  country data (id, name, etc.)
    * @param tpl      loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_accumulated_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Option<String>
+  fn generate_accumulated_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Result<String, Error>
   {
     // load graph section
-    if !tpl.load_section("graphAccumulated")
-    {
-      return None;
-    }
+    tpl.load_section("graphAccumulated")?;
     tpl.tag("title", &("Accumulated Coronavirus cases in ".to_owned()
                      + &country.name + " (" + &country.geo_id + ")"));
     tpl.tag("plotId", &("graph_accu_".to_owned() + &country.geo_id.to_lowercase()));
     // prepare numbers
-    let data = db.accumulated_numbers(&country.country_id);
+    let data = db.accumulated_numbers(&country.country_id, &self.config.date_range)?;
     let capacity = data.len();
     let mut dates: Vec<String> = Vec::with_capacity(capacity);
     let mut infections: Vec<String> = Vec::with_capacity(capacity);
@@ -628,19 +553,16 @@ impl Generator
    * @param db       reference to the Database instance
    * @param tpl      loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_accumulated_graph_world(&self, db: &Database, tpl: &mut Template) -> Option<String>
+  fn generate_accumulated_graph_world(&self, db: &Database, tpl: &mut Template) -> Result<String, Error>
   {
     // load graph section
-    if !tpl.load_section("graphAccumulated")
-    {
-      return None;
-    }
+    tpl.load_section("graphAccumulated")?;
     tpl.tag("title", "Accumulated Coronavirus cases worldwide");
     tpl.tag("plotId", "graph_world_accu");
     // prepare numbers
-    let data = db.accumulated_numbers_world();
+    let data = db.accumulated_numbers_world(&self.config.date_range)?;
     let capacity = data.len();
     let mut dates: Vec<String> = Vec::with_capacity(capacity);
     let mut infections: Vec<String> = Vec::with_capacity(capacity);
@@ -707,22 +629,19 @@ impl Generator
    * @param country  country data (id, name, etc.)
    * @param tpl      loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_incidence_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Option<String>
+  fn generate_incidence_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Result<String, Error>
   {
-    let data14 = db.incidence14(&country.country_id);
-    let data7 = db.incidence7(&country.country_id);
+    let data14 = db.incidence14(&country.country_id, &self.config.date_range)?;
+    let data7 = db.incidence7(&country.country_id, &self.config.date_range)?;
     // May be an empty array, if there is no known incidence.
     if data14.is_empty() && data7.is_empty()
     {
-      return Some(String::from(""));
+      return Ok(String::new());
     }
     // load graph section
-    if !tpl.load_section("graphIncidence")
-    {
-      return None;
-    }
+    tpl.load_section("graphIncidence")?;
     tpl.tag("title", &("Coronavirus: incidences in ".to_owned()
                      + &country.name + " (" + &country.geo_id + ")"));
     let (axis_title, hint_text) = match data14.is_empty()
@@ -798,21 +717,18 @@ impl Generator
    * @param country  country data (id, name, etc.)
    * @param tpl      loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_graph_incidence_by_year(&self, db: &Database, country: &Country, tpl: &mut Template) -> Option<String>
+  fn generate_graph_incidence_by_year(&self, db: &Database, country: &Country, tpl: &mut Template) -> Result<String, Error>
   {
-    let years = db.incidence7_by_year(&country.country_id);
+    let years = db.incidence7_by_year(&country.country_id, &self.config.date_range)?;
     // If there is only one year or no data at all, then there is no reason to make this graph.
     if years.len() <= 1
     {
-      return Some(String::new());
+      return Ok(String::new());
     }
     // load graph section
-    if !tpl.load_section("trace")
-    {
-      return None;
-    }
+    tpl.load_section("trace")?;
     let mut traces = String::new();
 
     // Keys in a map are not necessarily in sort order or insertion order, so
@@ -851,10 +767,7 @@ impl Generator
       traces = traces + &tpl.generate()?;
     }
     // template: graph
-    if !tpl.load_section("graphIncidenceByYear")
-    {
-      return None;
-    }
+    tpl.load_section("graphIncidenceByYear")?;
     tpl.integrate("traces", &traces);
     tpl.tag("plotId", &("graph_incidence_years_".to_owned() + &country.geo_id.to_lowercase()));
     tpl.tag("title", &("Coronavirus: 7-day incidence by year in ".to_owned()
@@ -862,6 +775,61 @@ impl Generator
     tpl.generate()
   }
 
+  /**
+   * Generates the HTML snippet containing the graph with the share of the
+   * population that is vaccinated, fully vaccinated and boosted.
+   *
+   * @param db       reference to the Database instance
+   * @param country  country data (id, name, etc.)
+   * @param tpl      loaded template instance of main.tpl
+   * @return Returns a string containing the HTML snippet, if the generation was successful.
+   *         Returns an empty string, if there are no vaccination numbers, if
+   *         the population is unknown or if the template has no section for
+   *         the graph, e. g. because it was written for an older version.
+   *         Returns an Error, if an error occurred.
+   */
+  fn generate_vaccination_graph(&self, db: &Database, country: &Country, tpl: &mut Template) -> Result<String, Error>
+  {
+    if country.population <= 0 || !tpl.has_section("graphVaccination")
+    {
+      return Ok(String::new());
+    }
+    let series = [
+      (Metric::PeopleVaccinated, "datesVaccinated", "vaccinated"),
+      (Metric::PeopleFullyVaccinated, "datesFullyVaccinated", "fullyVaccinated"),
+      (Metric::TotalBoosters, "datesBoosters", "boosters")
+    ];
+    let mut data = Vec::with_capacity(series.len());
+    for (metric, _, _) in series.iter()
+    {
+      data.push(db.metric(&country.country_id, *metric, &self.config.date_range)?);
+    }
+    if data.iter().all(|values| values.is_empty())
+    {
+      return Ok(String::new());
+    }
+    // load graph section
+    tpl.load_section("graphVaccination")?;
+    tpl.tag("title", &("Coronavirus: vaccinations in ".to_owned()
+                     + &country.name + " (" + &country.geo_id + ")"));
+    tpl.tag("plotId", &("graph_vaccination_".to_owned() + &country.geo_id.to_lowercase()));
+    let population = f64::from(country.population);
+    for ((_, dates_tag, shares_tag), values) in series.iter().zip(data.iter())
+    {
+      // Serializing plain strings and numbers into JSON cannot fail.
+      // graph: date values
+      let dates: Vec<&str> = values.iter().map(|v| v.date.as_str()).collect();
+      tpl.integrate(dates_tag, &serde_json::to_string(&dates).expect("dates are serializable"));
+      // graph: share of the population in percent, rounded to two decimals
+      let shares: Vec<f64> = values
+        .iter()
+        .map(|v| (v.value * 10000.0 / population).round() / 100.0)
+        .collect();
+      tpl.integrate(shares_tag, &serde_json::to_string(&shares).expect("numbers are serializable"));
+    }
+    tpl.generate()
+  }
+
   /**
    * Generates the HTML snippet containing the graph with 14-day incidence numbers of the continent.
    *
@@ -869,21 +837,18 @@ impl Generator
    * @param continent  name of the continent
    * @param tpl        loaded template instance of main.tpl
    * @return Returns a string containing the HTML snippet, if the generation was successful.
-   *         Returns None, if an error occurred.
+   *         Returns an Error, if an error occurred.
    */
-  fn generate_graph_continent(&self, db: &Database, continent: &str, tpl: &mut Template) -> Option<String>
+  fn generate_graph_continent(&self, db: &Database, continent: &str, tpl: &mut Template) -> Result<String, Error>
   {
     // load graph section
-    if !tpl.load_section("trace")
-    {
-      return None;
-    }
+    tpl.load_section("trace")?;
     let mut traces = String::new();
     // iterate over countries
-    let countries = db.countries_of_continent(continent);
-    for country in countries.iter()
+    let countries = db.countries_of_continent(continent)?;
+    for country in countries.iter().filter(|c| self.config.selection.includes(c))
     {
-      let data = db.incidence14(&country.country_id);
+      let data = db.incidence14(&country.country_id, &self.config.date_range)?;
       // May be an empty array, if there is no known incidence.
       if data.is_empty()
       {
@@ -918,10 +883,7 @@ impl Generator
       traces = traces + &tpl.generate()?;
     }
     // template: graph
-    if !tpl.load_section("graphContinent")
-    {
-      return None;
-    }
+    tpl.load_section("graphContinent")?;
     tpl.integrate("traces", &traces);
     tpl.tag("plotId", &("continent_".to_owned() + &continent.to_lowercase()));
     tpl.tag("title", &("Coronavirus: 14-day incidence in ".to_owned() + continent));
@@ -944,17 +906,12 @@ impl Generator
   /**
    * Creates any assets (i. e. library files) in the output directory.
    *
-   * @return Returns whether the operation was successful.
+   * @return Returns an Error, if the assets could not be created.
    */
-  fn create_assets(&self) -> bool
+  fn create_assets(&self) -> Result<(), Error>
   {
     let path = Path::new(&self.config.output_directory).join("assets");
-    let created = fs::create_dir_all(&path);
-    if let Err(error) = created
-    {
-      eprintln!("Error: Could not create directory {:?}: {error}", path);
-      return false;
-    }
+    fs::create_dir_all(&path).map_err(|e| Error::io(&path, e))?;
 
     self.copy_or_extract_plotly_js(&path)
   }
@@ -964,25 +921,18 @@ impl Generator
    * directory.
    *
    * @param assets_destination  destination directory for assets
-   * @return Returns true, if file was created successfully.
+   * @return Returns an Error, if the file could not be created.
    */
-  fn copy_or_extract_plotly_js(&self, assets_destination: &Path) -> bool
+  fn copy_or_extract_plotly_js(&self, assets_destination: &Path) -> Result<(), Error>
   {
     let plotly_origin = Generator::get_assets_path().join(Plotly::FILE_NAME);
     let plotly_destination = assets_destination.join(Plotly::FILE_NAME);
     if plotly_origin.exists()
     {
-      let cp_success = fs::copy(&plotly_origin, &plotly_destination);
-      return match cp_success
+      return match fs::copy(&plotly_origin, &plotly_destination)
       {
-        Ok(_bytes_written) => true,
-        Err(e) =>
-        {
-          eprintln!(
-            "Error: Could not copy asset file {plotly_origin:?} to {plotly_destination:?}: {e}"
-          );
-          false
-        }
+        Ok(_bytes_written) => Ok(()),
+        Err(e) => Err(Error::io(&plotly_destination, e))
       };
     }
 
@@ -995,37 +945,24 @@ impl Generator
    *
    * @param countries   array containing names and ids of the countries
    * @param countries   array containing names of the continents
-   * @return Returns whether the operation was successful.
+   * @param footer      HTML snippet that is shown at the bottom of the page
+   * @return Returns an Error, if the index could not be created.
    */
-  fn create_index(&self, countries: &[Country], continents: &[String]) -> bool
+  fn create_index(&self, countries: &[Country], continents: &[String], footer: &str) -> Result<(), Error>
   {
     let mut tpl = Template::new();
-    if !self.load_template(&mut tpl)
-    {
-      return false;
-    }
+    self.load_template(&mut tpl)?;
     // links
-    if !tpl.load_section("indexLink")
-    {
-      return false;
-    }
+    tpl.load_section("indexLink")?;
     // worldwide links + country links
     tpl.tag("url", "./world.html");
     tpl.tag("text", "All countries accumulated");
-    let mut links = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    let mut links = tpl.generate()?;
     for country in countries.iter()
     {
       tpl.tag("url", &("./".to_owned() + &country.geo_id.to_lowercase() + ".html"));
       tpl.tag("text", &(country.name.clone() + " (" + &country.geo_id + ")"));
-      links = match tpl.generate()
-      {
-        Some(generated) => links + &generated,
-        None => return false
-      };
+      links = links + &tpl.generate()?;
     }
     // continent links
     let mut continent_links = String::new();
@@ -1033,63 +970,29 @@ impl Generator
     {
       tpl.tag("url", &("./continent_".to_owned() + &Self::sanitize_continent_name(continent) + ".html"));
       tpl.tag("text", continent);
-      continent_links = match tpl.generate()
-      {
-        Some(generated) => continent_links + &generated,
-        None => return false
-      };
+      continent_links = continent_links + &tpl.generate()?;
     }
     // index template
-    if !tpl.load_section("index")
-    {
-      return false;
-    }
+    tpl.load_section("index")?;
     tpl.integrate("links", &links);
-    let mut content = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    let mut content = tpl.generate()?;
     // continent index template
-    if !tpl.load_section("indexContinents")
-    {
-      return false;
-    }
+    tpl.load_section("indexContinents")?;
     tpl.integrate("links", &continent_links);
-    content = match tpl.generate()
-    {
-      Some(generated) => content + "<br />\n" + &generated,
-      None => return false
-    };
+    content = content + "<br />\n" + &tpl.generate()? + footer;
     // main page template
     // -- header
-    if !tpl.load_section("header")
-    {
-      return false;
-    }
+    tpl.load_section("header")?;
     tpl.integrate("scripts", "");
     tpl.tag("title", "Corona worldwide");
-    let header = match tpl.generate()
-    {
-      Some(stuff) => stuff,
-      None => return false
-    };
+    let header = tpl.generate()?;
     // -- full template
-    if !tpl.load_section("full")
-    {
-      return false;
-    }
+    tpl.load_section("full")?;
     tpl.integrate("header", &header);
     tpl.integrate("content", &content);
-    let full = match tpl.generate()
-    {
-      Some(generated) => generated,
-      None => return false
-    };
+    let full = tpl.generate()?;
     // write it to a file
-    let file = format!("{}/index.html", self.config.output_directory);
-    let written = fs::write(file, full.as_bytes());
-    written.is_ok()
+    self.write_file("index.html", &full)
   }
 }
 
@@ -1097,6 +1000,8 @@ impl Generator
 mod tests
 {
   use super::*;
+  use crate::configuration::CountrySelection;
+  use crate::data::DateRange;
 
   /**
    * Gets the path to the corona.db file in data directory.
@@ -1124,10 +1029,14 @@ mod tests
     let config = HtmlConfiguration {
       db_path: get_sqlite_db_path(),
       output_directory: directory.to_str().unwrap().to_string(),
-      template_path: None
+      template_path: None,
+      graphs: Graph::ALL.to_vec(),
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      quiet: true
     };
     let gen = Generator::new(&config).unwrap();
-    assert!(gen.generate());
+    assert!(gen.generate().is_ok());
     // Check that some paths exists.
     assert!(directory.join("index.html").exists());
     assert!(directory.join("world.html").exists());
@@ -1137,6 +1046,12 @@ mod tests
     assert!(directory.join("el.html").exists());
     assert!(directory.join("ke.html").exists());
     assert!(directory.join("us.html").exists());
+    // Every page shows the date of the latest data.
+    for file in ["index.html", "world.html", "continent_asia.html", "de.html"]
+    {
+      let html = fs::read_to_string(directory.join(file)).unwrap();
+      assert!(html.contains("data as of 2020-12-14"));
+    }
     // clean up
     assert!(fs::remove_dir_all(directory).is_ok());
   }
@@ -1162,4 +1077,192 @@ mod tests
     assert_eq!(Generator::sanitize_continent_name("This is a peculiar näme för a cøntin€nt"), "this_is_a_peculiar_n_me_f_r_a_c_ntin_nt");
     assert_eq!(Generator::sanitize_continent_name("Slash/Slash\\"), "slash_slash_");
   }
+
+  #[test]
+  fn selection_of_countries()
+  {
+    use std::env;
+    use std::fs;
+
+    let directory = env::temp_dir().join("test_generation_of_selected_files");
+    let config = HtmlConfiguration {
+      db_path: get_sqlite_db_path(),
+      output_directory: directory.to_str().unwrap().to_string(),
+      template_path: None,
+      graphs: vec![Graph::Cases],
+      selection: CountrySelection {
+        countries: vec![String::from("DE"), String::from("FRA")],
+        ..Default::default()
+      },
+      date_range: DateRange::default(),
+      quiet: true
+    };
+    let gen = Generator::new(&config).unwrap();
+    assert!(gen.generate().is_ok());
+    assert!(directory.join("index.html").exists());
+    assert!(directory.join("de.html").exists());
+    assert!(directory.join("fr.html").exists());
+    assert!(directory.join("continent_europe.html").exists());
+    // Countries and continents outside the selection get no page.
+    assert!(!directory.join("cn.html").exists());
+    assert!(!directory.join("continent_asia.html").exists());
+    let index = fs::read_to_string(directory.join("index.html")).unwrap();
+    assert!(index.contains("./de.html"));
+    assert!(!index.contains("./us.html"));
+    assert!(!index.contains("continent_asia.html"));
+    // Only the graph of daily cases is on the country page.
+    let germany = fs::read_to_string(directory.join("de.html")).unwrap();
+    assert!(germany.contains("graph_de"));
+    assert!(!germany.contains("graph_accu_de"));
+    // clean up
+    assert!(fs::remove_dir_all(directory).is_ok());
+  }
+
+  #[test]
+  fn footer()
+  {
+    use crate::data::Provenance;
+
+    let config = HtmlConfiguration {
+      db_path: get_sqlite_db_path(),
+      output_directory: std::env::temp_dir().to_str().unwrap().to_string(),
+      template_path: None,
+      graphs: Graph::ALL.to_vec(),
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      quiet: true
+    };
+    let gen = Generator::new(&config).unwrap();
+    // The old database does not know its source.
+    let ecdc = Database::new(&get_sqlite_db_path()).unwrap();
+    let html = gen.generate_footer(&ecdc).unwrap();
+    assert!(html.contains("Data source: unknown &ndash; data as of 2020-12-14"));
+
+    let db_path = std::env::temp_dir().join("test_generation_footer.db");
+    let _ = std::fs::remove_file(&db_path);
+    let db = Database::create(db_path.to_str().unwrap()).unwrap();
+    let import = |format: &str, attribution: &str| Provenance {
+      format: format.to_string(),
+      attribution: attribution.to_string(),
+      file: String::from("data.csv"),
+      sha256: None,
+      imported_at: String::from("2026-10-18T12:34:56Z"),
+      version: String::from("0.20.1"),
+      rows_read: 0,
+      rows_kept: 0,
+      rows_skipped: 0
+    };
+    // Every source is only named once.
+    db.insert_provenance(&import("OWID", "Our World in Data")).unwrap();
+    db.insert_provenance(&import("JHU", "Johns & Hopkins")).unwrap();
+    db.insert_provenance(&import("OWID", "Our World in Data")).unwrap();
+    let html = gen.generate_footer(&db).unwrap();
+    assert!(html.contains("Data source: Our World in Data, Johns &amp; Hopkins &ndash; data as of unknown"));
+
+    drop(db);
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn vaccination_graph()
+  {
+    use crate::data::{MetricValue, Numbers};
+
+    let db_path = std::env::temp_dir().join("test_generation_vaccination.db");
+    let _ = std::fs::remove_file(&db_path);
+    let db = Database::create(db_path.to_str().unwrap()).unwrap();
+    let id = db.get_country_id_or_insert("XX", "Wonderland", &2000, "WON", "Utopia").unwrap();
+    let mut numbers = vec![Numbers { date: String::from("2021-06-01"), cases: 1, deaths: 0 }];
+    crate::db::save::numbers_into_db(&db, &id, &2000, &mut numbers).unwrap();
+    let value = |date: &str, metric: Metric, value: f64| MetricValue { date: date.to_string(), metric, value };
+    db.insert_metrics(&id, &[
+      value("2021-06-01", Metric::PeopleVaccinated, 500.0),
+      value("2021-06-02", Metric::PeopleVaccinated, 1001.0),
+      value("2021-06-02", Metric::PeopleFullyVaccinated, 300.0)
+    ]).unwrap();
+    let country = db.countries().unwrap().remove(0);
+
+    let config = HtmlConfiguration {
+      db_path: db_path.to_str().unwrap().to_string(),
+      output_directory: std::env::temp_dir().to_str().unwrap().to_string(),
+      template_path: None,
+      graphs: Graph::ALL.to_vec(),
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      quiet: true
+    };
+    let gen = Generator::new(&config).unwrap();
+    let mut tpl = Template::new();
+    assert!(gen.load_template(&mut tpl).is_ok());
+    let html = gen.generate_vaccination_graph(&db, &country, &mut tpl).unwrap();
+    assert!(html.contains("graph_vaccination_xx"));
+    assert!(html.contains("[\"2021-06-01\",\"2021-06-02\"]"));
+    assert!(html.contains("[25.0,50.05]"));
+    assert!(html.contains("[15.0]"));
+    // Booster doses are not in the database.
+    assert!(html.contains("x: [],"));
+
+    // Templates of older versions do not have the section.
+    let mut old = Template::new();
+    assert!(old.load_from_str("<!--section-start::full-->{{>content}}<!--section-end::full-->").is_ok());
+    assert_eq!(gen.generate_vaccination_graph(&db, &country, &mut old).unwrap(), "");
+    // Without vaccination numbers there is no graph.
+    let ecdc = Database::new(&get_sqlite_db_path()).unwrap();
+    let germany = ecdc.countries().unwrap().into_iter().find(|c| c.geo_id == "DE").unwrap();
+    assert_eq!(gen.generate_vaccination_graph(&ecdc, &germany, &mut tpl).unwrap(), "");
+
+    drop(db);
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
+
+  #[test]
+  fn graph_after_switch_to_weekly_reports()
+  {
+    use crate::data::Numbers;
+
+    let db_path = std::env::temp_dir().join("test_generation_weekly_switch.db");
+    let _ = std::fs::remove_file(&db_path);
+    let db = Database::create(db_path.to_str().unwrap()).unwrap();
+    let id = db.get_country_id_or_insert("XX", "Wonderland", &2000, "WON", "Utopia").unwrap();
+    // daily until 2022-01-10, weekly (on Sundays) from 2022-02-06 on
+    let mut numbers: Vec<Numbers> = (1..=10)
+      .map(|d| Numbers { date: format!("2022-01-{d:0>2}"), cases: 10, deaths: 0 })
+      .collect();
+    for date in ["2022-02-06", "2022-02-13", "2022-02-20", "2022-02-27", "2022-03-06", "2022-03-13"]
+    {
+      numbers.push(Numbers { date: date.to_string(), cases: 70, deaths: 1 });
+    }
+    crate::db::save::numbers_into_db(&db, &id, &2000, &mut numbers).unwrap();
+    assert_eq!(db.granularity(&(id as i32)).unwrap(), Granularity::Weekly);
+    let country = db.countries().unwrap().remove(0);
+
+    let mut config = HtmlConfiguration {
+      db_path: db_path.to_str().unwrap().to_string(),
+      output_directory: std::env::temp_dir().to_str().unwrap().to_string(),
+      template_path: None,
+      graphs: Graph::ALL.to_vec(),
+      selection: CountrySelection::default(),
+      date_range: DateRange::default(),
+      quiet: true
+    };
+    let gen = Generator::new(&config).unwrap();
+    let mut tpl = Template::new();
+    assert!(gen.load_template(&mut tpl).is_ok());
+    let html = gen.generate_graph(&db, &country, &mut tpl).unwrap();
+    // The daily numbers before the switch are kept as they are.
+    assert!(html.contains("Cases per day, per week since 2022-01-11"));
+    assert!(html.contains("[\"2022-01-01\",\"2022-01-02\","));
+    assert!(html.contains("\"2022-01-10\",\"2022-02-06\",\"2022-02-13\","));
+    assert!(html.contains("[10,10,10,10,10,10,10,10,10,10,70,70,70,70,70,70]"));
+
+    // Only weekly numbers are within the date range.
+    config.date_range = DateRange::new(Some(String::from("2022-02-01")), None).unwrap();
+    let gen = Generator::new(&config).unwrap();
+    let html = gen.generate_graph(&db, &country, &mut tpl).unwrap();
+    assert!(html.contains("Cases per week"));
+    assert!(html.contains("[\"2022-02-06\",\"2022-02-13\","));
+
+    drop(db);
+    assert!(std::fs::remove_file(db_path).is_ok());
+  }
 }
@@ -16,6 +16,7 @@
  -------------------------------------------------------------------------------
 */
 
+use crate::error::Error;
 use std::path::Path;
 
 pub struct Plotly
@@ -63,27 +64,20 @@ impl Plotly
    * Extracts the embedded minified plotly.js from the binary.
    *
    * @param destination  destination path for the .js file
-   * @return Returns true, if file was written successfully.
+   * @return Returns an Error, if the file could not be written.
    */
-  pub fn extract(destination: &Path) -> bool
+  pub fn extract(destination: &Path) -> Result<(), Error>
   {
     // Check SHA256 hash of the file.
     if !Plotly::check_hash(Self::PLOTLY_JS)
     {
-      eprintln!("Error: SHA256 hash of the embedded plotly.js does not match \
-                 the expected hash!");
-      return false;
+      return Err(Error::io(destination, std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "SHA256 hash of the embedded plotly.js does not match the expected hash!"
+      )));
     }
 
-    match std::fs::write(destination, Self::PLOTLY_JS)
-    {
-      Ok(()) => true,
-      Err(e) =>
-      {
-        eprintln!("Error while writing plotly.js file: {e}");
-        false
-      }
-    }
+    std::fs::write(destination, Self::PLOTLY_JS).map_err(|e| Error::io(destination, e))
   }
 }
 
@@ -104,7 +98,7 @@ mod tests
   {
     let destination = std::env::temp_dir().join(Plotly::FILE_NAME);
 
-    assert!(Plotly::extract(&destination));
+    assert!(Plotly::extract(&destination).is_ok());
     assert!(std::fs::remove_file(&destination).is_ok());
   }
 }
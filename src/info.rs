@@ -0,0 +1,358 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::configuration::InfoConfiguration;
+use crate::data::{CountryStatistics, Provenance};
+use crate::database::Database;
+use crate::error::Error;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub struct Info
+{
+  config: InfoConfiguration
+}
+
+/// summary of the content of a database
+#[derive(Serialize)]
+struct Summary
+{
+  /// format of the CSV file the database was created from, if known
+  source_format: Option<String>,
+  /// version of the database schema, zero for databases of older versions
+  schema_version: i64,
+  /// whether the columns totalCases and totalDeaths exist
+  #[serde(rename = "total_numbers")]
+  has_total_numbers: bool,
+  /// recorded imports of data, oldest first
+  imports: Vec<Provenance>,
+  /// date range and number of rows per country
+  countries: Vec<CountryStatistics>
+}
+
+impl Info
+{
+  /**
+   * Creates a new Info instance.
+   *
+   * @config   application configuration
+   * @return   Returns a Result containing the Info object, if successful.
+   *           Returns an Error, if the configuration seems to be invalid.
+   */
+  pub fn new(config: &InfoConfiguration) -> Result<Info, Error>
+  {
+    if config.db_path.is_empty()
+    {
+      return Err(Error::Configuration("Path to SQLite database must not be an empty string!".to_string()));
+    }
+
+    Ok(Info
+    {
+      config: InfoConfiguration
+      {
+        db_path: config.db_path.clone(),
+        json: config.json
+      }
+    })
+  }
+
+  /**
+   * Prints the summary of the database to standard output.
+   *
+   * @return Returns an Error, if the database could not be read.
+   */
+  pub fn show(&self) -> Result<(), Error>
+  {
+    let db = Database::new(&self.config.db_path)?;
+    let summary = Summary::from_db(&db)?;
+    match self.config.json
+    {
+      true => println!("{}", summary.to_json()),
+      false => print!("{}", summary.to_text())
+    }
+    Ok(())
+  }
+}
+
+impl Summary
+{
+  /**
+   * Collects the summary of a database.
+   *
+   * @param db   the database
+   * @return Returns the summary in case of success.
+   */
+  fn from_db(db: &Database) -> Result<Summary, Error>
+  {
+    Ok(Summary {
+      source_format: db.meta("source_format")?,
+      schema_version: db.schema_version()?,
+      has_total_numbers: db.has_total_numbers()?,
+      imports: db.provenance()?,
+      countries: db.country_statistics()?
+    })
+  }
+
+  /**
+   * Gets the earliest date with data over all countries.
+   *
+   * @return Returns the date, or None if there is no data at all.
+   */
+  fn first_date(&self) -> Option<&str>
+  {
+    self.countries.iter().filter_map(|c| c.first_date.as_deref()).min()
+  }
+
+  /**
+   * Gets the latest date with data over all countries.
+   *
+   * @return Returns the date, or None if there is no data at all.
+   */
+  fn last_date(&self) -> Option<&str>
+  {
+    self.countries.iter().filter_map(|c| c.last_date.as_deref()).max()
+  }
+
+  /**
+   * Counts the countries per continent.
+   *
+   * @return Returns a map from continent name to number of countries.
+   */
+  fn continents(&self) -> BTreeMap<&str, usize>
+  {
+    let mut result = BTreeMap::new();
+    for stats in self.countries.iter()
+    {
+      *result.entry(stats.country.continent.as_str()).or_insert(0) += 1;
+    }
+    result
+  }
+
+  /**
+   * Formats the summary as human-readable text.
+   *
+   * @return Returns the formatted summary.
+   */
+  fn to_text(&self) -> String
+  {
+    let mut text = format!(
+      "Source format: {}\n",
+      self.source_format.as_deref().unwrap_or("unknown")
+    );
+    text.push_str(&format!("Schema version: {}", self.schema_version));
+    if self.schema_version < Database::SCHEMA_VERSION
+    {
+      text.push_str(" (outdated, run the migrate subcommand to upgrade)");
+    }
+    text.push('\n');
+    text.push_str(&format!(
+      "Accumulated numbers (totalCases, totalDeaths): {}\n",
+      if self.has_total_numbers { "yes" } else { "no" }
+    ));
+    match (self.first_date(), self.last_date())
+    {
+      (Some(first), Some(last)) => text.push_str(&format!("Date range: {first} to {last}\n")),
+      _ => text.push_str("Date range: no data\n")
+    }
+    text.push_str("\nImports:\n");
+    if self.imports.is_empty()
+    {
+      text.push_str("  none recorded\n");
+    }
+    for import in self.imports.iter()
+    {
+      text.push_str(&format!(
+        "  {}: {} ({}) from {}, {} of {} rows imported, version {}\n",
+        import.imported_at, import.format, import.attribution, import.file,
+        import.rows_kept, import.rows_read, import.version
+      ));
+      if let Some(hash) = &import.sha256
+      {
+        text.push_str(&format!("    SHA-256: {hash}\n"));
+      }
+    }
+    text.push_str("\nCountries per continent:\n");
+    for (continent, count) in self.continents().iter()
+    {
+      let name = if continent.is_empty() { "(none)" } else { continent };
+      text.push_str(&format!("  {name}: {count}\n"));
+    }
+    text.push_str(&format!("  total: {}\n", self.countries.len()));
+    text.push_str("\nCountries:\n");
+    for stats in self.countries.iter()
+    {
+      let c = &stats.country;
+      match (&stats.first_date, &stats.last_date)
+      {
+        (Some(first), Some(last)) => text.push_str(&format!(
+          "  {} ({}): {first} to {last}, {} rows\n",
+          c.name, c.geo_id, stats.rows
+        )),
+        _ => text.push_str(&format!("  {} ({}): no data\n", c.name, c.geo_id))
+      }
+    }
+    text
+  }
+
+  /**
+   * Formats the summary as JSON.
+   *
+   * @return Returns the JSON-encoded summary.
+   */
+  fn to_json(&self) -> String
+  {
+    #[derive(Serialize)]
+    struct Json<'a>
+    {
+      #[serde(flatten)]
+      summary: &'a Summary,
+      first_date: Option<&'a str>,
+      last_date: Option<&'a str>,
+      continents: BTreeMap<&'a str, usize>
+    }
+
+    let json = Json {
+      summary: self,
+      first_date: self.first_date(),
+      last_date: self.last_date(),
+      continents: self.continents()
+    };
+    // Serialization of maps with string keys and of plain values cannot fail.
+    serde_json::to_string_pretty(&json).expect("summary contains only serializable values")
+  }
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  /**
+   * Gets the path to the corona.db file in data directory.
+   *
+   * @return Returns path of the SQLite database.
+   */
+  fn get_sqlite_db_path() -> String
+  {
+    use std::path::Path;
+
+    let db_path = Path::new(file!()) // current file: src/info.rs
+      .parent()
+      .unwrap() // parent: src/
+      .join("..") // up one directory
+      .join("data") // into directory data/
+      .join("corona-ecdc-2020-12-14.db"); // and to the corona-ecdc-2020-12-14.db file;
+    db_path.to_str().unwrap().to_string()
+  }
+
+  #[test]
+  fn summary()
+  {
+    let db = Database::new(&get_sqlite_db_path()).unwrap();
+    let summary = Summary::from_db(&db).unwrap();
+    // The shipped database was created before the source format was recorded.
+    assert!(summary.source_format.is_none());
+    assert_eq!(summary.schema_version, 0);
+    assert!(summary.imports.is_empty());
+    assert!(summary.countries.len() > 200);
+    let germany = summary.countries.iter().find(|c| c.country.geo_id == "DE");
+    assert!(germany.is_some());
+    let germany = germany.unwrap();
+    assert!(germany.rows > 300);
+    assert_eq!(germany.last_date, Some(String::from("2020-12-14")));
+    assert_eq!(summary.last_date(), Some("2020-12-14"));
+    assert!(summary.first_date().unwrap() <= germany.first_date.as_deref().unwrap());
+    let continents = summary.continents();
+    assert!(continents.contains_key("Europe"));
+    assert_eq!(continents.values().sum::<usize>(), summary.countries.len());
+  }
+
+  #[test]
+  fn text_and_json()
+  {
+    let summary = Summary {
+      source_format: Some(String::from("WHO")),
+      schema_version: 1,
+      has_total_numbers: false,
+      imports: vec![
+        Provenance {
+          format: String::from("WHO"),
+          attribution: String::from("World Health Organization (WHO)"),
+          file: String::from("WHO-COVID-19-global-data.csv"),
+          sha256: Some(String::from("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")),
+          imported_at: String::from("2026-10-18T12:34:56Z"),
+          version: String::from("0.20.1"),
+          rows_read: 32,
+          rows_kept: 31,
+          rows_skipped: 1
+        }
+      ],
+      countries: vec![
+        CountryStatistics {
+          country: crate::data::Country {
+            country_id: 1,
+            name: String::from("Wonderland"),
+            population: 42,
+            geo_id: String::from("XX"),
+            country_code: String::from("WON"),
+            continent: String::from("Utopia")
+          },
+          first_date: Some(String::from("2021-01-01")),
+          last_date: Some(String::from("2021-01-31")),
+          rows: 31
+        },
+        CountryStatistics {
+          country: crate::data::Country {
+            country_id: 2,
+            name: String::from("Neuland \"Web\""),
+            population: -1,
+            geo_id: String::from("NL"),
+            country_code: String::new(),
+            continent: String::from("Utopia")
+          },
+          first_date: None,
+          last_date: None,
+          rows: 0
+        }
+      ]
+    };
+    let text = summary.to_text();
+    assert!(text.contains("Source format: WHO\n"));
+    assert!(text.contains("Schema version: 1 (outdated, run the migrate subcommand to upgrade)\n"));
+    assert!(text.contains("Accumulated numbers (totalCases, totalDeaths): no\n"));
+    assert!(text.contains("Date range: 2021-01-01 to 2021-01-31\n"));
+    assert!(text.contains("  2026-10-18T12:34:56Z: WHO (World Health Organization (WHO)) from \
+                           WHO-COVID-19-global-data.csv, 31 of 32 rows imported, version 0.20.1\n"));
+    assert!(text.contains("    SHA-256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n"));
+    assert!(text.contains("  Utopia: 2\n"));
+    assert!(text.contains("  Wonderland (XX): 2021-01-01 to 2021-01-31, 31 rows\n"));
+    assert!(text.contains("  Neuland \"Web\" (NL): no data\n"));
+
+    let json: serde_json::Value = serde_json::from_str(&summary.to_json()).unwrap();
+    assert_eq!(json["source_format"], "WHO");
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["total_numbers"], false);
+    assert_eq!(json["first_date"], "2021-01-01");
+    assert_eq!(json["last_date"], "2021-01-31");
+    assert_eq!(json["imports"][0]["file"], "WHO-COVID-19-global-data.csv");
+    assert_eq!(json["imports"][0]["rows_skipped"], 1);
+    assert_eq!(json["continents"]["Utopia"], 2);
+    assert_eq!(json["countries"][0]["rows"], 31);
+    assert_eq!(json["countries"][1]["name"], "Neuland \"Web\"");
+    assert_eq!(json["countries"][1]["first_date"], serde_json::Value::Null);
+  }
+}
@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2020, 2021, 2023, 2024, 2025  Dirk Stolle
+    Copyright (C) 2020, 2021, 2023, 2024, 2025, 2026  Dirk Stolle
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
     the Free Software Foundation, either version 3 of the License, or
@@ -15,19 +15,61 @@
  -------------------------------------------------------------------------------
 */
 
+//! Generates HTML files with graphs of the Coronavirus case numbers from an
+//! SQLite database, and creates such databases from CSV files.
+//!
+//! Apart from the command line application, the crate can be used as a
+//! library to read the case numbers from an existing database:
+//!
+//! ```
+//! use corona::{Database, DateRange};
+//!
+//! # let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/corona-ecdc-2020-12-14.db");
+//! let db = Database::new(path)?;
+//! for country in db.countries()?.iter().filter(|c| c.continent == "Oceania")
+//! {
+//!   let numbers = db.numbers_with_incidence(&country.country_id, &DateRange::default())?;
+//!   println!("{}: {} days with data", country.name, numbers.len());
+//! }
+//! # Ok::<(), corona::Error>(())
+//! ```
+//!
+//! The items re-exported at the crate root as well as the modules `data`,
+//! `database` and `db` form the public library API. It follows semantic
+//! versioning of the crate version: as long as the major version is zero,
+//! incompatible changes of these items only happen when the minor version is
+//! increased.
+//! Everything else is an implementation detail of the application.
+
 mod checks;
 pub mod configuration;
 mod csv;
-mod data;
-mod database;
-mod db;
+pub mod data;
+pub mod database;
+pub mod db;
+mod error;
 mod generator;
+mod info;
 mod template;
+mod validate;
 mod world;
 
 use crate::configuration::*;
+pub use crate::data::{
+  Country, CountryStatistics, DailyRecord, DateRange, Granularity, Incidence14, Incidence7,
+  IncidenceWithDay, Metric, MetricValue, Numbers, NumbersAndIncidence, NumbersAndIncidenceAndTotals,
+  Provenance
+};
+pub use crate::database::Database;
+pub use crate::error::Error;
 
-pub fn run(op: &Operation) -> Result<(), String>
+/**
+ * Executes an operation that was created from the command line arguments.
+ *
+ * @param op   the operation to execute, see configuration::parse_args()
+ * @return Returns an Error, if the operation failed.
+ */
+pub fn run(op: &Operation) -> Result<(), Error>
 {
   match &op
   {
@@ -35,11 +77,11 @@ pub fn run(op: &Operation) -> Result<(), String>
     {
       use generator::Generator;
       let gen = Generator::new(config)?;
-      if !gen.generate()
+      gen.generate()?;
+      if !config.quiet
       {
-        return Err("Generation of HTML files failed!".to_string());
+        println!("Generation of HTML files was successful.");
       }
-      println!("Generation of HTML files was successful.");
       Ok(())
     },
     Operation::Csv(config) =>
@@ -47,23 +89,56 @@ pub fn run(op: &Operation) -> Result<(), String>
       use crate::csv::Csv;
 
       let csv = Csv::new(config)?;
-      if !csv.create_csv()
-      {
-        return Err("Failed to write CSV file!".to_string());
-      }
-
-      Ok(())
+      csv.create_csv()
     },
     Operation::Db(config) =>
     {
       use crate::db::Db;
 
       let db = Db::new(config)?;
-      if !db.create_db()
+      db.create_db()
+    },
+    Operation::Update(config) =>
+    {
+      use crate::db::Db;
+
+      Db::new(config)?.update_db()
+    },
+    Operation::Info(config) =>
+    {
+      use crate::info::Info;
+
+      Info::new(config)?.show()
+    },
+    Operation::Validate(config) =>
+    {
+      use crate::validate::Validator;
+
+      Validator::new(config)?.validate()
+    },
+    Operation::Merge(config) =>
+    {
+      use crate::db::DbMerge;
+
+      DbMerge::new(config)?.merge_db()
+    },
+    Operation::Migrate(config) =>
+    {
+      let db = Database::new(&config.db_path)?;
+      let (previous, current) = db.migrate(&!config.quiet)?;
+      if !config.quiet
       {
-        return Err("Failed to create SQLite database from CSV file!".to_string());
+        match previous == current
+        {
+          true => println!("The database already has the current schema version {current}."),
+          false => println!("The database was upgraded from schema version {previous} to {current}.")
+        }
       }
-
+      Ok(())
+    },
+    Operation::Help(program, topic) =>
+    {
+      println!("{}", help_text(program, topic));
       Ok(())
     },
     Operation::Version =>
@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2020, 2021, 2024, 2025  Dirk Stolle
+    Copyright (C) 2020, 2021, 2024, 2025, 2026  Dirk Stolle
 
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
@@ -24,25 +24,9 @@ fn main()
   let args: Vec<String> = env::args().collect();
 
   let config = corona::configuration::parse_args(&args).unwrap_or_else(|err| {
-    if !err.is_empty()
-    {
-      eprintln!("Error: {err}\n");
-    }
-    let basename = match std::path::Path::new(&args[0]).file_name()
-    {
-      // This conversion is getting really nasty here. Can Rust do better?
-      Some(name) => name.to_string_lossy().into_owned(),
-      None => args[0].clone()
-    };
-    eprintln!(
-      "Usage: {basename} html /path/to/corona.db /path/to/output/directory [/path/to/main.tpl]"
-    );
-    eprintln!("           or");
-    eprintln!("Usage: {basename} csv /path/to/corona.db /path/to/output.csv");
-    eprintln!("           or");
-    eprintln!("Usage: {basename} db /path/to/input.csv /path/to/output.db");
-    eprintln!("           or");
-    eprintln!("Usage: {basename} version");
+    eprintln!("Error: {err}\n");
+    let basename = corona::configuration::program_name(&args[0]);
+    eprintln!("{}", corona::configuration::help_text(&basename, ""));
     process::exit(1);
   });
 
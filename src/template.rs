@@ -17,6 +17,7 @@
 
 mod html;
 
+use crate::error::Error;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -53,19 +54,11 @@ impl Template
    * Loads template from a file.
    *
    * @param path  path of the file to load
-   * @return true, if template was loaded successfully; false otherwise
+   * @return Returns an Error, if the template could not be loaded.
    */
-  pub fn load_from_file(&mut self, path: &Path) -> bool
+  pub fn load_from_file(&mut self, path: &Path) -> Result<(), Error>
   {
-    if !path.is_file() || !path.exists()
-    {
-      return false;
-    }
-    let content = match std::fs::read_to_string(path)
-    {
-      Ok(s) => s,
-      Err(_) => return false
-    };
+    let content = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
     self.load_from_str(&content)
   }
 
@@ -73,13 +66,13 @@ impl Template
    * Loads template from a string slice.
    *
    * @param content  content of the template
-   * @return true, if template was loaded successfully; false otherwise
+   * @return Returns an Error, if the template contains no sections.
    */
-  pub fn load_from_str(&mut self, content: &str) -> bool
+  pub fn load_from_str(&mut self, content: &str) -> Result<(), Error>
   {
     if content.trim().is_empty()
     {
-      return false;
+      return Err(Error::Template(String::from("The template is empty.")));
     }
 
     let pattern = "<!--section-start::(.*?)-->(.*?)<!--section-end::(.*?)-->";
@@ -92,7 +85,7 @@ impl Template
       .unwrap();
     if re.captures_len() == 0
     {
-      return false;
+      return Err(Error::Template(String::from("The template pattern is invalid.")));
     }
     self.sections.clear();
     for cap in re.captures_iter(content)
@@ -110,33 +103,43 @@ impl Template
     self.tags.clear();
     self.includes.clear();
     self.template = None;
-    !self.sections.is_empty()
+    if self.sections.is_empty()
+    {
+      return Err(Error::Template(String::from("The template does not contain any sections.")));
+    }
+    Ok(())
   }
 
   /**
    * Loads a section of the template.
    *
    * @param section_name  name of the section to load
-   * @return true if section was loaded, false otherwise
+   * @return Returns Error::TemplateSectionMissing, if there is no such section.
    */
-  pub fn load_section(&mut self, section_name: &str) -> bool
+  pub fn load_section(&mut self, section_name: &str) -> Result<(), Error>
   {
     match self.sections.get(section_name)
     {
       Some(content) =>
       {
         self.template = Some(content.to_string());
-        true
+        Ok(())
       },
-      None =>
-      {
-        eprintln!("There is no section '{section_name}'.");
-        eprintln!("sections: {:?}", self.sections.keys());
-        false
-      }
+      None => Err(Error::TemplateSectionMissing(section_name.to_string()))
     }
   }
 
+  /**
+   * Checks whether the template contains a section.
+   *
+   * @param section_name  name of the section
+   * @return Returns true, if there is such a section.
+   */
+  pub fn has_section(&self, section_name: &str) -> bool
+  {
+    self.sections.contains_key(section_name)
+  }
+
   /**
    * Sets the replacements text for a tag.
    * HTML entities in replacement are escaped during template generation.
@@ -174,13 +177,12 @@ impl Template
    *
    * @return the template with all replacement values filled
    */
-  pub fn generate(&self) -> Option<String>
+  pub fn generate(&self) -> Result<String, Error>
   {
-    #[allow(clippy::question_mark)]
     let mut out = match &self.template
     {
       Some(val) => val.clone(),
-      None => return None
+      None => return Err(Error::Template(String::from("No section has been loaded.")))
     };
     for (tag_name, tag_value) in self.tags.iter()
     {
@@ -192,7 +194,7 @@ impl Template
       let find: String = String::from(INTEGRATE_OPENER) + inc_name + INTEGRATE_CLOSER;
       out = out.replace(&find, inc_value);
     }
-    Some(out)
+    Ok(out)
   }
 }
 
@@ -245,7 +247,7 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
+    assert!(tpl.load_from_file(&path).is_ok());
     assert_eq!(1, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
     assert_eq!(
@@ -264,7 +266,7 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
+    assert!(tpl.load_from_file(&path).is_ok());
     assert_eq!(1, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
     assert_eq!(
@@ -283,7 +285,7 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
+    assert!(tpl.load_from_file(&path).is_ok());
 
     assert_eq!(2, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
@@ -308,7 +310,7 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
+    assert!(tpl.load_from_file(&path).is_ok());
 
     assert_eq!(2, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
@@ -332,7 +334,7 @@ mod tests
       "<!--section-start::test--><li><a href=\"{{url}}\">{{text}}</a></li><!--section-end::test-->";
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_str(&simple_template));
+    assert!(tpl.load_from_str(&simple_template).is_ok());
     assert_eq!(1, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
     assert_eq!(
@@ -347,7 +349,7 @@ mod tests
     let simple_template = "<!--section-start::test--><li>\n  <a href=\"{{url}}\">{{text}}</a>\r\n</li><!--section-end::test-->";
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_str(&simple_template));
+    assert!(tpl.load_from_str(&simple_template).is_ok());
     assert_eq!(1, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
     assert_eq!(
@@ -362,7 +364,7 @@ mod tests
     let simple_template = "<!--section-start::test--><a href=\"{{url}}\">{{text}}</a><!--section-end::test-->\n<!--section-start::foo--><b>Info:</b> {{info}}<!--section-end::foo-->";
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_str(&simple_template));
+    assert!(tpl.load_from_str(&simple_template).is_ok());
 
     assert_eq!(2, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
@@ -383,7 +385,7 @@ mod tests
     let simple_template = "<!--section-start::test--><li>\n  <a href=\"{{url}}\">{{text}}</a>\r\n</li><!--section-end::test-->\n<!--section-start::foo--><b>Info\nFoo\r\nBar\rBaz:</b> {{info}}<!--section-end::foo-->";
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_str(&simple_template));
+    assert!(tpl.load_from_str(&simple_template).is_ok());
 
     assert_eq!(2, tpl.sections.len());
     assert!(tpl.sections.contains_key("test"));
@@ -398,6 +400,16 @@ mod tests
     );
   }
 
+  #[test]
+  fn load_from_str_without_sections()
+  {
+    let mut tpl = Template::new();
+    assert!(matches!(tpl.load_from_str(""), Err(Error::Template(_))));
+    assert!(matches!(tpl.load_from_str("<p>no sections here</p>"), Err(Error::Template(_))));
+    // Nothing is loaded, so nothing can be generated.
+    assert!(tpl.generate().is_err());
+  }
+
   #[test]
   fn load_section()
   {
@@ -407,13 +419,16 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
+    assert!(tpl.load_from_file(&path).is_ok());
 
     // Section "test" should load just fine.
-    assert!(tpl.load_section("test"));
+    assert!(tpl.has_section("test"));
+    assert!(tpl.load_section("test").is_ok());
     assert_eq!(Some(String::from("<a href=\"{{url}}\">{{text}}</a>")), tpl.template);
     // Section "something_else" does not exist, it should not be loaded.
-    assert!(!tpl.load_section("something_else"));
+    assert!(!tpl.has_section("something_else"));
+    assert!(matches!(tpl.load_section("something_else"),
+                     Err(Error::TemplateSectionMissing(name)) if name == "something_else"));
 
     fs::remove_file(path).expect("Unable to delete template file!");
   }
@@ -427,12 +442,12 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
-    assert!(tpl.load_section("test"));
+    assert!(tpl.load_from_file(&path).is_ok());
+    assert!(tpl.load_section("test").is_ok());
     tpl.tag("url", "http://localhost/");
     tpl.tag("text", "home");
 
-    assert_eq!(Some(String::from("<a href=\"http://localhost/\">home</a>")), tpl.generate());
+    assert_eq!(Some(String::from("<a href=\"http://localhost/\">home</a>")), tpl.generate().ok());
 
     fs::remove_file(path).expect("Unable to delete template file!");
   }
@@ -446,15 +461,15 @@ mod tests
     fs::write(&path, simple_template).expect("Unable to write template file for test!");
 
     let mut tpl = Template::new();
-    assert!(tpl.load_from_file(&path));
-    assert!(tpl.load_section("test"));
+    assert!(tpl.load_from_file(&path).is_ok());
+    assert!(tpl.load_section("test").is_ok());
     tpl.tag("url", "http://localhost/");
     tpl.tag("text", "<< foo & 'bar' >>");
 
     // Text should be escaped properly.
     assert_eq!(
       Some(String::from("<a href=\"http://localhost/\">&lt;&lt; foo &amp; &#39;bar&#39; &gt;&gt;</a>")),
-      tpl.generate());
+      tpl.generate().ok());
 
     fs::remove_file(path).expect("Unable to delete template file!");
   }
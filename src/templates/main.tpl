@@ -43,7 +43,7 @@
     },
     yaxis: {
       title: {
-        text: 'Cases per day'
+        text: '{{y_axis_title}}'
       }
     }
   };
@@ -161,6 +161,48 @@
 <div style="text-align: center; font-style: italic;">The 7-day incidence is the number of infections per 100000 inhabitants over the last seven days.</div>
 <!--section-end::graphIncidenceByYear-->
 
+<!--section-start::graphVaccination-->
+<div id="{{plotId}}"> </div>
+<script>
+  var traces = [];
+
+  traces.push({
+      x: {{>datesVaccinated}},
+      y: {{>vaccinated}},
+      type: 'scatter',
+      name: 'At least one dose'
+  });
+  traces.push({
+      x: {{>datesFullyVaccinated}},
+      y: {{>fullyVaccinated}},
+      type: 'scatter',
+      name: 'Fully vaccinated'
+  });
+  traces.push({
+      x: {{>datesBoosters}},
+      y: {{>boosters}},
+      type: 'scatter',
+      name: 'Booster doses'
+  });
+  var layout = {
+    title: {
+      text: '{{title}}'
+    },
+    yaxis: {
+      title: {
+        text: 'Share of the population in %'
+      }
+    }
+  };
+  Plotly.newPlot('{{plotId}}', traces, layout, {
+      displaylogo: false,
+      modeBarButtonsToRemove: ['sendDataToCloud']
+  });
+</script>
+<br />
+<div style="text-align: center; font-style: italic;">Booster doses are counted per dose, so people with several booster doses are counted more than once.</div>
+<!--section-end::graphVaccination-->
+
 <!--section-start::graphContinent-->
 <div id="{{plotId}}"> </div>
 <script>
@@ -206,5 +248,9 @@
 <ul>{{>links}}</ul>
 <!--section-end::indexContinents-->
 
+<!--section-start::footer--><br />
+<div style="text-align: center; font-size: small;">Data source: {{attribution}} &ndash; data as of {{asOf}}</div>
+<!--section-end::footer-->
+
 <!--section-start::indexLink--><li><a href="{{url}}">{{text}}</a></li>
 <!--section-end::indexLink-->
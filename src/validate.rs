@@ -0,0 +1,432 @@
+/*
+ -------------------------------------------------------------------------------
+    This file is part of the Corona numbers website generator.
+    Copyright (C) 2026  Dirk Stolle
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ -------------------------------------------------------------------------------
+*/
+
+use crate::configuration::ValidateConfiguration;
+use crate::data::{CountryStatistics, DailyRecord, Granularity};
+use crate::database::Database;
+use crate::error::Error;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// severity of a problem found during validation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity
+{
+  /// suspicious data that may still be correct, e. g. negative daily numbers
+  Warning,
+
+  /// data that is definitely inconsistent
+  Error
+}
+
+/// a single problem found during validation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem
+{
+  pub severity: Severity,
+  pub message: String
+}
+
+pub struct Validator
+{
+  config: ValidateConfiguration
+}
+
+impl Validator
+{
+  /**
+   * Creates a new Validator instance.
+   *
+   * @config   application configuration
+   * @return   Returns a Result containing the Validator object, if successful.
+   *           Returns an Error, if the configuration seems to be invalid.
+   */
+  pub fn new(config: &ValidateConfiguration) -> Result<Validator, Error>
+  {
+    if config.db_path.is_empty()
+    {
+      return Err(Error::Configuration("Path to SQLite database must not be an empty string!".to_string()));
+    }
+
+    Ok(Validator
+    {
+      config: ValidateConfiguration
+      {
+        db_path: config.db_path.clone(),
+        strict: config.strict,
+        quiet: config.quiet
+      }
+    })
+  }
+
+  /**
+   * Checks the database and prints all problems that were found.
+   *
+   * @return Returns an Error::InvalidData, if the database contains errors
+   *         (or warnings, when running in strict mode).
+   *         Returns another Error, if the database could not be read.
+   */
+  pub fn validate(&self) -> Result<(), Error>
+  {
+    let db = Database::new(&self.config.db_path)?;
+    let problems = check_database(&db)?;
+    let errors = problems.iter().filter(|p| p.severity == Severity::Error).count();
+    let warnings = problems.len() - errors;
+    for problem in problems.iter()
+    {
+      match problem.severity
+      {
+        Severity::Error => println!("Error: {}", problem.message),
+        Severity::Warning if !self.config.quiet => println!("Warning: {}", problem.message),
+        Severity::Warning => ()
+      }
+    }
+    if !self.config.quiet || errors > 0
+    {
+      println!("Found {errors} error(s) and {warnings} warning(s).");
+    }
+
+    let failures = match self.config.strict
+    {
+      true => errors + warnings,
+      false => errors
+    };
+    if failures > 0
+    {
+      return Err(Error::InvalidData(format!(
+        "Validation of {} failed with {failures} problem(s)!",
+        self.config.db_path
+      )));
+    }
+    Ok(())
+  }
+}
+
+/**
+ * Runs all checks against the tables country and covid19.
+ *
+ * @param db   the database to check
+ * @return Returns the list of problems, which is empty for a valid database.
+ */
+pub fn check_database(db: &Database) -> Result<Vec<Problem>, Error>
+{
+  let countries = db.country_statistics()?;
+  let records = db.daily_records()?;
+  let granularities = db.granularities()?;
+  let mut problems = check_countries(&countries);
+  problems.extend(check_records(&countries, &granularities, &records));
+  Ok(problems)
+}
+
+/**
+ * Checks the table country for missing or duplicate geo ids.
+ *
+ * @param countries   all countries of the database
+ * @return Returns the problems that were found.
+ */
+fn check_countries(countries: &[CountryStatistics]) -> Vec<Problem>
+{
+  let mut problems = Vec::new();
+  let mut seen: HashMap<&str, &str> = HashMap::new();
+  for stats in countries.iter()
+  {
+    let c = &stats.country;
+    if c.geo_id.is_empty()
+    {
+      problems.push(Problem {
+        severity: Severity::Error,
+        message: format!("Country '{}' (id {}) has no geoId.", c.name, c.country_id)
+      });
+      continue;
+    }
+    if let Some(other) = seen.insert(&c.geo_id, &c.name)
+    {
+      problems.push(Problem {
+        severity: Severity::Error,
+        message: format!("The geoId {} is used by '{other}' and by '{}'.", c.geo_id, c.name)
+      });
+    }
+  }
+  problems
+}
+
+/**
+ * Checks the rows of the covid19 table for duplicates, gaps, negative numbers
+ * and accumulated numbers that do not match the daily numbers.
+ *
+ * @param countries       all countries of the database
+ * @param granularities   granularity of the countries that is not daily
+ * @param records         all rows of the covid19 table, sorted by country and date
+ * @return Returns the problems that were found.
+ */
+fn check_records(countries: &[CountryStatistics], granularities: &HashMap<i32, Granularity>, records: &[DailyRecord]) -> Vec<Problem>
+{
+  let names: HashMap<i32, &str> = countries
+    .iter()
+    .map(|c| (c.country.country_id, c.country.name.as_str()))
+    .collect();
+  let mut problems = Vec::new();
+  let mut add_error = |message: String| problems.push(Problem { severity: Severity::Error, message });
+  let mut warnings = Vec::new();
+  let mut add_warning = |message: String| warnings.push(Problem { severity: Severity::Warning, message });
+
+  let mut start = 0;
+  while start < records.len()
+  {
+    // Records are sorted by country, so all rows of a country are adjacent.
+    let country_id = records[start].country_id;
+    let end = records[start..]
+      .iter()
+      .position(|r| r.country_id != country_id)
+      .map_or(records.len(), |len| start + len);
+    let group = &records[start..end];
+    start = end;
+    let name = match names.get(&country_id)
+    {
+      Some(name) => *name,
+      None =>
+      {
+        add_error(format!(
+          "{} row(s) belong to the country id {country_id}, which does not exist in the table country.",
+          group.len()
+        ));
+        continue;
+      }
+    };
+    // Weekly numbers may have one row per week, so gaps of up to six days
+    // between the rows are expected.
+    let expected_gap = match granularities.get(&country_id).copied().unwrap_or_default()
+    {
+      Granularity::Daily => 0,
+      Granularity::Weekly => 6
+    };
+    let mut previous: Option<(&str, NaiveDate)> = None;
+    let mut sum_cases: i64 = 0;
+    let mut sum_deaths: i64 = 0;
+    let mut check_totals = true;
+    for record in group.iter()
+    {
+      let date = match NaiveDate::parse_from_str(&record.date, "%Y-%m-%d")
+      {
+        Ok(d) => d,
+        Err(_) =>
+        {
+          add_error(format!("{name}: '{}' is not a valid date.", record.date));
+          check_totals = false;
+          continue;
+        }
+      };
+      if let Some((previous_str, previous_date)) = previous
+      {
+        if previous_date == date
+        {
+          add_error(format!("{name}: there is more than one row for {}.", record.date));
+          // Accumulated numbers cannot be checked reliably with duplicates.
+          check_totals = false;
+        }
+        else
+        {
+          let missing = (date - previous_date).num_days() - 1;
+          if missing > expected_gap
+          {
+            add_warning(format!(
+              "{name}: no data for {missing} day(s) between {previous_str} and {}.",
+              record.date
+            ));
+          }
+        }
+      }
+      previous = Some((&record.date, date));
+      if record.cases < 0
+      {
+        add_warning(format!("{name}: negative number of cases ({}) on {}.", record.cases, record.date));
+      }
+      if record.deaths < 0
+      {
+        add_warning(format!("{name}: negative number of deaths ({}) on {}.", record.deaths, record.date));
+      }
+      sum_cases += i64::from(record.cases);
+      sum_deaths += i64::from(record.deaths);
+      if !check_totals
+      {
+        continue;
+      }
+      // Totals are None for all rows, if they have not been calculated yet.
+      let totals = (record.total_cases.map(i64::from), record.total_deaths.map(i64::from));
+      if totals != (None, None) && totals != (Some(sum_cases), Some(sum_deaths))
+      {
+        let show = |v: Option<i64>| v.map_or(String::from("NULL"), |n| n.to_string());
+        add_error(format!(
+          "{name}: the accumulated numbers on {} ({} cases, {} deaths) do not match \
+           the sum of the daily numbers ({sum_cases} cases, {sum_deaths} deaths).",
+          record.date, show(totals.0), show(totals.1)
+        ));
+        // One mismatch is enough, all following days are usually off, too.
+        check_totals = false;
+      }
+    }
+  }
+
+  problems.extend(warnings);
+  problems
+}
+
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+  use crate::data::Country;
+
+  /**
+   * Gets the path to the corona.db file in data directory.
+   *
+   * @return Returns path of the SQLite database.
+   */
+  fn get_sqlite_db_path() -> String
+  {
+    use std::path::Path;
+
+    let db_path = Path::new(file!()) // current file: src/validate.rs
+      .parent()
+      .unwrap() // parent: src/
+      .join("..") // up one directory
+      .join("data") // into directory data/
+      .join("corona-ecdc-2020-12-14.db"); // and to the corona-ecdc-2020-12-14.db file;
+    db_path.to_str().unwrap().to_string()
+  }
+
+  /**
+   * Creates statistics for a country without any data.
+   */
+  fn country(country_id: i32, name: &str, geo_id: &str) -> CountryStatistics
+  {
+    CountryStatistics {
+      country: Country {
+        country_id,
+        name: name.to_string(),
+        population: 1000,
+        geo_id: geo_id.to_string(),
+        country_code: String::new(),
+        continent: String::from("Europe")
+      },
+      first_date: None,
+      last_date: None,
+      rows: 0
+    }
+  }
+
+  /**
+   * Creates a daily record without deaths.
+   */
+  fn record(country_id: i32, date: &str, cases: i32, totals: Option<(i32, i32)>) -> DailyRecord
+  {
+    DailyRecord {
+      country_id,
+      date: date.to_string(),
+      cases,
+      deaths: 0,
+      total_cases: totals.map(|t| t.0),
+      total_deaths: totals.map(|t| t.1)
+    }
+  }
+
+  #[test]
+  fn shipped_database_has_no_errors()
+  {
+    let db = Database::new(&get_sqlite_db_path()).unwrap();
+    let problems = check_database(&db).unwrap();
+    assert!(problems.iter().all(|p| p.severity == Severity::Warning));
+    // The ECDC data contains some corrections with negative daily numbers.
+    assert!(problems.iter().any(|p| p.message.contains("negative number of cases")));
+  }
+
+  #[test]
+  fn countries_without_or_with_duplicate_geo_id()
+  {
+    let countries = vec![
+      country(1, "Wonderland", "XX"),
+      country(2, "Neuland", ""),
+      country(3, "Utopia", "XX")
+    ];
+    let problems = check_countries(&countries);
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].message.contains("Neuland"));
+    assert!(problems[1].message.contains("'Wonderland' and by 'Utopia'"));
+  }
+
+  #[test]
+  fn valid_records()
+  {
+    let countries = vec![country(1, "Wonderland", "XX")];
+    let records = vec![
+      record(1, "2021-01-01", 5, Some((5, 0))),
+      record(1, "2021-01-02", 3, Some((8, 0))),
+      record(1, "2021-01-03", 0, Some((8, 0)))
+    ];
+    assert!(check_records(&countries, &HashMap::new(), &records).is_empty());
+    // Totals that have not been calculated yet are no problem.
+    let records = vec![
+      record(1, "2021-01-01", 5, None),
+      record(1, "2021-01-02", 3, None)
+    ];
+    assert!(check_records(&countries, &HashMap::new(), &records).is_empty());
+  }
+
+  #[test]
+  fn problematic_records()
+  {
+    let countries = vec![country(1, "Wonderland", "XX"), country(2, "Neuland", "NL")];
+    let records = vec![
+      record(1, "2021-01-01", 5, Some((5, 0))),
+      record(1, "2021-01-04", -1, Some((4, 0))),
+      record(1, "2021-01-04", 2, Some((6, 0))),
+      record(2, "2021-01-01", 5, Some((5, 0))),
+      record(2, "2021-01-02", 5, Some((7, 0))),
+      record(2, "2021-01-03", 5, Some((9, 0))),
+      record(3, "2021-01-01", 1, None),
+      record(3, "2021-01-02", 1, None)
+    ];
+    let problems = check_records(&countries, &HashMap::new(), &records);
+    let errors: Vec<&Problem> = problems.iter().filter(|p| p.severity == Severity::Error).collect();
+    let warnings: Vec<&Problem> = problems.iter().filter(|p| p.severity == Severity::Warning).collect();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].message, "Wonderland: there is more than one row for 2021-01-04.");
+    assert!(errors[1].message.starts_with("Neuland: the accumulated numbers on 2021-01-02"));
+    assert!(errors[2].message.starts_with("2 row(s) belong to the country id 3"));
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].message, "Wonderland: no data for 2 day(s) between 2021-01-01 and 2021-01-04.");
+    assert_eq!(warnings[1].message, "Wonderland: negative number of cases (-1) on 2021-01-04.");
+  }
+
+  #[test]
+  fn weekly_records()
+  {
+    let countries = vec![country(1, "Wonderland", "XX")];
+    let records = vec![
+      record(1, "2021-01-03", 5, None),
+      record(1, "2021-01-10", 3, None),
+      record(1, "2021-01-24", 4, None)
+    ];
+    let granularities = HashMap::from([(1, Granularity::Weekly)]);
+    let problems = check_records(&countries, &granularities, &records);
+    // Only the missing week is a gap.
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].message, "Wonderland: no data for 13 day(s) between 2021-01-10 and 2021-01-24.");
+    // Daily numbers get a warning for every gap.
+    assert_eq!(check_records(&countries, &HashMap::new(), &records).len(), 2);
+  }
+}
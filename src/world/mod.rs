@@ -1,7 +1,7 @@
 /*
  -------------------------------------------------------------------------------
     This file is part of the Corona numbers website generator.
-    Copyright (C) 2021, 2022, 2023, 2024, 2025  Dirk Stolle
+    Copyright (C) 2021, 2022, 2023, 2024, 2025, 2026  Dirk Stolle
 
     This program is free software: you can redistribute it and/or modify
     it under the terms of the GNU General Public License as published by
@@ -2054,6 +2054,18 @@ impl World
   {
     self.all_countries.iter().find(|c| c.country_code == code)
   }
+
+  /**
+   * Finds a country by its English name.
+   *
+   * Note that the search is case-insensitive, i. e. "germany" finds Germany.
+   * @return Returns the Country, if a match was found.
+   *         Returns None, if no match was found.
+   */
+  pub fn find_by_name(&self, name: &str) -> Option<&Country>
+  {
+    self.all_countries.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+  }
 }
 
 #[cfg(test)]
@@ -2107,6 +2119,16 @@ mod tests
     assert!(found.is_none());
   }
 
+  #[test]
+  fn find_by_name()
+  {
+    let the_world = World::new();
+    let found = the_world.find_by_name("croatia");
+    assert!(found.is_some());
+    assert_eq!(found.unwrap().geo_id, "HR");
+    assert!(the_world.find_by_name("Atlantis").is_none());
+  }
+
   #[test]
   fn find_by_country_code_montserrat_correct_code()
   {